@@ -15,6 +15,7 @@ echo "Build completed successfully!"
 if [ "$1" = "install" ]; then
     echo "Installing rustblue..."
     sudo cp target/release/rustblue /usr/local/bin/
+    sudo cp target/release/rustblue-pairing-helper /usr/local/bin/
     sudo cp data/org.rustblue.Manager.desktop /usr/share/applications/
     echo "Installation completed!"
 fi
@@ -0,0 +1,81 @@
+#!/usr/bin/env python3
+"""Regenerate the embedded vendor tables in src/bluetooth/vendor/.
+
+Works offline from two registry files downloaded beforehand:
+
+  company_identifiers.yaml  Bluetooth SIG assigned numbers,
+                            https://bitbucket.org/bluetooth-SIG/public/src/main/assigned_numbers/company_identifiers/
+  oui.csv                   IEEE MA-L registry,
+                            https://standards-oui.ieee.org/oui/oui.csv
+
+Usage: scripts/generate-vendor-tables.py company_identifiers.yaml oui.csv
+"""
+
+import csv
+import re
+import sys
+from pathlib import Path
+
+OUTPUT_DIR = Path(__file__).resolve().parent.parent / "src" / "bluetooth" / "vendor"
+HEADER = "// Generated by scripts/generate-vendor-tables.py from {}, do not edit.\n\n"
+
+
+def rust_string(value):
+    value = " ".join(value.split())
+    return '"' + value.replace("\\", "\\\\").replace('"', '\\"') + '"'
+
+
+def read_company_ids(path):
+    """Parse the assigned numbers YAML without needing PyYAML."""
+    companies = {}
+    value = None
+    for line in Path(path).read_text(encoding="utf-8").splitlines():
+        match = re.match(r"\s*-?\s*value:\s*(0x[0-9A-Fa-f]+)", line)
+        if match:
+            value = int(match.group(1), 16)
+            continue
+        match = re.match(r"\s*name:\s*(.+)$", line)
+        if match and value is not None:
+            name = match.group(1).strip()
+            if len(name) >= 2 and name[0] == name[-1] and name[0] in "'\"":
+                quote = name[0]
+                name = name[1:-1].replace(quote * 2, quote) if quote == "'" else name[1:-1]
+            companies[value] = name
+            value = None
+    return companies
+
+
+def read_ouis(path):
+    ouis = {}
+    with open(path, newline="", encoding="utf-8") as file:
+        for row in csv.DictReader(file):
+            if row["Registry"] != "MA-L":
+                continue
+            ouis[int(row["Assignment"], 16)] = row["Organization Name"]
+    return ouis
+
+
+def write_table(path, source, name, key_type, key_format, entries):
+    with open(path, "w", encoding="utf-8") as file:
+        file.write(HEADER.format(source))
+        file.write("/// Sorted by key for binary search\n")
+        file.write(f"pub const {name}: &[({key_type}, &str)] = &[\n")
+        for key in sorted(entries):
+            file.write(f"    ({key_format.format(key)}, {rust_string(entries[key])}),\n")
+        file.write("];\n")
+
+
+def main():
+    if len(sys.argv) != 3:
+        sys.exit(__doc__)
+
+    companies = read_company_ids(sys.argv[1])
+    ouis = read_ouis(sys.argv[2])
+
+    write_table(OUTPUT_DIR / "company_ids.rs", Path(sys.argv[1]).name, "COMPANY_IDS", "u16", "0x{:04X}", companies)
+    write_table(OUTPUT_DIR / "ouis.rs", Path(sys.argv[2]).name, "OUIS", "u32", "0x{:06X}", ouis)
+    print(f"Wrote {len(companies)} company identifiers and {len(ouis)} OUIs to {OUTPUT_DIR}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,141 @@
+#!/usr/bin/env python3
+"""Write tests/data/SYSTEM.hive, a small Windows SYSTEM hive with Bluetooth pairing keys.
+
+The hive has the layout Windows uses below ControlSet001\\Services\\BTHPORT\\Parameters\\Keys,
+one adapter with a BR/EDR link key, a device with both a link key and LE keys, an LE-only
+device and one whose value data points outside the hive. It uses every kind of subkey list
+("lf", "lh", "li" and "ri"), a key name in UTF-16 and values with their data inline.
+
+Usage: scripts/make-test-hive.py
+"""
+
+import struct
+from pathlib import Path
+
+OUTPUT = Path(__file__).resolve().parent.parent / "tests" / "data" / "SYSTEM.hive"
+
+BASE_BLOCK_SIZE = 4096
+HBIN_HEADER_SIZE = 32
+REG_BINARY = 3
+REG_DWORD = 4
+REG_QWORD = 11
+
+ADAPTER = "001a7dda7113"
+BOTH = "aabbccddeeff"
+LE_ONLY = "c0ffee123456"
+BROKEN = "112233445566"
+
+
+class Bins:
+    """Cells of one hive bin, offsets are relative to the first bin like in a hive."""
+
+    def __init__(self):
+        self.data = bytearray(HBIN_HEADER_SIZE)
+
+    def cell(self, payload):
+        offset = len(self.data)
+        size = (len(payload) + 4 + 7) // 8 * 8
+        self.data += struct.pack("<i", -size) + payload + bytes(size - 4 - len(payload))
+        return offset
+
+    def finish(self):
+        size = (len(self.data) + 4 + 4095) // 4096 * 4096
+        # The rest of the bin is one free cell
+        free = size - len(self.data)
+        self.data += struct.pack("<i", free) + bytes(free - 4)
+        self.data[:HBIN_HEADER_SIZE] = b"hbin" + struct.pack("<II", 0, size) + bytes(HBIN_HEADER_SIZE - 12)
+        return bytes(self.data)
+
+
+def value(bins, name, kind, data, data_offset=None):
+    if kind == REG_DWORD:
+        # Up to four bytes sit in the data offset field itself
+        size, offset = 0x8000_0000 | len(data), struct.unpack("<I", data.ljust(4, b"\0"))[0]
+    else:
+        size = len(data)
+        offset = bins.cell(data) if data_offset is None else data_offset
+    raw_name = name.encode("latin-1")
+    return bins.cell(b"vk" + struct.pack("<HIIIHH", len(raw_name), size, offset, kind, 0x01, 0) + raw_name)
+
+
+def key(bins, name, subkeys=(), values=(), list_kind="lf", compressed=True, root=False):
+    subkey_list = 0xFFFF_FFFF
+    if subkeys:
+        if list_kind == "li":
+            subkey_list = bins.cell(b"li" + struct.pack("<H", len(subkeys)) + b"".join(struct.pack("<I", k) for k in subkeys))
+        elif list_kind == "ri":
+            # One "lh" list per subkey, referenced from the index root
+            lists = [bins.cell(b"lh" + struct.pack("<HII", 1, k, 0)) for k in subkeys]
+            subkey_list = bins.cell(b"ri" + struct.pack("<H", len(lists)) + b"".join(struct.pack("<I", l) for l in lists))
+        else:
+            subkey_list = bins.cell(list_kind.encode() + struct.pack("<H", len(subkeys)) + b"".join(struct.pack("<II", k, 0) for k in subkeys))
+    value_list = 0xFFFF_FFFF
+    if values:
+        value_list = bins.cell(b"".join(struct.pack("<I", v) for v in values))
+
+    raw_name = name.encode("latin-1") if compressed else name.encode("utf-16-le")
+    flags = (0x20 if compressed else 0) | (0x2C if root else 0)
+    return bins.cell(
+        b"nk"
+        + struct.pack("<HQIIIIIIIIIIIIIIIHH", flags, 0, 0, 0, len(subkeys), 0, subkey_list, 0xFFFF_FFFF,
+                      len(values), value_list, 0xFFFF_FFFF, 0xFFFF_FFFF, 0, 0, 0, 0, 0, len(raw_name), 0)
+        + raw_name
+    )
+
+
+def base_block(root, bins_size):
+    block = bytearray(BASE_BLOCK_SIZE)
+    block[:0x30] = b"regf" + struct.pack("<IIQIIIIII", 1, 1, 0, 1, 5, 0, 1, root, bins_size) + struct.pack("<I", 1)
+    block[0x30:0x30 + 12] = "SYSTEM".encode("utf-16-le")
+    checksum = 0
+    for (word,) in struct.iter_unpack("<I", bytes(block[:0x1FC])):
+        checksum ^= word
+    block[0x1FC:0x200] = struct.pack("<I", checksum)
+    return bytes(block)
+
+
+def key_bytes(first):
+    return bytes(range(first, first + 16))
+
+
+def main():
+    bins = Bins()
+
+    both_le = key(bins, BOTH, values=[
+        value(bins, "LTK", REG_BINARY, key_bytes(0x10)),
+        value(bins, "KeyLength", REG_DWORD, struct.pack("<I", 16)),
+        value(bins, "EDIV", REG_DWORD, struct.pack("<I", 0x1234)),
+        value(bins, "ERand", REG_QWORD, struct.pack("<Q", 0x0102030405060708)),
+        value(bins, "AddressType", REG_DWORD, struct.pack("<I", 0)),
+        value(bins, "AuthReq", REG_DWORD, struct.pack("<I", 0x2D)),
+    ])
+    le_only = key(bins, LE_ONLY, values=[
+        value(bins, "LTK", REG_BINARY, key_bytes(0x20)),
+        value(bins, "IRK", REG_BINARY, key_bytes(0x30)),
+        value(bins, "CSRK", REG_BINARY, key_bytes(0x40)),
+        value(bins, "AddressType", REG_DWORD, struct.pack("<I", 1)),
+        value(bins, "AuthReq", REG_DWORD, struct.pack("<I", 0x01)),
+    ])
+    broken = key(bins, BROKEN, values=[
+        value(bins, "LTK", REG_BINARY, key_bytes(0x50), data_offset=0x7FFF_0000),
+    ])
+    adapter = key(bins, ADAPTER, subkeys=[both_le, le_only, broken], list_kind="lh", compressed=False, values=[
+        value(bins, BOTH, REG_BINARY, key_bytes(0x60)),
+        value(bins, "CentralIRK", REG_BINARY, key_bytes(0x70)),
+    ])
+
+    keys = key(bins, "Keys", subkeys=[adapter], list_kind="ri")
+    parameters = key(bins, "Parameters", subkeys=[keys], list_kind="li")
+    bthport = key(bins, "BTHPORT", subkeys=[parameters])
+    services = key(bins, "Services", subkeys=[bthport])
+    control_set = key(bins, "ControlSet001", subkeys=[services])
+    select = key(bins, "Select", values=[value(bins, "Current", REG_DWORD, struct.pack("<I", 1))])
+    root = key(bins, "ROOT", subkeys=[control_set, select], root=True)
+
+    data = bins.finish()
+    OUTPUT.write_bytes(base_block(root, len(data)) + data)
+    print(f"Wrote {OUTPUT}")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::MatchRule;
+use dbus::nonblock::stdintf::org_freedesktop_dbus::RequestNameReply;
+use dbus::nonblock::SyncConnection;
+use dbus::Message;
+use log::{debug, error, info, warn};
+use tokio::sync::broadcast;
+
+use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::events::BluetoothEvent;
+use crate::bluetooth::manager::BluetoothManager;
+use crate::bluetooth::uuids;
+
+/// GApplication already owns the application id, so the API has a bus name of its own
+pub const API_SERVICE: &str = "org.rustblue.Api";
+pub const API_PATH: &str = "/org/rustblue/Manager";
+pub const API_INTERFACE: &str = "org.rustblue.Manager";
+
+const INTROSPECTABLE_INTERFACE: &str = "org.freedesktop.DBus.Introspectable";
+const ERROR_FAILED: &str = "org.rustblue.Error.Failed";
+
+const INTROSPECTION: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.rustblue.Manager">
+    <method name="ListDevices">
+      <arg name="devices" type="aa{sv}" direction="out"/>
+    </method>
+    <method name="Connect">
+      <arg name="address" type="s" direction="in"/>
+    </method>
+    <method name="Disconnect">
+      <arg name="address" type="s" direction="in"/>
+    </method>
+    <method name="ConnectFavourites"/>
+    <method name="GetPowered">
+      <arg name="powered" type="b" direction="out"/>
+    </method>
+    <method name="SetPowered">
+      <arg name="powered" type="b" direction="in"/>
+    </method>
+    <method name="TogglePower">
+      <arg name="powered" type="b" direction="out"/>
+    </method>
+    <signal name="DeviceAdded">
+      <arg name="address" type="s"/>
+    </signal>
+    <signal name="DeviceRemoved">
+      <arg name="address" type="s"/>
+    </signal>
+    <signal name="DeviceConnected">
+      <arg name="address" type="s"/>
+    </signal>
+    <signal name="DeviceDisconnected">
+      <arg name="address" type="s"/>
+    </signal>
+    <signal name="DevicePaired">
+      <arg name="address" type="s"/>
+    </signal>
+    <signal name="BatteryChanged">
+      <arg name="address" type="s"/>
+      <arg name="percentage" type="y"/>
+    </signal>
+    <signal name="PoweredChanged">
+      <arg name="powered" type="b"/>
+    </signal>
+  </interface>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect">
+      <arg name="xml" type="s" direction="out"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+fn variant<T: RefArg + 'static>(value: T) -> Variant<Box<dyn RefArg>> {
+    Variant(Box::new(value) as Box<dyn RefArg>)
+}
+
+/// A device as returned by `ListDevices`, optional values are left out when unknown.
+fn device_properties(device: &BluetoothDevice) -> PropMap {
+    let mut properties: PropMap = HashMap::new();
+    properties.insert("Address".to_string(), variant(device.address.clone()));
+    properties.insert("Name".to_string(), variant(device.name.clone()));
+    if let Some(alias) = &device.alias {
+        properties.insert("Alias".to_string(), variant(alias.clone()));
+    }
+    properties.insert("Type".to_string(), variant(device.device_type.clone()));
+    properties.insert("Connected".to_string(), variant(device.connected));
+    properties.insert("Paired".to_string(), variant(device.paired));
+    properties.insert("Trusted".to_string(), variant(device.trusted));
+    let services: Vec<String> = device.uuids.iter().map(|uuid| uuids::label(uuid)).collect();
+    properties.insert("Services".to_string(), variant(services));
+    if let Some(rssi) = device.rssi {
+        properties.insert("RSSI".to_string(), variant(rssi));
+    }
+    if let Some(battery) = device.battery {
+        properties.insert("Battery".to_string(), variant(battery));
+    }
+    properties
+}
+
+fn failure(message: &Message, reason: &str) -> Message {
+    let reason = CString::new(reason).unwrap_or_default();
+    message.error(&ERROR_FAILED.into(), &reason)
+}
+
+/// A method call of the interface, with its arguments.
+enum Call {
+    ListDevices,
+    Connect(String),
+    Disconnect(String),
+    ConnectFavourites,
+    GetPowered,
+    SetPowered(bool),
+    TogglePower,
+}
+
+impl Call {
+    fn parse(member: &str, message: &Message) -> Result<Self> {
+        Ok(match member {
+            "ListDevices" => Call::ListDevices,
+            "Connect" => Call::Connect(message.read1()?),
+            "Disconnect" => Call::Disconnect(message.read1()?),
+            "ConnectFavourites" => Call::ConnectFavourites,
+            "GetPowered" => Call::GetPowered,
+            "SetPowered" => Call::SetPowered(message.read1()?),
+            "TogglePower" => Call::TogglePower,
+            _ => return Err(anyhow!("Unknown method {}", member)),
+        })
+    }
+}
+
+/// What a call returns, turned into the method return by [`reply`].
+enum Reply {
+    Empty,
+    Devices(Vec<BluetoothDevice>),
+    Powered(bool),
+}
+
+fn reply(message: &Message, reply: Reply) -> Message {
+    match reply {
+        Reply::Empty => message.method_return(),
+        Reply::Devices(devices) => {
+            let devices: Vec<PropMap> = devices.iter().map(device_properties).collect();
+            message.method_return().append1(devices)
+        }
+        Reply::Powered(powered) => message.method_return().append1(powered),
+    }
+}
+
+async fn call(manager: &BluetoothManager, call: Call) -> Result<Reply> {
+    match call {
+        Call::ListDevices => Ok(Reply::Devices(manager.get_devices().await?)),
+        Call::Connect(address) => {
+            manager.connect_device(&address).await?;
+            Ok(Reply::Empty)
+        }
+        Call::Disconnect(address) => {
+            manager.disconnect_device(&address).await?;
+            Ok(Reply::Empty)
+        }
+        Call::ConnectFavourites => {
+            manager.reconnect_favourites().await;
+            Ok(Reply::Empty)
+        }
+        Call::GetPowered => Ok(Reply::Powered(manager.is_adapter_powered().await?)),
+        Call::SetPowered(powered) => {
+            manager.set_adapter_powered(powered).await?;
+            Ok(Reply::Empty)
+        }
+        Call::TogglePower => {
+            let powered = !manager.is_adapter_powered().await?;
+            manager.set_adapter_powered(powered).await?;
+            Ok(Reply::Powered(powered))
+        }
+    }
+}
+
+/// The signal announcing `event` to API clients, if it is one they get.
+fn event_signal(event: &BluetoothEvent) -> Option<Message> {
+    let signal = |member: &str| Message::new_signal(API_PATH, API_INTERFACE, member).ok();
+    
+    match event {
+        BluetoothEvent::DeviceAdded { address, .. } => signal("DeviceAdded").map(|signal| signal.append1(address.as_str())),
+        BluetoothEvent::DeviceRemoved { address, .. } => signal("DeviceRemoved").map(|signal| signal.append1(address.as_str())),
+        BluetoothEvent::DeviceConnected { address, .. } => signal("DeviceConnected").map(|signal| signal.append1(address.as_str())),
+        BluetoothEvent::DeviceDisconnected { address, .. } => signal("DeviceDisconnected").map(|signal| signal.append1(address.as_str())),
+        BluetoothEvent::DevicePaired { address, .. } => signal("DevicePaired").map(|signal| signal.append1(address.as_str())),
+        BluetoothEvent::BatteryChanged { address, percentage, .. } => {
+            signal("BatteryChanged").map(|signal| signal.append2(address.as_str(), *percentage))
+        }
+        BluetoothEvent::AdapterPowered { powered, .. } => signal("PoweredChanged").map(|signal| signal.append1(*powered)),
+        _ => None,
+    }
+}
+
+/// Re-emit the manager's events as D-Bus signals.
+fn forward_events(manager: &BluetoothManager, connection: Arc<SyncConnection>) {
+    let mut receiver = manager.subscribe();
+    
+    tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            
+            if let Some(signal) = event_signal(&event) {
+                if connection.send(signal).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Own [`API_SERVICE`] on the session bus and export the `org.rustblue.Manager` interface at
+/// [`API_PATH`], answering calls with `manager`.
+///
+/// Returns once the name is owned; the object stays exported until the connection drops.
+pub async fn serve(manager: BluetoothManager) -> Result<()> {
+    let (resource, connection) = dbus_tokio::connection::new_session_sync()?;
+    tokio::spawn(async move {
+        let err = resource.await;
+        error!("Lost connection to the session bus: {}", err);
+    });
+    
+    let name_reply = connection.request_name(API_SERVICE, false, false, true).await?;
+    if name_reply != RequestNameReply::PrimaryOwner {
+        return Err(anyhow!("{} is already owned, is another RustBlue running?", API_SERVICE));
+    }
+    
+    forward_events(&manager, connection.clone());
+    
+    let handler_connection = connection.clone();
+    connection.start_receive(
+        MatchRule::new_method_call().with_path(API_PATH),
+        Box::new(move |message, conn| {
+            let interface = message.interface().map(|interface| interface.to_string()).unwrap_or_default();
+            let member = message.member().map(|member| member.to_string()).unwrap_or_default();
+            debug!("API call: {}.{}", interface, member);
+            
+            match interface.as_str() {
+                API_INTERFACE => {
+                    let request = match Call::parse(&member, &message) {
+                        Ok(request) => request,
+                        Err(e) => {
+                            warn!("Invalid API call {}: {}", member, e);
+                            let _ = conn.send(failure(&message, &e.to_string()));
+                            return true;
+                        }
+                    };
+                    let manager = manager.clone();
+                    let connection = handler_connection.clone();
+                    tokio::spawn(async move {
+                        let result = call(&manager, request).await;
+                        let response = match result {
+                            Ok(result) => reply(&message, result),
+                            Err(e) => {
+                                warn!("API call {} failed: {}", member, e);
+                                failure(&message, &e.to_string())
+                            }
+                        };
+                        let _ = connection.send(response);
+                    });
+                }
+                INTROSPECTABLE_INTERFACE if member == "Introspect" => {
+                    let _ = conn.send(message.method_return().append1(INTROSPECTION));
+                }
+                _ => {
+                    if let Some(reply) = dbus::channel::default_reply(&message) {
+                        let _ = conn.send(reply);
+                    }
+                }
+            }
+            true
+        }),
+    );
+    
+    info!("Exported {} at {} as {}", API_INTERFACE, API_PATH, API_SERVICE);
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{debug, info, warn};
+
+use crate::audio::pulse::{AudioNode, PulseAudio};
+
+/// The sink and source appear a moment after the device connects, once the profile is set up
+const NODE_WAIT_ATTEMPTS: u32 = 10;
+const NODE_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Default)]
+struct PreviousDefaults {
+    sink: Option<String>,
+    source: Option<String>,
+}
+
+/// Makes a connected Bluetooth device the default sink and source, and puts the
+/// previous defaults back when it disconnects.
+#[derive(Debug, Clone)]
+pub struct DefaultAudioSwitcher {
+    pulse: PulseAudio,
+    previous: Arc<Mutex<HashMap<String, PreviousDefaults>>>,
+}
+
+impl DefaultAudioSwitcher {
+    pub fn new(pulse: PulseAudio) -> Self {
+        Self {
+            pulse,
+            previous: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+    
+    fn find_node(nodes: Vec<AudioNode>, address: &str) -> Option<String> {
+        nodes.into_iter()
+            .filter(|node| !node.name.ends_with(".monitor"))
+            .find(|node| node.address.as_deref() == Some(address))
+            .map(|node| node.name)
+    }
+    
+    async fn wait_for_nodes(&self, address: &str) -> Result<(Option<String>, Option<String>)> {
+        for attempt in 1..=NODE_WAIT_ATTEMPTS {
+            let sink = Self::find_node(self.pulse.sinks().await?, address);
+            let source = Self::find_node(self.pulse.sources().await?, address);
+            
+            if sink.is_some() || source.is_some() {
+                return Ok((sink, source));
+            }
+            
+            debug!("No audio nodes for {} yet (attempt {}/{})", address, attempt, NODE_WAIT_ATTEMPTS);
+            tokio::time::sleep(NODE_WAIT_INTERVAL).await;
+        }
+        
+        Ok((None, None))
+    }
+    
+    /// Switch the default sink and source to the device, remembering the current ones.
+    pub async fn make_default(&self, address: &str) -> Result<()> {
+        let address = address.to_uppercase();
+        let (sink, source) = self.wait_for_nodes(&address).await?;
+        if sink.is_none() && source.is_none() {
+            warn!("Device {} has no audio sink or source, not making it the default", address);
+            return Ok(());
+        }
+        
+        let current = PreviousDefaults {
+            sink: self.pulse.default_sink().await.ok().filter(|current| Some(current) != sink.as_ref()),
+            source: self.pulse.default_source().await.ok().filter(|current| Some(current) != source.as_ref()),
+        };
+        
+        // Only the first switch counts, reconnect events must not overwrite the original defaults
+        self.previous.lock().unwrap()
+            .entry(address.clone())
+            .or_insert(current);
+        
+        if let Some(sink) = sink {
+            self.pulse.set_default_sink(&sink).await?;
+        }
+        // Headsets in A2DP mode have no microphone, keep the current input then
+        if let Some(source) = source {
+            self.pulse.set_default_source(&source).await?;
+        }
+        
+        info!("Made {} the default audio device", address);
+        Ok(())
+    }
+    
+    /// Restore whatever was the default before `make_default` switched to the device.
+    pub async fn restore(&self, address: &str) -> Result<()> {
+        let address = address.to_uppercase();
+        let previous = match self.previous.lock().unwrap().remove(&address) {
+            Some(previous) => previous,
+            None => return Ok(()),
+        };
+        
+        info!("Restoring default audio devices after {} disconnected", address);
+        if let Some(sink) = previous.sink {
+            if let Err(e) = self.pulse.set_default_sink(&sink).await {
+                warn!("Failed to restore default sink {}: {}", sink, e);
+            }
+        }
+        if let Some(source) = previous.source {
+            if let Err(e) = self.pulse.set_default_source(&source).await {
+                warn!("Failed to restore default source {}: {}", source, e);
+            }
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+use serde::Deserialize;
+use tokio::process::Command;
+
+/// Environment variable that overrides the `pactl` binary, e.g. to point at a stand-in script.
+const PACTL_ENV: &str = "RUSTBLUE_PACTL";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioCodec {
+    Sbc,
+    SbcXq,
+    Aac,
+    Aptx,
+    AptxHd,
+    AptxLl,
+    Ldac,
+    Msbc,
+    Cvsd,
+    Lc3,
+    Other(String),
+}
+
+impl AudioCodec {
+    pub fn from_id(id: &str) -> Self {
+        match id.to_lowercase().as_str() {
+            "sbc" => AudioCodec::Sbc,
+            "sbc_xq" => AudioCodec::SbcXq,
+            "aac" => AudioCodec::Aac,
+            "aptx" => AudioCodec::Aptx,
+            "aptx_hd" => AudioCodec::AptxHd,
+            "aptx_ll" | "aptx_ll_duplex" => AudioCodec::AptxLl,
+            "ldac" => AudioCodec::Ldac,
+            "msbc" => AudioCodec::Msbc,
+            "cvsd" => AudioCodec::Cvsd,
+            "lc3" | "lc3_swb" => AudioCodec::Lc3,
+            other => AudioCodec::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioCodec::Sbc => write!(f, "SBC"),
+            AudioCodec::SbcXq => write!(f, "SBC-XQ"),
+            AudioCodec::Aac => write!(f, "AAC"),
+            AudioCodec::Aptx => write!(f, "aptX"),
+            AudioCodec::AptxHd => write!(f, "aptX HD"),
+            AudioCodec::AptxLl => write!(f, "aptX LL"),
+            AudioCodec::Ldac => write!(f, "LDAC"),
+            AudioCodec::Msbc => write!(f, "mSBC"),
+            AudioCodec::Cvsd => write!(f, "CVSD"),
+            AudioCodec::Lc3 => write!(f, "LC3"),
+            AudioCodec::Other(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioProfile {
+    pub name: String,
+    pub description: String,
+    pub available: bool,
+    pub priority: u32,
+}
+
+impl AudioProfile {
+    /// Codec encoded in the profile, e.g. PipeWire's `a2dp-sink-ldac` or
+    /// "High Fidelity Playback (A2DP Sink, codec LDAC)".
+    pub fn codec(&self) -> Option<AudioCodec> {
+        if let Some(index) = self.description.find("codec ") {
+            let id = self.description[index + 6..]
+                .trim_end_matches(')')
+                .replace([' ', '-'], "_");
+            return Some(AudioCodec::from_id(&id));
+        }
+        
+        for prefix in ["a2dp-sink-", "headset-head-unit-", "audio-gateway-"] {
+            if let Some(id) = self.name.strip_prefix(prefix) {
+                return Some(AudioCodec::from_id(id));
+            }
+        }
+        
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioCard {
+    pub name: String,
+    pub address: String,
+    pub active_profile: String,
+    pub profiles: Vec<AudioProfile>,
+    pub codec: Option<AudioCodec>,
+}
+
+impl AudioCard {
+    pub fn active_profile(&self) -> Option<&AudioProfile> {
+        self.profiles.iter().find(|profile| profile.name == self.active_profile)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioNode {
+    pub name: String,
+    pub address: Option<String>,
+    pub codec: Option<AudioCodec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PactlCard {
+    name: String,
+    #[serde(default)]
+    properties: HashMap<String, String>,
+    #[serde(default)]
+    profiles: HashMap<String, PactlProfile>,
+    #[serde(default)]
+    active_profile: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PactlProfile {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    priority: u32,
+    #[serde(default = "default_available")]
+    available: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PactlNode {
+    name: String,
+    #[serde(default)]
+    properties: HashMap<String, String>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+/// Bluetooth address advertised by a card or node, from PipeWire's `api.bluez5.address`
+/// or PulseAudio's `device.string`.
+fn address_from_properties(name: &str, properties: &HashMap<String, String>) -> Option<String> {
+    if let Some(address) = properties.get("api.bluez5.address") {
+        return Some(address.to_uppercase());
+    }
+    
+    if properties.get("device.bus").map(String::as_str) == Some("bluetooth") {
+        if let Some(address) = properties.get("device.string") {
+            return Some(address.to_uppercase());
+        }
+    }
+    
+    // Fall back to the name, e.g. "bluez_card.AA_BB_CC_DD_EE_FF"
+    let suffix = name.strip_prefix("bluez_card.")
+        .or_else(|| name.strip_prefix("bluez_output."))
+        .or_else(|| name.strip_prefix("bluez_input."))
+        .or_else(|| name.strip_prefix("bluez_sink."))
+        .or_else(|| name.strip_prefix("bluez_source."))?;
+    let address: String = suffix.chars().take(17).collect();
+    if address.len() == 17 {
+        Some(address.replace('_', ":").to_uppercase())
+    } else {
+        None
+    }
+}
+
+fn codec_from_properties(properties: &HashMap<String, String>) -> Option<AudioCodec> {
+    properties.get("api.bluez5.codec")
+        .or_else(|| properties.get("bluetooth.codec"))
+        .map(|codec| AudioCodec::from_id(codec))
+}
+
+/// Sinks or sources from `pactl --format=json list sinks|sources`.
+fn parse_nodes(output: &str) -> Result<Vec<AudioNode>> {
+    let nodes: Vec<PactlNode> = serde_json::from_str(output)?;
+    
+    Ok(nodes.into_iter()
+        .map(|node| AudioNode {
+            address: address_from_properties(&node.name, &node.properties),
+            codec: codec_from_properties(&node.properties),
+            name: node.name,
+        })
+        .collect())
+}
+
+/// The Bluetooth cards from `pactl --format=json list cards`, profiles by priority.
+fn parse_cards(output: &str) -> Result<Vec<AudioCard>> {
+    let cards: Vec<PactlCard> = serde_json::from_str(output)?;
+    
+    let mut audio_cards = Vec::new();
+    for card in cards {
+        let address = match address_from_properties(&card.name, &card.properties) {
+            Some(address) => address,
+            None => continue,
+        };
+        
+        let mut profiles: Vec<AudioProfile> = card.profiles.into_iter()
+            .map(|(name, profile)| AudioProfile {
+                name,
+                description: profile.description,
+                available: profile.available,
+                priority: profile.priority,
+            })
+            .collect();
+        profiles.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.name.cmp(&b.name)));
+        
+        let mut audio_card = AudioCard {
+            name: card.name,
+            address,
+            active_profile: card.active_profile,
+            profiles,
+            codec: codec_from_properties(&card.properties),
+        };
+        
+        if audio_card.codec.is_none() {
+            audio_card.codec = audio_card.active_profile().and_then(|profile| profile.codec());
+        }
+        
+        audio_cards.push(audio_card);
+    }
+    
+    Ok(audio_cards)
+}
+
+/// Talks to PipeWire (through pipewire-pulse) or PulseAudio using `pactl`.
+///
+/// `pactl` honours `PULSE_SERVER`, so pointing that at a local stand-in server, or
+/// setting `RUSTBLUE_PACTL` to a script that prints canned output, is enough for testing.
+#[derive(Debug, Clone)]
+pub struct PulseAudio {
+    pactl: String,
+}
+
+impl PulseAudio {
+    pub fn new() -> Self {
+        let pactl = env::var(PACTL_ENV).unwrap_or_else(|_| "pactl".to_string());
+        Self { pactl }
+    }
+    
+    async fn pactl(&self, args: &[&str]) -> Result<String> {
+        debug!("Running {} {}", self.pactl, args.join(" "));
+        let output = Command::new(&self.pactl)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| anyhow!("Failed to run {}: {}", self.pactl, e))?;
+        
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} {} failed: {}",
+                self.pactl,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+    
+    async fn list_nodes(&self, kind: &str) -> Result<Vec<AudioNode>> {
+        parse_nodes(&self.pactl(&["--format=json", "list", kind]).await?)
+    }
+    
+    pub async fn sinks(&self) -> Result<Vec<AudioNode>> {
+        self.list_nodes("sinks").await
+    }
+    
+    pub async fn sources(&self) -> Result<Vec<AudioNode>> {
+        self.list_nodes("sources").await
+    }
+    
+    /// All Bluetooth cards known to the sound server.
+    pub async fn cards(&self) -> Result<Vec<AudioCard>> {
+        parse_cards(&self.pactl(&["--format=json", "list", "cards"]).await?)
+    }
+    
+    /// Find the card belonging to a Bluetooth device, with the codec actually in use.
+    pub async fn find_card(&self, address: &str) -> Result<Option<AudioCard>> {
+        let address = address.to_uppercase();
+        let mut card = match self.cards().await?.into_iter().find(|card| card.address == address) {
+            Some(card) => card,
+            None => {
+                debug!("No audio card found for {}", address);
+                return Ok(None);
+            }
+        };
+        
+        // The nodes report the negotiated codec more reliably than the profile name
+        let mut nodes = self.sinks().await.unwrap_or_default();
+        nodes.extend(self.sources().await.unwrap_or_default());
+        if let Some(codec) = nodes.into_iter()
+            .filter(|node| node.address.as_deref() == Some(address.as_str()))
+            .find_map(|node| node.codec)
+        {
+            card.codec = Some(codec);
+        }
+        
+        Ok(Some(card))
+    }
+    
+    pub async fn set_card_profile(&self, card: &str, profile: &str) -> Result<()> {
+        info!("Setting profile of {} to {}", card, profile);
+        self.pactl(&["set-card-profile", card, profile]).await?;
+        Ok(())
+    }
+    
+    pub async fn default_sink(&self) -> Result<String> {
+        Ok(self.pactl(&["get-default-sink"]).await?.trim().to_string())
+    }
+    
+    pub async fn default_source(&self) -> Result<String> {
+        Ok(self.pactl(&["get-default-source"]).await?.trim().to_string())
+    }
+    
+    pub async fn set_default_sink(&self, sink: &str) -> Result<()> {
+        info!("Setting default sink to {}", sink);
+        self.pactl(&["set-default-sink", sink]).await?;
+        Ok(())
+    }
+    
+    pub async fn set_default_source(&self, source: &str) -> Result<()> {
+        info!("Setting default source to {}", source);
+        self.pactl(&["set-default-source", source]).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const CARDS: &str = include_str!("../../tests/data/pactl-cards.json");
+    const SINKS: &str = include_str!("../../tests/data/pactl-sinks.json");
+    
+    /// `PulseAudio` running the stand-in script next to the canned output.
+    fn stand_in() -> PulseAudio {
+        PulseAudio {
+            pactl: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/pactl").to_string(),
+        }
+    }
+    
+    #[test]
+    fn cards_are_bluetooth_only() {
+        let cards = parse_cards(CARDS).unwrap();
+        let addresses: Vec<&str> = cards.iter().map(|card| card.address.as_str()).collect();
+        assert_eq!(addresses, ["AA:BB:CC:DD:EE:FF", "11:22:33:44:55:66"]);
+    }
+    
+    #[test]
+    fn pipewire_card_profiles_and_codec() {
+        let cards = parse_cards(CARDS).unwrap();
+        let card = &cards[0];
+        
+        assert_eq!(card.name, "bluez_card.AA_BB_CC_DD_EE_FF");
+        let names: Vec<&str> = card.profiles.iter().map(|profile| profile.name.as_str()).collect();
+        assert_eq!(names, ["a2dp-sink-ldac", "a2dp-sink-sbc", "headset-head-unit-msbc", "off"]);
+        assert!(!card.profiles[2].available);
+        assert_eq!(card.active_profile().unwrap().name, "a2dp-sink-ldac");
+        // No codec property, so it comes from the active profile
+        assert_eq!(card.codec, Some(AudioCodec::Ldac));
+    }
+    
+    #[test]
+    fn pulseaudio_card_address_and_codec() {
+        let cards = parse_cards(CARDS).unwrap();
+        let card = &cards[1];
+        
+        assert_eq!(card.address, "11:22:33:44:55:66");
+        assert_eq!(card.codec, Some(AudioCodec::Aac));
+        assert_eq!(card.active_profile().unwrap().codec(), None);
+    }
+    
+    #[test]
+    fn profile_codecs() {
+        let profile = |name: &str, description: &str| AudioProfile {
+            name: name.to_string(),
+            description: description.to_string(),
+            available: true,
+            priority: 0,
+        };
+        
+        assert_eq!(profile("a2dp-sink", "High Fidelity Playback (A2DP Sink, codec aptX HD)").codec(), Some(AudioCodec::AptxHd));
+        assert_eq!(profile("a2dp-sink-sbc_xq", "").codec(), Some(AudioCodec::SbcXq));
+        assert_eq!(profile("headset-head-unit-cvsd", "").codec(), Some(AudioCodec::Cvsd));
+        assert_eq!(profile("a2dp-sink-opus_g", "").codec(), Some(AudioCodec::Other("opus_g".to_string())));
+        assert_eq!(profile("off", "Off").codec(), None);
+    }
+    
+    #[test]
+    fn node_addresses() {
+        let nodes = parse_nodes(SINKS).unwrap();
+        
+        assert_eq!(nodes[0].address, None);
+        assert_eq!(nodes[1].address.as_deref(), Some("AA:BB:CC:DD:EE:FF"));
+        assert_eq!(nodes[1].codec, Some(AudioCodec::Aac));
+    }
+    
+    #[test]
+    fn address_from_node_name() {
+        let address = address_from_properties("bluez_sink.aa_bb_cc_dd_ee_ff.a2dp_sink", &HashMap::new());
+        assert_eq!(address.as_deref(), Some("AA:BB:CC:DD:EE:FF"));
+        assert_eq!(address_from_properties("bluez_card.AA_BB", &HashMap::new()), None);
+    }
+    
+    #[test]
+    fn invalid_output() {
+        assert!(parse_cards("Connection failure: Connection refused").is_err());
+    }
+    
+    #[tokio::test]
+    async fn find_card_prefers_the_node_codec() {
+        let card = stand_in().find_card("aa:bb:cc:dd:ee:ff").await.unwrap().unwrap();
+        assert_eq!(card.codec, Some(AudioCodec::Aac));
+    }
+    
+    #[tokio::test]
+    async fn find_card_misses() {
+        assert!(stand_in().find_card("00:00:00:00:00:01").await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn failing_pactl() {
+        let error = stand_in().set_card_profile("bluez_card.AA_BB_CC_DD_EE_FF", "off").await.unwrap_err();
+        assert!(error.to_string().contains("Unsupported"));
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::manager::BluetoothManager;
+use crate::bluetooth::uuids;
+use crate::config::{DeviceSettings, Settings};
+
+const BACKUP_VERSION: u32 = 1;
+
+/// What is backed up of one device: the BlueZ properties RustBlue lets you change, and
+/// RustBlue's own settings for it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceBackup {
+    /// To recognise the device in the file, not restored
+    pub name: String,
+    /// Names of the services the device offers, not restored either
+    pub services: Vec<String>,
+    pub alias: Option<String>,
+    pub trusted: bool,
+    pub blocked: bool,
+    pub settings: DeviceSettings,
+}
+
+/// Device metadata exported to a JSON file, keyed by address.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Backup {
+    pub version: u32,
+    pub devices: BTreeMap<String, DeviceBackup>,
+}
+
+impl Backup {
+    pub fn load(path: &Path) -> Result<Self> {
+        let backup: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if backup.version > BACKUP_VERSION {
+            return Err(anyhow!("{} is from a newer RustBlue", path.display()));
+        }
+        Ok(backup)
+    }
+    
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        info!("Exported {} devices to {}", self.devices.len(), path.display());
+        Ok(())
+    }
+}
+
+/// The current state of `device`, or `None` if there is nothing worth keeping about it.
+async fn device_backup(manager: &BluetoothManager, device: &BluetoothDevice, settings: &Settings) -> Option<DeviceBackup> {
+    let blocked = manager.is_device_blocked(&device.address).await.unwrap_or_else(|e| {
+        warn!("Failed to read whether {} is blocked: {}", device.address, e);
+        false
+    });
+    let backup = DeviceBackup {
+        name: device.name.clone(),
+        services: device.uuids.iter().map(|uuid| uuids::label(uuid)).collect(),
+        alias: device.alias.clone(),
+        trusted: device.trusted,
+        blocked,
+        settings: settings.device(&device.address),
+    };
+    
+    let keep = device.paired || backup.alias.is_some() || backup.trusted || backup.blocked
+        || backup.settings != DeviceSettings::default();
+    keep.then_some(backup)
+}
+
+/// Back up every device BlueZ knows that is paired or has anything set.
+pub async fn export(manager: &BluetoothManager, settings: &Settings) -> Result<Backup> {
+    let mut devices = BTreeMap::new();
+    for device in manager.get_devices().await? {
+        if let Some(backup) = device_backup(manager, &device, settings).await {
+            devices.insert(device.address.clone(), backup);
+        }
+    }
+    
+    Ok(Backup { version: BACKUP_VERSION, devices })
+}
+
+/// What a [`Change`] is about, named as on the details page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Alias,
+    Trusted,
+    Blocked,
+    AutoConnect,
+    DefaultAudio,
+    LockWhenAway,
+    Hooks,
+}
+
+impl Field {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Alias => "Alias",
+            Field::Trusted => "Trusted",
+            Field::Blocked => "Blocked",
+            Field::AutoConnect => "Reconnect Automatically",
+            Field::DefaultAudio => "Default Audio Device",
+            Field::LockWhenAway => "Lock When Away",
+            Field::Hooks => "Hooks",
+        }
+    }
+}
+
+/// A value restoring a backup would change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub field: Field,
+    pub from: String,
+    pub to: String,
+}
+
+/// What restoring a backup would do, for the user to confirm first.
+#[derive(Debug, Clone, Default)]
+pub struct Preview {
+    /// Changes per device, by address and name
+    pub devices: Vec<(String, String, Vec<Change>)>,
+    /// Devices in the backup BlueZ does not know, only RustBlue's settings can be restored
+    pub unknown: Vec<String>,
+}
+
+impl Preview {
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty() && self.unknown.is_empty()
+    }
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Yes" } else { "No" }.to_string()
+}
+
+fn hook_count(settings: &DeviceSettings) -> String {
+    match settings.hooks.len() {
+        1 => "1 hook".to_string(),
+        count => format!("{} hooks", count),
+    }
+}
+
+fn changes(current: &DeviceBackup, wanted: &DeviceBackup) -> Vec<Change> {
+    let alias = |backup: &DeviceBackup| backup.alias.clone().unwrap_or_else(|| "(none)".to_string());
+    let fields = [
+        (Field::Alias, alias(current), alias(wanted)),
+        (Field::Trusted, yes_no(current.trusted), yes_no(wanted.trusted)),
+        (Field::Blocked, yes_no(current.blocked), yes_no(wanted.blocked)),
+        (Field::AutoConnect, yes_no(current.settings.auto_connect), yes_no(wanted.settings.auto_connect)),
+        (Field::DefaultAudio, yes_no(current.settings.default_audio_on_connect), yes_no(wanted.settings.default_audio_on_connect)),
+        (Field::LockWhenAway, yes_no(current.settings.lock_when_away), yes_no(wanted.settings.lock_when_away)),
+    ];
+    
+    let mut changes: Vec<Change> = fields.into_iter()
+        .filter(|(_, from, to)| from != to)
+        .map(|(field, from, to)| Change { field, from, to })
+        .collect();
+    // The commands themselves are too long for the preview
+    if current.settings.hooks != wanted.settings.hooks {
+        changes.push(Change {
+            field: Field::Hooks,
+            from: hook_count(&current.settings),
+            to: hook_count(&wanted.settings),
+        });
+    }
+    changes
+}
+
+/// Compare `backup` with the devices BlueZ knows right now.
+pub async fn preview(backup: &Backup, manager: &BluetoothManager, settings: &Settings) -> Result<Preview> {
+    let known = manager.get_devices().await?;
+    let mut preview = Preview::default();
+    
+    for (address, wanted) in &backup.devices {
+        let Some(device) = known.iter().find(|device| &device.address == address) else {
+            preview.unknown.push(address.clone());
+            continue;
+        };
+        let current = device_backup(manager, device, settings).await.unwrap_or_default();
+        let device_changes = changes(&current, wanted);
+        if !device_changes.is_empty() {
+            preview.devices.push((address.clone(), device.name.clone(), device_changes));
+        }
+    }
+    
+    Ok(preview)
+}
+
+/// Apply the BlueZ properties of `backup` to the devices of `preview`; RustBlue's own
+/// settings are up to the caller.
+pub async fn restore(backup: &Backup, preview: &Preview, manager: &BluetoothManager) -> Result<()> {
+    let mut failed = 0;
+    for (address, _, device_changes) in &preview.devices {
+        let Some(wanted) = backup.devices.get(address) else {
+            continue;
+        };
+        
+        for change in device_changes {
+            let result = match change.field {
+                Field::Alias => manager.set_device_alias(address, wanted.alias.as_deref()).await,
+                Field::Trusted => manager.set_device_trusted(address, wanted.trusted).await,
+                Field::Blocked => manager.set_device_blocked(address, wanted.blocked).await,
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                warn!("Failed to restore {} of {}: {}", change.field.name(), address, e);
+                failed += 1;
+            }
+        }
+    }
+    
+    if failed > 0 {
+        return Err(anyhow!("{} values could not be restored", failed));
+    }
+    Ok(())
+}
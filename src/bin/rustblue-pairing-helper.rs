@@ -0,0 +1,224 @@
+// Writes pairing keys imported by RustBlue into BlueZ's storage. Runs as root through pkexec,
+// takes a JSON list of `InfoUpdate`s on stdin and restarts bluetoothd afterwards so it loads
+// them. It never parses the Windows hive itself, that happens unprivileged in RustBlue.
+
+#[path = "../dualboot/info.rs"]
+mod info;
+
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::Path;
+use std::process::{Command, ExitCode};
+
+use anyhow::{anyhow, Result};
+
+use info::{InfoSection, InfoUpdate};
+
+const STORAGE_DIR: &str = "/var/lib/bluetooth";
+
+/// A key file as BlueZ writes it: sections of `key=value` lines, in order.
+type KeyFile = Vec<(String, Vec<(String, String)>)>;
+
+fn parse(contents: &str) -> KeyFile {
+    let mut sections: KeyFile = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            sections.push((name.to_string(), Vec::new()));
+        } else if let (Some((key, value)), Some((_, entries))) = (line.split_once('='), sections.last_mut()) {
+            entries.push((key.to_string(), value.to_string()));
+        }
+    }
+    sections
+}
+
+fn render(sections: &KeyFile) -> String {
+    let mut contents = String::new();
+    for (name, entries) in sections {
+        contents.push_str(&format!("[{}]\n", name));
+        for (key, value) in entries {
+            contents.push_str(&format!("{}={}\n", key, value));
+        }
+        contents.push('\n');
+    }
+    contents
+}
+
+fn merge(file: &mut KeyFile, section: &InfoSection) {
+    let index = match file.iter().position(|(name, _)| *name == section.name) {
+        Some(index) => index,
+        None => {
+            file.push((section.name.clone(), Vec::new()));
+            file.len() - 1
+        }
+    };
+    let entries = &mut file[index].1;
+    
+    for entry in &section.entries {
+        match entries.iter_mut().find(|(key, _)| *key == entry.key) {
+            Some(_) if entry.only_if_missing => {}
+            Some((_, value)) => *value = entry.value.clone(),
+            None => entries.push((entry.key.clone(), entry.value.clone())),
+        }
+    }
+}
+
+/// The info file at `path`, empty if there is none yet. Any other error stops the import, an
+/// unreadable file would otherwise be replaced by one with only the imported keys.
+fn read(path: &Path) -> Result<KeyFile> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(parse(&contents)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(KeyFile::new()),
+        Err(e) => Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+fn write(storage_dir: &Path, update: &InfoUpdate) -> Result<()> {
+    let adapter_dir = storage_dir.join(&update.adapter);
+    if !adapter_dir.is_dir() {
+        return Err(anyhow!("BlueZ has no adapter {}", update.adapter));
+    }
+    
+    // BlueZ keeps its storage readable by root only
+    let device_dir = adapter_dir.join(&update.device);
+    DirBuilder::new().recursive(true).mode(0o700).create(&device_dir)?;
+    let path = device_dir.join("info");
+    let mut file = read(&path)?;
+    for section in &update.sections {
+        merge(&mut file, section);
+    }
+    
+    let temporary = device_dir.join("info.rustblue");
+    OpenOptions::new().write(true).create(true).truncate(true).mode(0o600)
+        .open(&temporary)?
+        .write_all(render(&file).as_bytes())?;
+    fs::rename(&temporary, &path)?;
+    Ok(())
+}
+
+fn run() -> Result<()> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    let updates: Vec<InfoUpdate> = serde_json::from_str(&input)?;
+    // Check everything before writing anything
+    for update in &updates {
+        update.validate()?;
+    }
+    
+    for update in &updates {
+        write(Path::new(STORAGE_DIR), update)?;
+    }
+    
+    let status = Command::new("systemctl").args(["try-restart", "bluetooth.service"]).status()?;
+    if !status.success() {
+        return Err(anyhow!("Restarting bluetooth.service failed with {}, restart it to load the keys", status));
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    use info::InfoEntry;
+    
+    const ADAPTER: &str = "00:1A:7D:DA:71:13";
+    const DEVICE: &str = "AA:BB:CC:DD:EE:FF";
+    
+    /// A storage directory with one adapter, removed again when dropped.
+    struct Storage(std::path::PathBuf);
+    
+    impl Storage {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("rustblue-helper-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join(ADAPTER)).unwrap();
+            Self(dir)
+        }
+        
+        fn info(&self) -> std::path::PathBuf {
+            self.0.join(ADAPTER).join(DEVICE).join("info")
+        }
+    }
+    
+    impl Drop for Storage {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+    
+    fn entry(key: &str, value: &str, only_if_missing: bool) -> InfoEntry {
+        InfoEntry { key: key.to_string(), value: value.to_string(), only_if_missing }
+    }
+    
+    fn update() -> InfoUpdate {
+        InfoUpdate {
+            adapter: ADAPTER.to_string(),
+            device: DEVICE.to_string(),
+            sections: vec![InfoSection {
+                name: "LinkKey".to_string(),
+                entries: vec![
+                    entry("Key", "606162636465666768696A6B6C6D6E6F", false),
+                    entry("Type", "4", true),
+                    entry("PINLength", "0", true),
+                ],
+            }],
+        }
+    }
+    
+    #[test]
+    fn merges_into_existing_file() {
+        let storage = Storage::new("merge");
+        fs::create_dir_all(storage.info().parent().unwrap()).unwrap();
+        fs::write(storage.info(), "[General]\nName=Headset\nTrusted=true\n\n[LinkKey]\nKey=00000000000000000000000000000000\nType=5\n").unwrap();
+        
+        write(&storage.0, &update()).unwrap();
+        
+        assert_eq!(
+            fs::read_to_string(storage.info()).unwrap(),
+            "[General]\nName=Headset\nTrusted=true\n\n[LinkKey]\nKey=606162636465666768696A6B6C6D6E6F\nType=5\nPINLength=0\n\n",
+        );
+    }
+    
+    #[test]
+    fn creates_missing_file() {
+        let storage = Storage::new("create");
+        
+        write(&storage.0, &update()).unwrap();
+        
+        assert_eq!(
+            fs::read_to_string(storage.info()).unwrap(),
+            "[LinkKey]\nKey=606162636465666768696A6B6C6D6E6F\nType=4\nPINLength=0\n\n",
+        );
+    }
+    
+    #[test]
+    fn keeps_unreadable_file() {
+        let storage = Storage::new("unreadable");
+        // Not valid UTF-8, so it cannot be read as a key file
+        let contents = b"[General]\nName=\xff\n";
+        fs::create_dir_all(storage.info().parent().unwrap()).unwrap();
+        fs::write(storage.info(), contents).unwrap();
+        
+        assert!(write(&storage.0, &update()).is_err());
+        assert_eq!(fs::read(storage.info()).unwrap(), contents);
+    }
+    
+    #[test]
+    fn rejects_unknown_adapter() {
+        let storage = Storage::new("adapter");
+        let update = InfoUpdate { adapter: "11:22:33:44:55:66".to_string(), ..update() };
+        
+        assert!(write(&storage.0, &update).is_err());
+    }
+}
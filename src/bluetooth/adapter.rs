@@ -1,14 +1,54 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
-use bluer::{Adapter as BluerAdapter, Address};
+use bluer::monitor::{Monitor, MonitorEvent, Pattern};
+use bluer::{Adapter as BluerAdapter, AdapterEvent, AdapterProperty, Address, DeviceEvent, DeviceProperty};
 use futures::StreamExt;
 use log::{debug, info, warn};
+use tokio::sync::broadcast;
+use tokio::task::AbortHandle;
 
+use crate::bluetooth::advertisement::AdvertisementStore;
 use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::events::BluetoothEvent;
+use crate::bluetooth::gatt::GattClient;
+use crate::bluetooth::history::HistoryStore;
+use crate::bluetooth::records::DeviceRecords;
+use crate::bluetooth::vendor;
+
+/// Devices advertise many times a second; more events than this per device would crowd
+/// connection changes out of the event channel for subscribers that fall behind
+const ADVERTISEMENT_EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Who asked for discovery, the session is shared and runs until all of them are done with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiscoveryOwner {
+    /// Scanning for devices to show in the list
+    Window,
+    /// RSSI readings for the proximity lock when no advertisement monitor is available
+    Proximity,
+}
+
+#[derive(Debug, Default)]
+struct Discovery {
+    owners: HashSet<DiscoveryOwner>,
+    /// Consumes the discovery stream, BlueZ discovers for as long as it is alive
+    session: Option<AbortHandle>,
+}
+
+impl Discovery {
+    fn is_running(&self) -> bool {
+        self.session.as_ref().is_some_and(|session| !session.is_finished())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Adapter {
     adapter: BluerAdapter,
     name: String,
+    discovery: Arc<Mutex<Discovery>>,
 }
 
 impl Adapter {
@@ -16,6 +56,7 @@ impl Adapter {
         Ok(Self {
             adapter,
             name,
+            discovery: Arc::new(Mutex::new(Discovery::default())),
         })
     }
     
@@ -23,6 +64,10 @@ impl Adapter {
         &self.name
     }
     
+    pub async fn address(&self) -> Result<String> {
+        Ok(self.adapter.address().await?.to_string())
+    }
+    
     pub async fn is_powered(&self) -> Result<bool> {
         Ok(self.adapter.is_powered().await?)
     }
@@ -53,25 +98,67 @@ impl Adapter {
         Ok(())
     }
     
-    pub async fn start_discovery(&self) -> Result<()> {
-        info!("Starting device discovery");
-        // Start discovery and consume the stream
+    pub async fn start_discovery(&self, owner: DiscoveryOwner) -> Result<()> {
+        {
+            let mut discovery = self.discovery.lock().unwrap();
+            if discovery.is_running() {
+                debug!("Joining the running discovery session for {:?}", owner);
+                discovery.owners.insert(owner);
+                return Ok(());
+            }
+        }
+        
+        info!("Starting device discovery for {:?}", owner);
         let mut stream = self.adapter.discover_devices().await?;
-        // Start the discovery but don't block - just consume the first item to initiate
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             while let Some(_event) = stream.next().await {
                 // Discovery events are handled, devices will appear in device_addresses()
             }
-        });
+        }).abort_handle();
+        
+        let mut discovery = self.discovery.lock().unwrap();
+        discovery.owners.insert(owner);
+        // Another owner may have started one meanwhile, a second session would outlive stop_discovery
+        if discovery.is_running() {
+            handle.abort();
+        } else {
+            discovery.session = Some(handle);
+        }
         Ok(())
     }
     
-    pub async fn stop_discovery(&self) -> Result<()> {
+    /// Dropping the stream ends the discovery session, once no other owner needs it.
+    pub async fn stop_discovery(&self, owner: DiscoveryOwner) -> Result<()> {
+        let mut discovery = self.discovery.lock().unwrap();
+        discovery.owners.remove(&owner);
+        if !discovery.owners.is_empty() {
+            debug!("Discovery no longer needed for {:?}, still used by {:?}", owner, discovery.owners);
+            return Ok(());
+        }
+        
         info!("Stopping device discovery");
-        // Note: bluer doesn't have explicit stop_discovery, it handles this automatically
+        if let Some(session) = discovery.session.take() {
+            session.abort();
+        }
         Ok(())
     }
     
+    /// Stop discovering for every owner, e.g. before the system sleeps.
+    pub fn end_discovery(&self) {
+        let mut discovery = self.discovery.lock().unwrap();
+        discovery.owners.clear();
+        if let Some(session) = discovery.session.take() {
+            info!("Stopping device discovery");
+            session.abort();
+        }
+    }
+    
+    /// Whether `owner` asked for discovery and the session is still running.
+    pub fn is_discovering(&self, owner: DiscoveryOwner) -> bool {
+        let discovery = self.discovery.lock().unwrap();
+        discovery.owners.contains(&owner) && discovery.is_running()
+    }
+    
     pub async fn get_devices(&self) -> Result<Vec<BluetoothDevice>> {
         debug!("Getting known devices");
         let mut devices = Vec::new();
@@ -86,10 +173,16 @@ impl Adapter {
                 Ok(device) => {
                     // Get device properties
                     let name = device.name().await.unwrap_or(None);
+                    // BlueZ derives an alias from the address for unnamed devices
+                    let alias = match &name {
+                        Some(name) => device.alias().await.ok().filter(|alias| alias != name),
+                        None => None,
+                    };
                     let connected = device.is_connected().await.unwrap_or(false);
                     let paired = device.is_paired().await.unwrap_or(false);
                     let trusted = device.is_trusted().await.unwrap_or(false);
                     let rssi = device.rssi().await.ok().flatten();
+                    let battery = device.battery_percentage().await.ok().flatten();
                     let uuids = match device.uuids().await {
                         Ok(uuid_set) => uuid_set.into_iter()
                             .map(|uuid| format!("{:?}", uuid))
@@ -99,15 +192,30 @@ impl Adapter {
                     
                     let mut bluetooth_device = BluetoothDevice {
                         address: address.to_string(),
+                        named: name.is_some(),
                         name: name.unwrap_or_else(|| "Unknown Device".to_string()),
+                        alias,
                         device_type: "Unknown".to_string(),
                         connected,
                         paired,
                         trusted,
                         rssi,
+                        battery,
                         uuids,
+                        reconnecting: false,
+                        beacon: None,
+                        distance: None,
+                        vendor: None,
+                        last_seen: None,
+                        record: None,
                     };
                     
+                    if let Ok(address_type) = device.address_type().await {
+                        if let Some(vendor) = vendor::address_vendor(address, address_type) {
+                            bluetooth_device.set_vendor(vendor);
+                        }
+                    }
+                    
                     // Update device type based on UUIDs
                     bluetooth_device.update_device_type();
                     debug!("Added device: {} ({})", bluetooth_device.name, address);
@@ -137,6 +245,41 @@ impl Adapter {
         Ok(())
     }
     
+    pub async fn is_device_connected(&self, address: Address) -> Result<bool> {
+        Ok(self.adapter.device(address)?.is_connected().await?)
+    }
+    
+    pub async fn is_device_trusted(&self, address: Address) -> Result<bool> {
+        Ok(self.adapter.device(address)?.is_trusted().await?)
+    }
+    
+    pub async fn is_device_blocked(&self, address: Address) -> Result<bool> {
+        Ok(self.adapter.device(address)?.is_blocked().await?)
+    }
+    
+    /// An empty alias makes BlueZ fall back to the device's own name.
+    pub async fn set_device_alias(&self, address: Address, alias: &str) -> Result<()> {
+        info!("Setting alias of {} to {:?}", address, alias);
+        self.adapter.device(address)?.set_alias(alias.to_string()).await?;
+        Ok(())
+    }
+    
+    pub async fn set_device_trusted(&self, address: Address, trusted: bool) -> Result<()> {
+        info!("Setting {} trusted: {}", address, trusted);
+        self.adapter.device(address)?.set_trusted(trusted).await?;
+        Ok(())
+    }
+    
+    pub async fn set_device_blocked(&self, address: Address, blocked: bool) -> Result<()> {
+        info!("Setting {} blocked: {}", address, blocked);
+        self.adapter.device(address)?.set_blocked(blocked).await?;
+        Ok(())
+    }
+    
+    pub fn gatt_client(&self, address: Address) -> Result<GattClient> {
+        Ok(GattClient::new(self.adapter.device(address)?))
+    }
+    
     pub async fn pair_device(&self, address: Address) -> Result<()> {
         info!("Pairing with device: {}", address);
         let device = self.adapter.device(address)?;
@@ -149,4 +292,201 @@ impl Adapter {
         self.adapter.remove_device(address).await?;
         Ok(())
     }
+    
+    /// Passively watch for `addresses` with a BlueZ advertisement monitor matching `patterns`,
+    /// sending `PresenceChanged` when one comes into range above `rssi_high` dBm or stays below
+    /// `rssi_low` dBm for `timeout`. Needs no discovery session; abort the handle to stop.
+    pub async fn monitor_presence(&self, patterns: Vec<Pattern>, addresses: HashSet<Address>, rssi_high: i16, rssi_low: i16, timeout: Duration, sender: broadcast::Sender<BluetoothEvent>) -> Result<AbortHandle> {
+        info!("Monitoring presence of {} devices on adapter {}", addresses.len(), self.name);
+        let monitor_manager = self.adapter.monitor().await?;
+        let mut monitor = monitor_manager.register(Monitor {
+            rssi_high_threshold: Some(rssi_high),
+            rssi_low_threshold: Some(rssi_low),
+            rssi_high_timeout: Some(Duration::from_secs(1)),
+            rssi_low_timeout: Some(timeout),
+            patterns: Some(patterns),
+            ..Default::default()
+        }).await?;
+        
+        let adapter_name = self.name.clone();
+        let task = tokio::spawn(async move {
+            // Unregisters the monitor when the task is aborted
+            let _monitor_manager = monitor_manager;
+            while let Some(event) = monitor.next().await {
+                let (address, present) = match event {
+                    MonitorEvent::DeviceFound(id) => (id.device, true),
+                    MonitorEvent::DeviceLost(id) => (id.device, false),
+                    _ => continue,
+                };
+                // Patterns are broad, e.g. a company identifier, so other devices match too
+                if !addresses.contains(&address) {
+                    continue;
+                }
+                
+                debug!("Device {} is {}", address, if present { "in range" } else { "out of range" });
+                let _ = sender.send(BluetoothEvent::PresenceChanged {
+                    adapter: adapter_name.clone(),
+                    address: address.to_string(),
+                    present,
+                });
+            }
+        });
+        
+        Ok(task.abort_handle())
+    }
+    
+    /// Forward adapter and device changes to `sender` until the adapter goes away, recording
+    /// advertisement data in `advertisements`, RSSI and battery readings in `history` and
+    /// sightings and connections in `records`.
+    pub async fn watch_events(&self, sender: broadcast::Sender<BluetoothEvent>, advertisements: AdvertisementStore, history: HistoryStore, records: DeviceRecords) -> Result<()> {
+        info!("Watching events of adapter {}", self.name);
+        let mut events = Box::pin(self.adapter.events().await?);
+        
+        // Devices BlueZ already knows about never produce a DeviceAdded event
+        for address in self.adapter.device_addresses().await? {
+            Self::watch_device(&self.adapter, &self.name, address, sender.clone(), advertisements.clone(), history.clone(), records.clone());
+        }
+        
+        let adapter = self.adapter.clone();
+        let adapter_name = self.name.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                match event {
+                    AdapterEvent::DeviceAdded(address) => {
+                        Self::watch_device(&adapter, &adapter_name, address, sender.clone(), advertisements.clone(), history.clone(), records.clone());
+                        let _ = sender.send(BluetoothEvent::DeviceAdded {
+                            adapter: adapter_name.clone(),
+                            address: address.to_string(),
+                        });
+                    }
+                    AdapterEvent::DeviceRemoved(address) => {
+                        advertisements.remove(&address.to_string());
+                        history.remove(&address.to_string());
+                        let _ = sender.send(BluetoothEvent::DeviceRemoved {
+                            adapter: adapter_name.clone(),
+                            address: address.to_string(),
+                        });
+                    }
+                    AdapterEvent::PropertyChanged(AdapterProperty::Powered(powered)) => {
+                        let _ = sender.send(BluetoothEvent::AdapterPowered {
+                            adapter: adapter_name.clone(),
+                            powered,
+                        });
+                    }
+                    AdapterEvent::PropertyChanged(AdapterProperty::Discovering(discovering)) => {
+                        let _ = sender.send(BluetoothEvent::AdapterDiscovering {
+                            adapter: adapter_name.clone(),
+                            discovering,
+                        });
+                    }
+                    AdapterEvent::PropertyChanged(_) => {}
+                }
+            }
+            debug!("Event stream of adapter {} ended", adapter_name);
+        });
+        
+        Ok(())
+    }
+    
+    fn watch_device(adapter: &BluerAdapter, adapter_name: &str, address: Address, sender: broadcast::Sender<BluetoothEvent>, advertisements: AdvertisementStore, history: HistoryStore, records: DeviceRecords) {
+        let device = match adapter.device(address) {
+            Ok(device) => device,
+            Err(e) => {
+                warn!("Failed to watch device {}: {}", address, e);
+                return;
+            }
+        };
+        
+        let adapter_name = adapter_name.to_string();
+        tokio::spawn(async move {
+            let mut events = match device.events().await {
+                Ok(events) => Box::pin(events),
+                Err(e) => {
+                    warn!("Failed to get events of device {}: {}", address, e);
+                    return;
+                }
+            };
+            
+            // Whatever the device advertised before we started listening
+            let key = address.to_string();
+            let mut current = Vec::new();
+            if let Ok(Some(data)) = device.manufacturer_data().await {
+                current.push(DeviceProperty::ManufacturerData(data));
+            }
+            if let Ok(Some(data)) = device.service_data().await {
+                current.push(DeviceProperty::ServiceData(data));
+            }
+            if let Ok(flags) = device.advertising_flags().await {
+                if !flags.is_empty() {
+                    current.push(DeviceProperty::AdvertisingFlags(flags));
+                }
+            }
+            if let Ok(Some(power)) = device.tx_power().await {
+                current.push(DeviceProperty::TxPower(power));
+            }
+            if let Ok(Some(rssi)) = device.rssi().await {
+                current.push(DeviceProperty::Rssi(rssi));
+            }
+            if let Ok(Some(percentage)) = device.battery_percentage().await {
+                current.push(DeviceProperty::BatteryPercentage(percentage));
+            }
+            for property in &current {
+                history.update(&key, property);
+                advertisements.update(&key, property);
+            }
+            if let Ok(true) = device.is_connected().await {
+                records.already_connected(&key);
+            }
+            
+            let mut last_advertisement_event: Option<Instant> = None;
+            while let Some(DeviceEvent::PropertyChanged(property)) = events.next().await {
+                // RSSI is advertisement data as well, so no separate event for it
+                history.update(&key, &property);
+                if let DeviceProperty::BatteryPercentage(percentage) = property {
+                    let _ = sender.send(BluetoothEvent::BatteryChanged {
+                        adapter: adapter_name.clone(),
+                        address: key.clone(),
+                        percentage,
+                    });
+                    continue;
+                }
+                if advertisements.update(&key, &property) {
+                    records.seen(&key);
+                    if last_advertisement_event.is_none_or(|sent| sent.elapsed() >= ADVERTISEMENT_EVENT_INTERVAL) {
+                        last_advertisement_event = Some(Instant::now());
+                        let _ = sender.send(BluetoothEvent::AdvertisementReceived {
+                            adapter: adapter_name.clone(),
+                            address: key.clone(),
+                        });
+                    }
+                    continue;
+                }
+                
+                let event = match property {
+                    DeviceProperty::Connected(true) => {
+                        records.connected(&key);
+                        BluetoothEvent::DeviceConnected {
+                            adapter: adapter_name.clone(),
+                            address: address.to_string(),
+                        }
+                    }
+                    DeviceProperty::Connected(false) => {
+                        records.disconnected(&key);
+                        BluetoothEvent::DeviceDisconnected {
+                            adapter: adapter_name.clone(),
+                            address: address.to_string(),
+                        }
+                    }
+                    DeviceProperty::Paired(true) => BluetoothEvent::DevicePaired {
+                        adapter: adapter_name.clone(),
+                        address: address.to_string(),
+                    },
+                    _ => continue,
+                };
+                
+                debug!("Device event: {:?}", event);
+                let _ = sender.send(event);
+            }
+        });
+    }
 }
@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use bluer::monitor::Pattern;
+use bluer::DeviceProperty;
+
+use crate::bluetooth::{uuids, vendor};
+
+/// Records kept per device for the live log, older ones are dropped
+const HISTORY_LIMIT: usize = 100;
+/// AD types of manufacturer specific data and 16-bit UUID service data
+const AD_MANUFACTURER_DATA: u8 = 0xff;
+const AD_SERVICE_DATA_16: u8 = 0x16;
+
+/// One advertised field as it arrived from BlueZ.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdvertisementField {
+    ManufacturerData(u16, Vec<u8>),
+    ServiceData(String, Vec<u8>),
+    Flags(Vec<u8>),
+    /// A raw AD structure by type, only reported for types BlueZ does not parse itself
+    Data(u8, Vec<u8>),
+    TxPower(i16),
+    Rssi(i16),
+}
+
+impl fmt::Display for AdvertisementField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdvertisementField::ManufacturerData(company, data) => {
+                write!(f, "Manufacturer {}: {}", company_label(*company), hex(data))
+            }
+            AdvertisementField::ServiceData(uuid, data) => write!(f, "Service {}: {}", uuid, hex(data)),
+            AdvertisementField::Flags(flags) => write!(f, "Flags: {}", hex(flags)),
+            AdvertisementField::Data(ad_type, data) => write!(f, "AD 0x{:02x}: {}", ad_type, hex(data)),
+            AdvertisementField::TxPower(power) => write!(f, "TX power: {} dBm", power),
+            AdvertisementField::Rssi(rssi) => write!(f, "RSSI: {} dBm", rssi),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AdvertisementRecord {
+    pub time: SystemTime,
+    pub field: AdvertisementField,
+}
+
+/// Everything a device advertised most recently, plus a log of what arrived when.
+#[derive(Debug, Clone, Default)]
+pub struct Advertisement {
+    pub manufacturer_data: BTreeMap<u16, Vec<u8>>,
+    pub service_data: BTreeMap<String, Vec<u8>>,
+    pub flags: Vec<u8>,
+    pub data: BTreeMap<u8, Vec<u8>>,
+    pub tx_power: Option<i16>,
+    pub rssi: Option<i16>,
+    pub last_seen: Option<SystemTime>,
+    pub history: VecDeque<AdvertisementRecord>,
+}
+
+impl Advertisement {
+    pub fn is_empty(&self) -> bool {
+        self.last_seen.is_none()
+    }
+    
+    /// Advertisement monitor patterns matching what the device advertises: its company
+    /// identifiers and 16-bit service data UUIDs, which lead their AD structures.
+    pub fn patterns(&self) -> Vec<Pattern> {
+        let companies = self.manufacturer_data.keys()
+            .map(|company| (AD_MANUFACTURER_DATA, company.to_le_bytes()));
+        let services = self.service_data.keys()
+            .filter_map(|uuid| uuids::short_uuid(uuid))
+            .map(|alias| (AD_SERVICE_DATA_16, alias.to_le_bytes()));
+        
+        companies.chain(services)
+            .map(|(data_type, content)| Pattern {
+                data_type,
+                start_position: 0,
+                content: content.to_vec(),
+            })
+            .collect()
+    }
+    
+    fn record(&mut self, field: AdvertisementField) {
+        let time = SystemTime::now();
+        self.last_seen = Some(time);
+        self.history.push_back(AdvertisementRecord { time, field });
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+    }
+    
+    /// Apply a property change, returning whether it was advertisement data.
+    pub fn update(&mut self, property: &DeviceProperty) -> bool {
+        match property {
+            DeviceProperty::ManufacturerData(data) => {
+                for (company, value) in data {
+                    self.manufacturer_data.insert(*company, value.clone());
+                    self.record(AdvertisementField::ManufacturerData(*company, value.clone()));
+                }
+            }
+            DeviceProperty::ServiceData(data) => {
+                for (uuid, value) in data {
+                    self.service_data.insert(uuid.to_string(), value.clone());
+                    self.record(AdvertisementField::ServiceData(uuid.to_string(), value.clone()));
+                }
+            }
+            DeviceProperty::AdvertisingFlags(flags) => {
+                self.flags = flags.clone();
+                self.record(AdvertisementField::Flags(flags.clone()));
+            }
+            DeviceProperty::AdvertisingData(data) => {
+                for (ad_type, value) in data {
+                    self.data.insert(*ad_type, value.clone());
+                    self.record(AdvertisementField::Data(*ad_type, value.clone()));
+                }
+            }
+            DeviceProperty::TxPower(power) => {
+                self.tx_power = Some(*power);
+                self.record(AdvertisementField::TxPower(*power));
+            }
+            DeviceProperty::Rssi(rssi) => {
+                self.rssi = Some(*rssi);
+                self.record(AdvertisementField::Rssi(*rssi));
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Advertisement data of every device seen, shared between the adapter watchers and the UI.
+#[derive(Debug, Clone, Default)]
+pub struct AdvertisementStore {
+    devices: Arc<Mutex<HashMap<String, Advertisement>>>,
+}
+
+impl AdvertisementStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn update(&self, address: &str, property: &DeviceProperty) -> bool {
+        self.devices.lock().unwrap()
+            .entry(address.to_string())
+            .or_default()
+            .update(property)
+    }
+    
+    pub fn get(&self, address: &str) -> Option<Advertisement> {
+        self.devices.lock().unwrap().get(address).cloned()
+    }
+    
+    pub fn remove(&self, address: &str) {
+        self.devices.lock().unwrap().remove(address);
+    }
+}
+
+/// Names of the AD flag bits that are set.
+pub fn flag_names(flags: &[u8]) -> Vec<&'static str> {
+    const NAMES: [&str; 5] = [
+        "LE Limited Discoverable",
+        "LE General Discoverable",
+        "BR/EDR Not Supported",
+        "LE and BR/EDR Controller",
+        "LE and BR/EDR Host",
+    ];
+    
+    let flags = flags.first().copied().unwrap_or(0);
+    NAMES.iter()
+        .enumerate()
+        .filter(|(bit, _)| flags & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// "Apple (0x004C)", or just the identifier for unknown companies.
+pub fn company_label(id: u16) -> String {
+    match vendor::company_name(id) {
+        Some(name) => format!("{} (0x{:04X})", vendor::short_name(name), id),
+        None => format!("0x{:04X}", id),
+    }
+}
+
+pub fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" ")
+}
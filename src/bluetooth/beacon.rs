@@ -0,0 +1,337 @@
+use std::fmt;
+
+use crate::bluetooth::advertisement::{hex, Advertisement};
+
+const APPLE: u16 = 0x004c;
+const MICROSOFT: u16 = 0x0006;
+const EDDYSTONE_UUID: &str = "0000feaa-0000-1000-8000-00805f9b34fb";
+const FAST_PAIR_UUID: &str = "0000fe2c-0000-1000-8000-00805f9b34fb";
+
+/// Path loss exponent for the distance estimate, 2 is free space
+const PATH_LOSS_EXPONENT: f64 = 2.0;
+/// Eddystone advertises its power at 0 m, iBeacon at 1 m
+const EDDYSTONE_ONE_METRE_LOSS: i16 = 41;
+
+/// A recognised beacon or pairing frame in a device's advertisement.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum Beacon {
+    IBeacon { uuid: String, major: u16, minor: u16, measured_power: i8 },
+    EddystoneUid { namespace: String, instance: String, tx_power: i8 },
+    EddystoneUrl { url: String, tx_power: i8 },
+    EddystoneTlm { battery_mv: Option<u16>, temperature: Option<f32>, advertisements: u32, uptime_secs: u32 },
+    /// `model_id` is only advertised while the device is in pairing mode
+    FastPair { model_id: Option<u32> },
+    AppleContinuity { kind: &'static str },
+    SwiftPair { name: Option<String> },
+}
+
+impl Beacon {
+    /// Short label for the device list.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Beacon::IBeacon { .. } => "iBeacon",
+            Beacon::EddystoneUid { .. } | Beacon::EddystoneUrl { .. } | Beacon::EddystoneTlm { .. } => "Eddystone",
+            Beacon::FastPair { .. } => "Fast Pair",
+            Beacon::AppleContinuity { .. } => "Apple",
+            Beacon::SwiftPair { .. } => "Swift Pair",
+        }
+    }
+    
+    /// Expected RSSI at one metre, if the frame advertises one.
+    pub fn measured_power(&self) -> Option<i16> {
+        match self {
+            Beacon::IBeacon { measured_power, .. } => Some(*measured_power as i16),
+            Beacon::EddystoneUid { tx_power, .. } | Beacon::EddystoneUrl { tx_power, .. } => {
+                Some(*tx_power as i16 - EDDYSTONE_ONE_METRE_LOSS)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Beacon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Beacon::IBeacon { uuid, major, minor, measured_power } => {
+                write!(f, "iBeacon {} major {} minor {}, {} dBm at 1 m", uuid, major, minor, measured_power)
+            }
+            Beacon::EddystoneUid { namespace, instance, tx_power } => {
+                write!(f, "Eddystone UID namespace {} instance {}, {} dBm at 0 m", namespace, instance, tx_power)
+            }
+            Beacon::EddystoneUrl { url, tx_power } => write!(f, "Eddystone URL {}, {} dBm at 0 m", url, tx_power),
+            Beacon::EddystoneTlm { battery_mv, temperature, advertisements, uptime_secs } => {
+                write!(f, "Eddystone TLM")?;
+                if let Some(battery_mv) = battery_mv {
+                    write!(f, " battery {} mV", battery_mv)?;
+                }
+                if let Some(temperature) = temperature {
+                    write!(f, " {:.1} °C", temperature)?;
+                }
+                write!(f, ", {} advertisements, up {} s", advertisements, uptime_secs)
+            }
+            Beacon::FastPair { model_id: Some(model_id) } => write!(f, "Google Fast Pair model 0x{:06X}", model_id),
+            Beacon::FastPair { model_id: None } => write!(f, "Google Fast Pair (not in pairing mode)"),
+            Beacon::AppleContinuity { kind } => write!(f, "Apple Continuity {}", kind),
+            Beacon::SwiftPair { name: Some(name) } => write!(f, "Microsoft Swift Pair \"{}\"", name),
+            Beacon::SwiftPair { name: None } => write!(f, "Microsoft Swift Pair"),
+        }
+    }
+}
+
+/// All beacon frames found in the advertisement, most specific first.
+pub fn decode(advertisement: &Advertisement) -> Vec<Beacon> {
+    let mut beacons = Vec::new();
+    
+    if let Some(data) = advertisement.manufacturer_data.get(&APPLE) {
+        beacons.extend(decode_apple(data));
+    }
+    if let Some(data) = advertisement.manufacturer_data.get(&MICROSOFT) {
+        beacons.extend(decode_swift_pair(data));
+    }
+    if let Some(data) = advertisement.service_data.get(EDDYSTONE_UUID) {
+        beacons.extend(decode_eddystone(data));
+    }
+    if let Some(data) = advertisement.service_data.get(FAST_PAIR_UUID) {
+        let model_id = match data.as_slice() {
+            [a, b, c] => Some(u32::from_be_bytes([0, *a, *b, *c])),
+            _ => None,
+        };
+        beacons.push(Beacon::FastPair { model_id });
+    }
+    
+    beacons
+}
+
+/// Rough distance in metres from the log-distance path loss model.
+pub fn estimate_distance(measured_power: i16, rssi: i16) -> f64 {
+    10f64.powf((measured_power - rssi) as f64 / (10.0 * PATH_LOSS_EXPONENT))
+}
+
+/// Distance to the device if one of its beacon frames advertises its power.
+pub fn distance(advertisement: &Advertisement) -> Option<f64> {
+    let rssi = advertisement.rssi?;
+    decode(advertisement).iter()
+        .find_map(Beacon::measured_power)
+        .map(|measured_power| estimate_distance(measured_power, rssi))
+}
+
+fn decode_apple(data: &[u8]) -> Option<Beacon> {
+    let (&kind, rest) = data.split_first()?;
+    
+    if kind == 0x02 && rest.len() >= 22 && rest[0] == 0x15 {
+        let uuid = uuid::Uuid::from_slice(&rest[1..17]).ok()?;
+        return Some(Beacon::IBeacon {
+            uuid: uuid.to_string(),
+            major: u16::from_be_bytes([rest[17], rest[18]]),
+            minor: u16::from_be_bytes([rest[19], rest[20]]),
+            measured_power: rest[21] as i8,
+        });
+    }
+    
+    let kind = match kind {
+        0x05 => "AirDrop",
+        0x06 => "HomeKit",
+        0x07 => "Proximity Pairing",
+        0x08 => "Hey Siri",
+        0x09 => "AirPlay Target",
+        0x0a => "AirPlay Source",
+        0x0b => "Magic Switch",
+        0x0c => "Handoff",
+        0x0d => "Tethering Target",
+        0x0e => "Tethering Source",
+        0x0f => "Nearby Action",
+        0x10 => "Nearby Info",
+        0x12 => "Find My",
+        _ => return None,
+    };
+    Some(Beacon::AppleContinuity { kind })
+}
+
+fn decode_swift_pair(data: &[u8]) -> Option<Beacon> {
+    // Microsoft beacon id 0x03, then the Swift Pair scenario and a reserved RSSI byte
+    let name_offset = match data {
+        [0x03, 0x00, _, ..] => 3,
+        [0x03, 0x01, _, ..] => 3 + 6 + 3,
+        [0x03, 0x02, _, ..] => 3 + 3,
+        _ => return None,
+    };
+    
+    let name = data.get(name_offset..)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned());
+    Some(Beacon::SwiftPair { name })
+}
+
+fn decode_eddystone(data: &[u8]) -> Option<Beacon> {
+    match data.first()? {
+        0x00 if data.len() >= 18 => Some(Beacon::EddystoneUid {
+            tx_power: data[1] as i8,
+            namespace: hex(&data[2..12]).replace(' ', ""),
+            instance: hex(&data[12..18]).replace(' ', ""),
+        }),
+        0x10 if data.len() >= 3 => Some(Beacon::EddystoneUrl {
+            tx_power: data[1] as i8,
+            url: decode_eddystone_url(data[2], &data[3..]),
+        }),
+        // Only the unencrypted version 0 is readable
+        0x20 if data.len() >= 14 && data[1] == 0x00 => {
+            let battery_mv = u16::from_be_bytes([data[2], data[3]]);
+            let temperature = i16::from_be_bytes([data[4], data[5]]);
+            Some(Beacon::EddystoneTlm {
+                battery_mv: Some(battery_mv).filter(|mv| *mv != 0),
+                temperature: Some(temperature).filter(|t| *t != i16::MIN).map(|t| t as f32 / 256.0),
+                advertisements: u32::from_be_bytes([data[6], data[7], data[8], data[9]]),
+                uptime_secs: u32::from_be_bytes([data[10], data[11], data[12], data[13]]) / 10,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn decode_eddystone_url(scheme: u8, encoded: &[u8]) -> String {
+    const SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+    const EXPANSIONS: [&str; 14] = [
+        ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/",
+        ".com", ".org", ".edu", ".net", ".info", ".biz", ".gov",
+    ];
+    
+    let mut url = SCHEMES.get(scheme as usize).copied().unwrap_or("").to_string();
+    for &byte in encoded {
+        match EXPANSIONS.get(byte as usize) {
+            Some(expansion) => url.push_str(expansion),
+            None => url.push(byte as char),
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn manufacturer(company: u16, data: &[u8]) -> Advertisement {
+        let mut advertisement = Advertisement::default();
+        advertisement.manufacturer_data.insert(company, data.to_vec());
+        advertisement
+    }
+    
+    fn service(uuid: &str, data: &[u8]) -> Advertisement {
+        let mut advertisement = Advertisement::default();
+        advertisement.service_data.insert(uuid.to_string(), data.to_vec());
+        advertisement
+    }
+    
+    fn ibeacon_frame() -> Vec<u8> {
+        let mut frame = vec![0x02, 0x15];
+        frame.extend(1..=16u8);
+        frame.extend([0x00, 0x2a, 0x01, 0x00, 0xc5]);
+        frame
+    }
+    
+    #[test]
+    fn ibeacon() {
+        let beacons = decode(&manufacturer(APPLE, &ibeacon_frame()));
+        assert_eq!(beacons, [Beacon::IBeacon {
+            uuid: "01020304-0506-0708-090a-0b0c0d0e0f10".to_string(),
+            major: 42,
+            minor: 256,
+            measured_power: -59,
+        }]);
+        assert_eq!(beacons[0].measured_power(), Some(-59));
+        
+        let frame = ibeacon_frame();
+        assert!(decode(&manufacturer(APPLE, &frame[..frame.len() - 1])).is_empty());
+    }
+    
+    #[test]
+    fn apple_continuity() {
+        assert_eq!(decode(&manufacturer(APPLE, &[0x10, 0x05, 0x01])), [Beacon::AppleContinuity { kind: "Nearby Info" }]);
+        assert!(decode(&manufacturer(APPLE, &[0x99])).is_empty());
+        assert!(decode(&manufacturer(APPLE, &[])).is_empty());
+    }
+    
+    #[test]
+    fn swift_pair() {
+        assert_eq!(decode(&manufacturer(MICROSOFT, b"\x03\x00\x80Mouse")), [Beacon::SwiftPair { name: Some("Mouse".to_string()) }]);
+        // The name follows a six byte address and three byte class of device
+        assert_eq!(decode(&manufacturer(MICROSOFT, b"\x03\x01\x80abcdefghiPen")), [Beacon::SwiftPair { name: Some("Pen".to_string()) }]);
+        assert_eq!(decode(&manufacturer(MICROSOFT, &[0x03, 0x02, 0x80, 0, 0, 0])), [Beacon::SwiftPair { name: None }]);
+        assert_eq!(decode(&manufacturer(MICROSOFT, &[0x03, 0x01, 0x80, 0])), [Beacon::SwiftPair { name: None }]);
+        assert!(decode(&manufacturer(MICROSOFT, &[0x03, 0x00])).is_empty());
+        assert!(decode(&manufacturer(MICROSOFT, &[0x01, 0x00, 0x80])).is_empty());
+    }
+    
+    #[test]
+    fn eddystone_uid() {
+        let mut frame = vec![0x00, 0xee];
+        frame.extend(0..16u8);
+        let beacons = decode(&service(EDDYSTONE_UUID, &frame));
+        assert_eq!(beacons, [Beacon::EddystoneUid {
+            namespace: "00010203040506070809".to_string(),
+            instance: "0a0b0c0d0e0f".to_string(),
+            tx_power: -18,
+        }]);
+        assert_eq!(beacons[0].measured_power(), Some(-59));
+        
+        assert!(decode(&service(EDDYSTONE_UUID, &frame[..17])).is_empty());
+    }
+    
+    #[test]
+    fn eddystone_url() {
+        let beacons = decode(&service(EDDYSTONE_UUID, b"\x10\xf4\x03example\x07"));
+        assert_eq!(beacons, [Beacon::EddystoneUrl { url: "https://example.com".to_string(), tx_power: -12 }]);
+        
+        assert_eq!(decode_eddystone_url(0x00, b"rust-lang\x01docs"), "http://www.rust-lang.org/docs");
+        assert_eq!(decode_eddystone_url(0x01, b"gnome\x02"), "https://www.gnome.edu/");
+        assert_eq!(decode_eddystone_url(0x02, b"a\x0db"), "http://a.govb");
+        // Unknown schemes are left out, bytes past the expansions kept as they are
+        assert_eq!(decode_eddystone_url(0x04, b"a\x0e"), "a\u{e}");
+        
+        assert!(decode(&service(EDDYSTONE_UUID, &[0x10, 0xf4])).is_empty());
+    }
+    
+    #[test]
+    fn eddystone_tlm() {
+        let frame = [0x20, 0x00, 0x0b, 0xb8, 0x17, 0x80, 0, 0, 0, 100, 0, 0, 0x03, 0xe8];
+        assert_eq!(decode(&service(EDDYSTONE_UUID, &frame)), [Beacon::EddystoneTlm {
+            battery_mv: Some(3000),
+            temperature: Some(23.5),
+            advertisements: 100,
+            uptime_secs: 100,
+        }]);
+        
+        // Unknown battery and temperature
+        let frame = [0x20, 0x00, 0, 0, 0x80, 0x00, 0, 0, 0, 1, 0, 0, 0, 10];
+        assert_eq!(decode(&service(EDDYSTONE_UUID, &frame)), [Beacon::EddystoneTlm {
+            battery_mv: None,
+            temperature: None,
+            advertisements: 1,
+            uptime_secs: 1,
+        }]);
+        
+        // Encrypted, and truncated
+        let mut frame = frame;
+        frame[1] = 0x01;
+        assert!(decode(&service(EDDYSTONE_UUID, &frame)).is_empty());
+        assert!(decode(&service(EDDYSTONE_UUID, &frame[..13])).is_empty());
+        assert!(decode(&service(EDDYSTONE_UUID, &[])).is_empty());
+    }
+    
+    #[test]
+    fn fast_pair() {
+        assert_eq!(decode(&service(FAST_PAIR_UUID, &[0xab, 0xcd, 0xef])), [Beacon::FastPair { model_id: Some(0xabcdef) }]);
+        assert_eq!(decode(&service(FAST_PAIR_UUID, &[0x00, 0x01, 0x02, 0x03])), [Beacon::FastPair { model_id: None }]);
+    }
+    
+    #[test]
+    fn distances() {
+        assert_eq!(estimate_distance(-59, -59), 1.0);
+        assert!((estimate_distance(-59, -79) - 10.0).abs() < 1e-9);
+        
+        let mut advertisement = manufacturer(APPLE, &ibeacon_frame());
+        assert_eq!(distance(&advertisement), None);
+        advertisement.rssi = Some(-59);
+        assert_eq!(distance(&advertisement), Some(1.0));
+    }
+}
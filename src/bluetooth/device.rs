@@ -1,31 +1,74 @@
+use std::time::SystemTime;
+
 use bluer::Address;
 
+use crate::bluetooth::records::DeviceRecord;
+use crate::bluetooth::vendor;
+
 #[derive(Debug, Clone)]
 pub struct BluetoothDevice {
     pub address: String,
     pub name: String,
+    /// Name given to the device locally, when it differs from the advertised one
+    pub alias: Option<String>,
+    /// The device reported a name, `name` is a placeholder otherwise
+    pub named: bool,
     pub device_type: String,
     pub connected: bool,
     pub paired: bool,
     pub trusted: bool,
     pub rssi: Option<i16>,
+    /// Battery level in percent, for devices that report it
+    pub battery: Option<u8>,
     pub uuids: Vec<String>,
+    /// RustBlue is retrying to connect the device after it dropped out
+    pub reconnecting: bool,
+    /// Beacon or pairing frame recognised in the advertisement, e.g. "iBeacon"
+    pub beacon: Option<String>,
+    /// Estimated distance in metres, only for beacons that advertise their power
+    pub distance: Option<f64>,
+    /// Manufacturer from the address' OUI or the advertised company identifier
+    pub vendor: Option<String>,
+    /// When the device was last seen advertising or connected, also in earlier sessions
+    pub last_seen: Option<SystemTime>,
+    /// Sightings and connections over time, once the device was seen
+    pub record: Option<DeviceRecord>,
 }
 
 impl BluetoothDevice {
+    /// Every value `device_type` can take
+    pub const TYPES: [&'static str; 5] = ["Audio Device", "Input Device", "Network Device", "File Transfer", "Unknown Device"];
+    
     pub fn new(address: Address, name: Option<String>) -> Self {
         Self {
             address: address.to_string(),
+            named: name.is_some(),
             name: name.unwrap_or_else(|| "Unknown Device".to_string()),
+            alias: None,
             device_type: "Unknown Device".to_string(),
             connected: false,
             paired: false,
             trusted: false,
             rssi: None,
+            battery: None,
             uuids: Vec::new(),
+            reconnecting: false,
+            beacon: None,
+            distance: None,
+            vendor: None,
+            last_seen: None,
+            record: None,
         }
     }
     
+    /// Record the manufacturer, and name unnamed devices after it, e.g. "Unknown (Logitech)".
+    pub fn set_vendor(&mut self, vendor: &str) {
+        if self.name == "Unknown Device" {
+            self.name = format!("Unknown ({})", vendor::short_name(vendor));
+        }
+        self.vendor = Some(vendor.to_string());
+    }
+    
     pub fn update_device_type(&mut self) {
         self.device_type = Self::determine_device_type(&self.uuids);
     }
@@ -34,12 +77,21 @@ impl BluetoothDevice {
         Self {
             address: address.to_string(),
             name: name.to_string(),
+            alias: None,
+            named: true,
             device_type: "Test Device".to_string(),
             connected,
             paired: false,
             trusted: false,
             rssi: Some(-50),
+            battery: None,
             uuids: Vec::new(),
+            reconnecting: false,
+            beacon: None,
+            distance: None,
+            vendor: None,
+            last_seen: None,
+            record: None,
         }
     }
     
@@ -83,6 +135,17 @@ impl BluetoothDevice {
         self.trusted = trusted;
     }
     
+    /// Themed icon matching `device_type`.
+    pub fn icon_name(&self) -> &'static str {
+        match self.device_type.as_str() {
+            "Audio Device" => "audio-headphones",
+            "Input Device" => "input-keyboard",
+            "Network Device" => "network-wireless",
+            "File Transfer" => "folder-remote",
+            _ => "bluetooth",
+        }
+    }
+    
     fn determine_device_type(uuids: &[String]) -> String {
         // Basic device type detection based on UUIDs
         // This is a simplified version - in reality, you'd check against known service UUIDs
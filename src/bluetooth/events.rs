@@ -0,0 +1,36 @@
+/// Changes reported by BlueZ, broadcast by [`crate::bluetooth::manager::BluetoothManager`].
+#[derive(Debug, Clone)]
+pub enum BluetoothEvent {
+    DeviceAdded { adapter: String, address: String },
+    DeviceRemoved { adapter: String, address: String },
+    DeviceConnected { adapter: String, address: String },
+    DeviceDisconnected { adapter: String, address: String },
+    DevicePaired { adapter: String, address: String },
+    /// Connecting or pairing through [`crate::bluetooth::manager::BluetoothManager`] failed
+    ConnectFailed { address: String, error: String },
+    PairingFailed { address: String, error: String },
+    /// New advertisement data, at most once a second per device, see
+    /// [`crate::bluetooth::advertisement::AdvertisementStore`]
+    AdvertisementReceived { adapter: String, address: String },
+    BatteryChanged { adapter: String, address: String, percentage: u8 },
+    /// A device watched by an advertisement monitor came into or left range
+    PresenceChanged { adapter: String, address: String, present: bool },
+    /// An adapter was plugged in or unplugged after startup
+    AdapterAdded { adapter: String },
+    AdapterRemoved { adapter: String },
+    AdapterPowered { adapter: String, powered: bool },
+    AdapterDiscovering { adapter: String, discovering: bool },
+    /// RustBlue started retrying to connect an auto-connect device
+    ReconnectStarted { address: String },
+    ReconnectStopped { address: String, outcome: ReconnectOutcome },
+}
+
+/// How retrying to connect an auto-connect device ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectOutcome {
+    Connected,
+    /// Every attempt failed
+    GaveUp,
+    /// Cancelled, or not worth trying, e.g. because the adapter was turned off
+    Stopped,
+}
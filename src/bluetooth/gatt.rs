@@ -0,0 +1,298 @@
+use std::fmt;
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use bluer::gatt::remote::{Characteristic, Descriptor};
+use bluer::Device;
+use futures::Stream;
+use log::{debug, info};
+
+/// BlueZ resolves services in the background after connecting
+const RESOLVE_WAIT_ATTEMPTS: u32 = 20;
+const RESOLVE_WAIT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Identifies a characteristic, or one of its descriptors, by BlueZ's attribute ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GattHandle {
+    pub service: u16,
+    pub characteristic: u16,
+    pub descriptor: Option<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GattDescriptor {
+    pub handle: GattHandle,
+    pub uuid: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GattCharacteristic {
+    pub handle: GattHandle,
+    pub uuid: String,
+    /// Property and permission names as BlueZ spells them, e.g. "read", "write-without-response"
+    pub flags: Vec<String>,
+    pub descriptors: Vec<GattDescriptor>,
+}
+
+impl GattCharacteristic {
+    pub fn can_read(&self) -> bool {
+        self.has_flag("read")
+    }
+    
+    pub fn can_write(&self) -> bool {
+        self.has_flag("write") || self.has_flag("write-without-response")
+    }
+    
+    pub fn can_notify(&self) -> bool {
+        self.has_flag("notify") || self.has_flag("indicate")
+    }
+    
+    fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GattService {
+    pub id: u16,
+    pub uuid: String,
+    pub primary: bool,
+    pub characteristics: Vec<GattCharacteristic>,
+}
+
+/// How attribute values are shown and entered in the GATT explorer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+    Hex,
+    Utf8,
+    /// Little-endian unsigned integer, as GATT encodes numbers
+    Unsigned,
+    Signed,
+}
+
+impl ValueFormat {
+    pub const ALL: [ValueFormat; 4] = [ValueFormat::Hex, ValueFormat::Utf8, ValueFormat::Unsigned, ValueFormat::Signed];
+    
+    pub fn format(&self, value: &[u8]) -> String {
+        match self {
+            ValueFormat::Hex => value.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" "),
+            ValueFormat::Utf8 => String::from_utf8_lossy(value).into_owned(),
+            ValueFormat::Unsigned | ValueFormat::Signed if value.is_empty() || value.len() > 8 => {
+                format!("({} bytes)", value.len())
+            }
+            ValueFormat::Unsigned => {
+                let mut bytes = [0u8; 8];
+                bytes[..value.len()].copy_from_slice(value);
+                u64::from_le_bytes(bytes).to_string()
+            }
+            ValueFormat::Signed => {
+                // Sign-extend from the highest byte actually present
+                let fill = if value[value.len() - 1] & 0x80 != 0 { 0xff } else { 0 };
+                let mut bytes = [fill; 8];
+                bytes[..value.len()].copy_from_slice(value);
+                i64::from_le_bytes(bytes).to_string()
+            }
+        }
+    }
+    
+    /// Parse user input into bytes, integers are encoded in the smallest width that fits.
+    pub fn parse(&self, text: &str) -> Result<Vec<u8>> {
+        let text = text.trim();
+        match self {
+            ValueFormat::Hex => {
+                let digits: String = text.trim_start_matches("0x")
+                    .chars()
+                    .filter(|c| !c.is_whitespace() && *c != ':' && *c != '-')
+                    .collect();
+                // Checked first, slicing below is by byte
+                if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+                    bail!("Invalid hex digit: {}", c);
+                }
+                if digits.len() % 2 == 1 {
+                    bail!("Odd number of hex digits");
+                }
+                (0..digits.len())
+                    .step_by(2)
+                    .map(|i| Ok(u8::from_str_radix(&digits[i..i + 2], 16)?))
+                    .collect()
+            }
+            ValueFormat::Utf8 => Ok(text.as_bytes().to_vec()),
+            ValueFormat::Unsigned => {
+                let value: u64 = text.parse().map_err(|_| anyhow!("Not an unsigned integer: {}", text))?;
+                let width = (8 - value.leading_zeros() as usize / 8).max(1);
+                Ok(value.to_le_bytes()[..width].to_vec())
+            }
+            ValueFormat::Signed => {
+                let value: i64 = text.parse().map_err(|_| anyhow!("Not an integer: {}", text))?;
+                let width = [1, 2, 4, 8].into_iter()
+                    .find(|width| {
+                        let bits = width * 8 - 1;
+                        bits >= 63 || (-(1i64 << bits)..(1i64 << bits)).contains(&value)
+                    })
+                    .unwrap_or(8);
+                Ok(value.to_le_bytes()[..width].to_vec())
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValueFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueFormat::Hex => "Hex",
+            ValueFormat::Utf8 => "UTF-8",
+            ValueFormat::Unsigned => "Unsigned integer",
+            ValueFormat::Signed => "Signed integer",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// GATT client for a connected LE device.
+#[derive(Debug, Clone)]
+pub struct GattClient {
+    device: Device,
+}
+
+impl GattClient {
+    pub fn new(device: Device) -> Self {
+        Self { device }
+    }
+    
+    async fn wait_for_services(&self) -> Result<()> {
+        if !self.device.is_connected().await? {
+            bail!("Connect the device to browse its services");
+        }
+        
+        for attempt in 1..=RESOLVE_WAIT_ATTEMPTS {
+            if self.device.is_services_resolved().await? {
+                return Ok(());
+            }
+            debug!("Services of {} not resolved yet (attempt {}/{})", self.device.address(), attempt, RESOLVE_WAIT_ATTEMPTS);
+            tokio::time::sleep(RESOLVE_WAIT_INTERVAL).await;
+        }
+        
+        bail!("Timed out waiting for the services of {}", self.device.address())
+    }
+    
+    /// Enumerate services, characteristics and descriptors once BlueZ has resolved them.
+    pub async fn services(&self) -> Result<Vec<GattService>> {
+        self.wait_for_services().await?;
+        info!("Enumerating GATT services of {}", self.device.address());
+        
+        let mut services = Vec::new();
+        for service in self.device.services().await? {
+            let mut characteristics = Vec::new();
+            for characteristic in service.characteristics().await? {
+                let flags = characteristic.flags().await?;
+                let flag_names = [
+                    (flags.broadcast, "broadcast"),
+                    (flags.read, "read"),
+                    (flags.write_without_response, "write-without-response"),
+                    (flags.write, "write"),
+                    (flags.notify, "notify"),
+                    (flags.indicate, "indicate"),
+                    (flags.authenticated_signed_writes, "authenticated-signed-writes"),
+                    (flags.extended_properties, "extended-properties"),
+                    (flags.reliable_write, "reliable-write"),
+                    (flags.writable_auxiliaries, "writable-auxiliaries"),
+                    (flags.encrypt_read, "encrypt-read"),
+                    (flags.encrypt_write, "encrypt-write"),
+                    (flags.encrypt_authenticated_read, "encrypt-authenticated-read"),
+                    (flags.encrypt_authenticated_write, "encrypt-authenticated-write"),
+                    (flags.secure_read, "secure-read"),
+                    (flags.secure_write, "secure-write"),
+                    (flags.authorize, "authorize"),
+                ];
+                
+                let handle = GattHandle {
+                    service: service.id(),
+                    characteristic: characteristic.id(),
+                    descriptor: None,
+                };
+                
+                let mut descriptors = Vec::new();
+                for descriptor in characteristic.descriptors().await? {
+                    descriptors.push(GattDescriptor {
+                        handle: GattHandle { descriptor: Some(descriptor.id()), ..handle },
+                        uuid: descriptor.uuid().await?.to_string(),
+                    });
+                }
+                
+                characteristics.push(GattCharacteristic {
+                    handle,
+                    uuid: characteristic.uuid().await?.to_string(),
+                    flags: flag_names.iter().filter(|(set, _)| *set).map(|(_, name)| name.to_string()).collect(),
+                    descriptors,
+                });
+            }
+            
+            services.push(GattService {
+                id: service.id(),
+                uuid: service.uuid().await?.to_string(),
+                primary: service.primary().await?,
+                characteristics,
+            });
+        }
+        
+        debug!("Found {} GATT services", services.len());
+        Ok(services)
+    }
+    
+    async fn characteristic(&self, handle: GattHandle) -> Result<Characteristic> {
+        let service = self.device.service(handle.service).await?;
+        Ok(service.characteristic(handle.characteristic).await?)
+    }
+    
+    async fn descriptor(&self, handle: GattHandle, id: u16) -> Result<Descriptor> {
+        Ok(self.characteristic(handle).await?.descriptor(id).await?)
+    }
+    
+    /// Read a characteristic, or a descriptor if the handle names one.
+    pub async fn read(&self, handle: GattHandle) -> Result<Vec<u8>> {
+        debug!("Reading {:?}", handle);
+        match handle.descriptor {
+            Some(id) => Ok(self.descriptor(handle, id).await?.read().await?),
+            None => Ok(self.characteristic(handle).await?.read().await?),
+        }
+    }
+    
+    pub async fn write(&self, handle: GattHandle, value: &[u8]) -> Result<()> {
+        info!("Writing {} bytes to {:?}", value.len(), handle);
+        match handle.descriptor {
+            Some(id) => self.descriptor(handle, id).await?.write(value).await?,
+            None => self.characteristic(handle).await?.write(value).await?,
+        }
+        Ok(())
+    }
+    
+    /// Subscribe to notifications or indications; dropping the stream unsubscribes.
+    pub async fn notify(&self, handle: GattHandle) -> Result<Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>> {
+        info!("Subscribing to notifications of {:?}", handle);
+        let characteristic = self.characteristic(handle).await?;
+        Ok(Box::pin(characteristic.notify().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parse_hex() {
+        assert_eq!(ValueFormat::Hex.parse("0x0aFF").unwrap(), [0x0a, 0xff]);
+        assert_eq!(ValueFormat::Hex.parse("01:02 03-04").unwrap(), [1, 2, 3, 4]);
+        assert!(ValueFormat::Hex.parse("abc").is_err());
+        assert!(ValueFormat::Hex.parse("zz").is_err());
+        // Multi-byte characters must not be sliced through
+        assert!(ValueFormat::Hex.parse("aéb").is_err());
+    }
+    
+    #[test]
+    fn parse_integers() {
+        assert_eq!(ValueFormat::Unsigned.parse("258").unwrap(), [2, 1]);
+        assert_eq!(ValueFormat::Signed.parse("-1").unwrap(), [0xff]);
+        assert_eq!(ValueFormat::Signed.parse("-129").unwrap(), [0x7f, 0xff]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use bluer::DeviceProperty;
+
+/// How far back the history goes
+pub const HISTORY_WINDOW: Duration = Duration::from_secs(30 * 60);
+/// Upper bound per series, in case a device reports its RSSI very often
+const SAMPLE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample<T> {
+    pub time: SystemTime,
+    pub value: T,
+}
+
+fn push<T>(samples: &mut VecDeque<Sample<T>>, value: T) {
+    let time = SystemTime::now();
+    samples.push_back(Sample { time, value });
+    
+    while samples.len() > SAMPLE_LIMIT
+        || samples.front().is_some_and(|sample| time.duration_since(sample.time).unwrap_or_default() > HISTORY_WINDOW)
+    {
+        samples.pop_front();
+    }
+}
+
+/// Rolling RSSI and battery readings of a device.
+#[derive(Debug, Clone, Default)]
+pub struct SignalHistory {
+    pub rssi: VecDeque<Sample<i16>>,
+    pub battery: VecDeque<Sample<u8>>,
+}
+
+impl SignalHistory {
+    pub fn is_empty(&self) -> bool {
+        self.rssi.is_empty() && self.battery.is_empty()
+    }
+    
+    /// Record an RSSI or battery change, returning whether the property was one.
+    pub fn update(&mut self, property: &DeviceProperty) -> bool {
+        match property {
+            DeviceProperty::Rssi(rssi) => push(&mut self.rssi, *rssi),
+            DeviceProperty::BatteryPercentage(percentage) => push(&mut self.battery, *percentage),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Signal history of every device seen, recorded by the adapter watchers.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryStore {
+    devices: Arc<Mutex<HashMap<String, SignalHistory>>>,
+}
+
+impl HistoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn update(&self, address: &str, property: &DeviceProperty) -> bool {
+        self.devices.lock().unwrap()
+            .entry(address.to_string())
+            .or_default()
+            .update(property)
+    }
+    
+    pub fn get(&self, address: &str) -> Option<SignalHistory> {
+        self.devices.lock().unwrap().get(address).cloned()
+    }
+    
+    pub fn remove(&self, address: &str) {
+        self.devices.lock().unwrap().remove(address);
+    }
+}
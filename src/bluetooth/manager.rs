@@ -1,40 +1,410 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::Result;
-use bluer::{Address, Session};
+use bluer::monitor::Pattern;
+use bluer::{Address, Session, SessionEvent};
+use futures::StreamExt;
 use log::{debug, error, info, warn};
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::AbortHandle;
 
-use crate::bluetooth::adapter::Adapter;
+use crate::bluetooth::adapter::{Adapter, DiscoveryOwner};
+use crate::bluetooth::advertisement::{Advertisement, AdvertisementStore};
+use crate::bluetooth::{beacon, vendor};
 use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::events::BluetoothEvent;
+use crate::bluetooth::gatt::GattClient;
+use crate::bluetooth::history::{HistoryStore, SignalHistory};
+use crate::bluetooth::power::{PowerPolicy, PowerState};
+use crate::bluetooth::records::DeviceRecords;
+use crate::bluetooth::reconnect::Reconnector;
+use crate::bluetooth::rfkill;
 
-#[derive(Debug)]
+/// How many events a slow subscriber may fall behind before it starts missing some
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+/// BlueZ refuses to power an adapter until it noticed the rfkill unblock itself
+const RFKILL_SETTLE_DELAY: Duration = Duration::from_secs(1);
+/// How often sightings and the time of ongoing connections are written to the device records
+const RECORDS_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Cheap to clone, clones share the same adapters, stores and event channel.
+#[derive(Debug, Clone)]
 pub struct BluetoothManager {
     session: Session,
     adapters: Arc<RwLock<HashMap<String, Adapter>>>,
     default_adapter: Arc<RwLock<Option<String>>>,
+    events: broadcast::Sender<BluetoothEvent>,
+    reconnector: Reconnector,
+    advertisements: AdvertisementStore,
+    history: HistoryStore,
+    records: DeviceRecords,
+    /// The running advertisement monitor, see `monitor_presence`
+    presence_monitor: Arc<Mutex<Option<AbortHandle>>>,
+    /// Whether each adapter was powered when the system went to sleep, see `resume`
+    powered_before_sleep: Arc<Mutex<HashMap<String, bool>>>,
+    power: PowerState,
 }
 
 impl BluetoothManager {
-    pub async fn new() -> Result<Self> {
+    pub async fn new(power_policy: PowerPolicy) -> Result<Self> {
         info!("Initializing Bluetooth manager");
         
         let session = Session::new().await?;
         let adapters = Arc::new(RwLock::new(HashMap::new()));
         let default_adapter = Arc::new(RwLock::new(None));
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let reconnector = Reconnector::new(events.clone());
         
         let manager = Self {
             session,
             adapters,
             default_adapter,
+            events,
+            reconnector,
+            advertisements: AdvertisementStore::new(),
+            history: HistoryStore::new(),
+            records: DeviceRecords::load(),
+            presence_monitor: Arc::new(Mutex::new(None)),
+            powered_before_sleep: Arc::new(Mutex::new(HashMap::new())),
+            power: PowerState::load(power_policy),
         };
         
         manager.discover_adapters().await?;
+        manager.watch_events().await;
+        manager.watch_reconnects();
+        manager.watch_adapters().await;
+        manager.watch_rfkill();
+        manager.save_records();
+        
+        let adapters: Vec<Adapter> = manager.adapters.read().await.values().cloned().collect();
+        for adapter in adapters {
+            manager.apply_power_policy(&adapter).await;
+        }
         
         Ok(manager)
     }
     
+    async fn watch_events(&self) {
+        let adapters = self.adapters.read().await;
+        for adapter in adapters.values() {
+            if let Err(e) = adapter.watch_events(self.events.clone(), self.advertisements.clone(), self.history.clone(), self.records.clone()).await {
+                error!("Failed to watch events of adapter {}: {}", adapter.name(), e);
+            }
+        }
+    }
+    
+    /// Pick up adapters plugged in after startup and forget unplugged ones.
+    async fn watch_adapters(&self) {
+        let mut events = match self.session.events().await {
+            Ok(events) => Box::pin(events),
+            Err(e) => {
+                error!("Failed to watch for new adapters: {}", e);
+                return;
+            }
+        };
+        
+        let manager = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                match event {
+                    SessionEvent::AdapterAdded(name) => manager.adapter_added(name).await,
+                    SessionEvent::AdapterRemoved(name) => manager.adapter_removed(&name).await,
+                }
+            }
+            debug!("Session event stream ended");
+        });
+    }
+    
+    async fn adapter_added(&self, name: String) {
+        if self.adapters.read().await.contains_key(&name) {
+            return;
+        }
+        
+        let adapter = match self.session.adapter(&name) {
+            Ok(bluer_adapter) => match Adapter::new(bluer_adapter, name.clone()).await {
+                Ok(adapter) => adapter,
+                Err(e) => {
+                    error!("Failed to initialize adapter {}: {}", name, e);
+                    return;
+                }
+            },
+            Err(e) => {
+                error!("Failed to get adapter {}: {}", name, e);
+                return;
+            }
+        };
+        info!("Adapter added: {}", name);
+        
+        if let Err(e) = adapter.watch_events(self.events.clone(), self.advertisements.clone(), self.history.clone(), self.records.clone()).await {
+            error!("Failed to watch events of adapter {}: {}", name, e);
+        }
+        self.adapters.write().await.insert(name.clone(), adapter.clone());
+        {
+            let mut default_adapter = self.default_adapter.write().await;
+            if default_adapter.is_none() {
+                *default_adapter = Some(name.clone());
+                info!("Set default adapter: {}", name);
+            }
+        }
+        
+        self.apply_power_policy(&adapter).await;
+        let _ = self.events.send(BluetoothEvent::AdapterAdded { adapter: name });
+    }
+    
+    async fn adapter_removed(&self, name: &str) {
+        let mut adapters = self.adapters.write().await;
+        if adapters.remove(name).is_none() {
+            return;
+        }
+        info!("Adapter removed: {}", name);
+        
+        let mut default_adapter = self.default_adapter.write().await;
+        if default_adapter.as_deref() == Some(name) {
+            *default_adapter = adapters.keys().next().cloned();
+        }
+        let _ = self.events.send(BluetoothEvent::AdapterRemoved { adapter: name.to_string() });
+    }
+    
+    /// Apply the power policy again once rfkill unblocks Bluetooth.
+    fn watch_rfkill(&self) {
+        let mut unblocked = match rfkill::watch_unblock() {
+            Ok(unblocked) => unblocked,
+            Err(e) => {
+                warn!("Not watching rfkill: {}", e);
+                return;
+            }
+        };
+        
+        let manager = self.clone();
+        tokio::spawn(async move {
+            while unblocked.recv().await.is_some() {
+                tokio::time::sleep(RFKILL_SETTLE_DELAY).await;
+                let adapters: Vec<Adapter> = manager.adapters.read().await.values().cloned().collect();
+                for adapter in adapters {
+                    manager.apply_power_policy(&adapter).await;
+                }
+            }
+        });
+    }
+    
+    /// Write the device records now and then, connections are saved as they change already.
+    fn save_records(&self) {
+        let records = self.records.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RECORDS_SAVE_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                records.checkpoint();
+            }
+        });
+    }
+    
+    /// Save the device records, e.g. before quitting.
+    pub fn save_device_records(&self) {
+        self.records.checkpoint();
+    }
+    
+    /// Power `adapter` on or off as the power policy wants.
+    async fn apply_power_policy(&self, adapter: &Adapter) {
+        let address = match adapter.address().await {
+            Ok(address) => address,
+            Err(e) => {
+                warn!("Failed to read address of {}: {}", adapter.name(), e);
+                return;
+            }
+        };
+        let Some(wanted) = self.power.wanted(&address) else {
+            return;
+        };
+        
+        match adapter.is_powered().await {
+            Ok(powered) if powered == wanted => {}
+            Ok(_) => {
+                info!("Powering {} {} as set in the power policy", adapter.name(), if wanted { "on" } else { "off" });
+                if let Err(e) = adapter.set_powered(wanted).await {
+                    warn!("Failed to apply power policy to {}: {}", adapter.name(), e);
+                }
+            }
+            Err(e) => warn!("Failed to read power state of {}: {}", adapter.name(), e),
+        }
+    }
+    
+    pub fn set_power_policy(&self, policy: PowerPolicy) {
+        self.power.set_policy(policy);
+    }
+    
+    /// Start and stop reconnect attempts as devices drop out and adapters power up.
+    fn watch_reconnects(&self) {
+        let mut receiver = self.events.subscribe();
+        let adapters = self.adapters.clone();
+        let reconnector = self.reconnector.clone();
+        
+        tokio::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                
+                match event {
+                    BluetoothEvent::DeviceConnected { address, .. } => {
+                        reconnector.device_connected(&address);
+                    }
+                    BluetoothEvent::DeviceDisconnected { adapter, address } => {
+                        if let Some(adapter) = adapters.read().await.get(&adapter).cloned() {
+                            reconnector.device_lost(adapter, &address);
+                        }
+                    }
+                    BluetoothEvent::AdapterPowered { adapter, powered: true } => {
+                        if let Some(adapter) = adapters.read().await.get(&adapter).cloned() {
+                            reconnector.reconnect_all(adapter).await;
+                        }
+                    }
+                    BluetoothEvent::AdapterPowered { powered: false, .. } => {
+                        reconnector.cancel_all();
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+    
+    /// Devices to reconnect when they drop out, at startup and after power-on.
+    pub fn set_auto_connect_devices(&self, addresses: HashSet<String>) {
+        self.reconnector.set_favourites(addresses);
+    }
+    
+    /// Reconnect all auto-connect devices that are not connected right now.
+    pub async fn reconnect_favourites(&self) {
+        if let Some(adapter) = self.get_default_adapter().await {
+            self.reconnector.reconnect_all(adapter).await;
+        }
+    }
+    
+    /// Stop what should not run across a suspend and remember the power state of the adapters.
+    pub async fn prepare_for_sleep(&self) {
+        info!("Preparing Bluetooth for sleep");
+        self.reconnector.cancel_all();
+        self.stop_presence_monitor();
+        
+        let adapters: Vec<Adapter> = self.adapters.read().await.values().cloned().collect();
+        let mut powered = HashMap::new();
+        for adapter in adapters {
+            match adapter.is_powered().await {
+                Ok(is_powered) => {
+                    powered.insert(adapter.name().to_string(), is_powered);
+                }
+                Err(e) => warn!("Failed to read power state of {}: {}", adapter.name(), e),
+            }
+        }
+        *self.powered_before_sleep.lock().unwrap() = powered;
+        self.records.checkpoint();
+        
+        if let Some(adapter) = self.get_default_adapter().await {
+            adapter.end_discovery();
+        }
+    }
+    
+    /// Pick up adapters that came or went while asleep, power them as they were before and
+    /// reconnect the auto-connect devices.
+    pub async fn resume(&self) -> Result<()> {
+        info!("Restoring Bluetooth after resume");
+        let known: HashSet<String> = self.list_adapters().await.into_iter().collect();
+        self.discover_adapters().await?;
+        
+        let powered = std::mem::take(&mut *self.powered_before_sleep.lock().unwrap());
+        let adapters: Vec<Adapter> = self.adapters.read().await.values().cloned().collect();
+        for adapter in adapters {
+            if !known.contains(adapter.name()) {
+                if let Err(e) = adapter.watch_events(self.events.clone(), self.advertisements.clone(), self.history.clone(), self.records.clone()).await {
+                    error!("Failed to watch events of adapter {}: {}", adapter.name(), e);
+                }
+            }
+            
+            let Some(&was_powered) = powered.get(adapter.name()) else {
+                continue;
+            };
+            match adapter.is_powered().await {
+                Ok(is_powered) if is_powered != was_powered => {
+                    if let Err(e) = adapter.set_powered(was_powered).await {
+                        warn!("Failed to restore power state of {}: {}", adapter.name(), e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to read power state of {}: {}", adapter.name(), e),
+            }
+        }
+        
+        self.reconnect_favourites().await;
+        Ok(())
+    }
+    
+    /// What the device advertised most recently, if it was seen advertising.
+    pub fn advertisement(&self, address: &str) -> Option<Advertisement> {
+        self.advertisements.get(address).filter(|advertisement| !advertisement.is_empty())
+    }
+    
+    /// Recent RSSI and battery readings of the device.
+    pub fn signal_history(&self, address: &str) -> Option<SignalHistory> {
+        self.history.get(address).filter(|history| !history.is_empty())
+    }
+    
+    /// Passively watch `addresses` with an advertisement monitor on the default adapter instead
+    /// of a discovery session, replacing the previous monitor. Their presence is broadcast as
+    /// `PresenceChanged` events.
+    ///
+    /// The monitor matches on what the devices advertised before, so this fails for devices not
+    /// seen advertising yet, and with BlueZ versions or controllers lacking monitor support.
+    pub async fn monitor_presence(&self, addresses: &HashSet<String>, rssi_high: i16, rssi_low: i16, timeout: Duration) -> Result<()> {
+        self.stop_presence_monitor();
+        
+        let mut patterns = Vec::new();
+        for address in addresses {
+            if let Some(advertisement) = self.advertisements.get(address) {
+                for pattern in advertisement.patterns() {
+                    let known = patterns.iter()
+                        .any(|known: &Pattern| known.data_type == pattern.data_type && known.content == pattern.content);
+                    if !known {
+                        patterns.push(pattern);
+                    }
+                }
+            }
+        }
+        if patterns.is_empty() {
+            return Err(anyhow::anyhow!("None of the devices has been seen advertising yet"));
+        }
+        
+        let addresses = addresses.iter()
+            .map(|address| address.parse())
+            .collect::<Result<HashSet<Address>, _>>()?;
+        let adapter = self.get_default_adapter().await
+            .ok_or_else(|| anyhow::anyhow!("No default adapter available"))?;
+        let handle = adapter.monitor_presence(patterns, addresses, rssi_high, rssi_low, timeout, self.events.clone()).await?;
+        *self.presence_monitor.lock().unwrap() = Some(handle);
+        Ok(())
+    }
+    
+    /// Whether the monitor of `monitor_presence` is still registered, it is stopped for sleep.
+    pub fn is_monitoring_presence(&self) -> bool {
+        self.presence_monitor.lock().unwrap().as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+    
+    pub fn stop_presence_monitor(&self) {
+        if let Some(handle) = self.presence_monitor.lock().unwrap().take() {
+            info!("Stopping presence monitor");
+            handle.abort();
+        }
+    }
+    
+    /// Stream of device and adapter changes, e.g. connections made outside RustBlue.
+    pub fn subscribe(&self) -> broadcast::Receiver<BluetoothEvent> {
+        self.events.subscribe()
+    }
+    
     pub async fn discover_adapters(&self) -> Result<()> {
         debug!("Discovering Bluetooth adapters");
         
@@ -42,32 +412,45 @@ impl BluetoothManager {
         let mut adapters = self.adapters.write().await;
         let mut default_adapter = self.default_adapter.write().await;
         
-        adapters.clear();
+        // Adapters that are still there are kept, with their discovery session
+        let mut previous = std::mem::take(&mut *adapters);
+        
+        // The default adapter may be gone, e.g. a dongle unplugged while suspended
+        if default_adapter.as_ref().is_some_and(|name| !adapter_names.contains(name)) {
+            *default_adapter = None;
+        }
         
         for name in adapter_names {
-                match self.session.adapter(&name) {
-                    Ok(bluer_adapter) => {
-                        match Adapter::new(bluer_adapter, name.clone()).await {
-                            Ok(adapter) => {
-                                info!("Found adapter: {}", adapter.name());
-                                
-                                // Set first adapter as default if none is set
-                                if default_adapter.is_none() {
-                                    *default_adapter = Some(name.clone());
-                                    info!("Set default adapter: {}", name);
-                                }
-                                
-                                adapters.insert(name, adapter);
-                            }
-                            Err(e) => {
-                                error!("Failed to initialize adapter {}: {}", name, e);
+            if let Some(adapter) = previous.remove(&name) {
+                if default_adapter.is_none() {
+                    *default_adapter = Some(name.clone());
+                }
+                adapters.insert(name, adapter);
+                continue;
+            }
+            match self.session.adapter(&name) {
+                Ok(bluer_adapter) => {
+                    match Adapter::new(bluer_adapter, name.clone()).await {
+                        Ok(adapter) => {
+                            info!("Found adapter: {}", adapter.name());
+                            
+                            // Set first adapter as default if none is set
+                            if default_adapter.is_none() {
+                                *default_adapter = Some(name.clone());
+                                info!("Set default adapter: {}", name);
                             }
+                            
+                            adapters.insert(name, adapter);
+                        }
+                        Err(e) => {
+                            error!("Failed to initialize adapter {}: {}", name, e);
                         }
-                    }
-                    Err(e) => {
-                        error!("Failed to get adapter {}: {}", name, e);
                     }
                 }
+                Err(e) => {
+                    error!("Failed to get adapter {}: {}", name, e);
+                }
+            }
         }
         
         info!("Discovered {} adapters", adapters.len());
@@ -94,24 +477,45 @@ impl BluetoothManager {
         adapters.keys().cloned().collect()
     }
     
-    pub async fn start_discovery(&self) -> Result<()> {
+    /// The Bluetooth addresses of the adapters, e.g. to match them with those of another system.
+    pub async fn adapter_addresses(&self) -> Vec<String> {
+        let adapters: Vec<Adapter> = self.adapters.read().await.values().cloned().collect();
+        let mut addresses = Vec::new();
+        for adapter in adapters {
+            match adapter.address().await {
+                Ok(address) => addresses.push(address),
+                Err(e) => warn!("Failed to read the address of {}: {}", adapter.name(), e),
+            }
+        }
+        addresses
+    }
+    
+    pub async fn start_discovery(&self, owner: DiscoveryOwner) -> Result<()> {
         if let Some(adapter) = self.get_default_adapter().await {
-            adapter.start_discovery().await?;
+            adapter.start_discovery(owner).await?;
         } else {
             warn!("No default adapter available for discovery");
         }
         Ok(())
     }
     
-    pub async fn stop_discovery(&self) -> Result<()> {
+    pub async fn stop_discovery(&self, owner: DiscoveryOwner) -> Result<()> {
         if let Some(adapter) = self.get_default_adapter().await {
-            adapter.stop_discovery().await?;
+            adapter.stop_discovery(owner).await?;
         } else {
             warn!("No default adapter available to stop discovery");
         }
         Ok(())
     }
     
+    /// Whether `owner` is discovering on the default adapter.
+    pub async fn is_discovering(&self, owner: DiscoveryOwner) -> bool {
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.is_discovering(owner),
+            None => false,
+        }
+    }
+    
     pub async fn get_devices(&self) -> Result<Vec<BluetoothDevice>> {
         debug!("Getting known devices");
         let mut devices = Vec::new();
@@ -124,6 +528,25 @@ impl BluetoothManager {
             }
         }
         
+        for device in devices.iter_mut() {
+            device.reconnecting = self.reconnector.is_reconnecting(&device.address);
+            if let Some(advertisement) = self.advertisements.get(&device.address) {
+                device.beacon = beacon::decode(&advertisement).first().map(|beacon| beacon.kind().to_string());
+                device.distance = beacon::distance(&advertisement);
+                device.last_seen = advertisement.last_seen;
+                if device.vendor.is_none() {
+                    let company = advertisement.manufacturer_data.keys().find_map(|id| vendor::company_name(*id));
+                    if let Some(company) = company {
+                        device.set_vendor(company);
+                    }
+                }
+            }
+            if let Some(record) = self.records.get(&device.address) {
+                device.last_seen = device.last_seen.max(record.last_seen);
+                device.record = Some(record);
+            }
+        }
+        
         debug!("Found {} devices", devices.len());
         Ok(devices)
     }
@@ -132,7 +555,13 @@ impl BluetoothManager {
         let addr: Address = address.parse()?;
         
         if let Some(adapter) = self.get_default_adapter().await {
-            adapter.connect_device(addr).await?;
+            if let Err(e) = adapter.connect_device(addr).await {
+                let _ = self.events.send(BluetoothEvent::ConnectFailed {
+                    address: address.to_string(),
+                    error: e.to_string(),
+                });
+                return Err(e);
+            }
         } else {
             return Err(anyhow::anyhow!("No default adapter available"));
         }
@@ -142,6 +571,7 @@ impl BluetoothManager {
     
     pub async fn disconnect_device(&self, address: &str) -> Result<()> {
         let addr: Address = address.parse()?;
+        self.reconnector.suppress(address);
         
         if let Some(adapter) = self.get_default_adapter().await {
             adapter.disconnect_device(addr).await?;
@@ -152,11 +582,63 @@ impl BluetoothManager {
         Ok(())
     }
     
+    pub async fn is_device_blocked(&self, address: &str) -> Result<bool> {
+        let addr: Address = address.parse()?;
+        
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.is_device_blocked(addr).await,
+            None => Err(anyhow::anyhow!("No default adapter available")),
+        }
+    }
+    
+    /// Give the device a local name, or go back to its own name with `None`.
+    pub async fn set_device_alias(&self, address: &str, alias: Option<&str>) -> Result<()> {
+        let addr: Address = address.parse()?;
+        
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.set_device_alias(addr, alias.unwrap_or("")).await,
+            None => Err(anyhow::anyhow!("No default adapter available")),
+        }
+    }
+    
+    pub async fn set_device_trusted(&self, address: &str, trusted: bool) -> Result<()> {
+        let addr: Address = address.parse()?;
+        
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.set_device_trusted(addr, trusted).await,
+            None => Err(anyhow::anyhow!("No default adapter available")),
+        }
+    }
+    
+    pub async fn set_device_blocked(&self, address: &str, blocked: bool) -> Result<()> {
+        let addr: Address = address.parse()?;
+        
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.set_device_blocked(addr, blocked).await,
+            None => Err(anyhow::anyhow!("No default adapter available")),
+        }
+    }
+    
+    pub async fn gatt_client(&self, address: &str) -> Result<GattClient> {
+        let addr: Address = address.parse()?;
+        
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.gatt_client(addr),
+            None => Err(anyhow::anyhow!("No default adapter available")),
+        }
+    }
+    
     pub async fn pair_device(&self, address: &str) -> Result<()> {
         let addr: Address = address.parse()?;
         
         if let Some(adapter) = self.get_default_adapter().await {
-            adapter.pair_device(addr).await?;
+            if let Err(e) = adapter.pair_device(addr).await {
+                let _ = self.events.send(BluetoothEvent::PairingFailed {
+                    address: address.to_string(),
+                    error: e.to_string(),
+                });
+                return Err(e);
+            }
         } else {
             return Err(anyhow::anyhow!("No default adapter available"));
         }
@@ -166,6 +648,7 @@ impl BluetoothManager {
     
     pub async fn remove_device(&self, address: &str) -> Result<()> {
         let addr: Address = address.parse()?;
+        self.reconnector.suppress(address);
         
         if let Some(adapter) = self.get_default_adapter().await {
             adapter.remove_device(addr).await?;
@@ -176,9 +659,25 @@ impl BluetoothManager {
         Ok(())
     }
     
+    pub async fn is_adapter_powered(&self) -> Result<bool> {
+        match self.get_default_adapter().await {
+            Some(adapter) => adapter.is_powered().await,
+            None => Err(anyhow::anyhow!("No default adapter available")),
+        }
+    }
+    
+    /// Switch the default adapter on or off on behalf of the user, which the power policy remembers.
     pub async fn set_adapter_powered(&self, powered: bool) -> Result<()> {
         if let Some(adapter) = self.get_default_adapter().await {
+            // The header bar switch follows every power change, only remember actual changes
+            if adapter.is_powered().await? == powered {
+                return Ok(());
+            }
             adapter.set_powered(powered).await?;
+            match adapter.address().await {
+                Ok(address) => self.power.remember(&address, powered),
+                Err(e) => warn!("Failed to remember power state of {}: {}", adapter.name(), e),
+            }
         } else {
             return Err(anyhow::anyhow!("No default adapter available"));
         }
@@ -1,3 +1,14 @@
 pub mod manager;
 pub mod device;
 pub mod adapter;
+pub mod events;
+pub mod reconnect;
+pub mod gatt;
+pub mod advertisement;
+pub mod beacon;
+pub mod vendor;
+pub mod uuids;
+pub mod history;
+pub mod power;
+pub mod records;
+pub mod rfkill;
\ No newline at end of file
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+/// Power state adapters are put in when they appear: at startup, when plugged in and when
+/// rfkill unblocks Bluetooth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerPolicy {
+    /// As the user last switched it, left to BlueZ until they have
+    #[default]
+    Remember,
+    On,
+    Off,
+}
+
+impl PowerPolicy {
+    pub const ALL: [PowerPolicy; 3] = [PowerPolicy::Remember, PowerPolicy::On, PowerPolicy::Off];
+}
+
+impl fmt::Display for PowerPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PowerPolicy::Remember => "Remember Last State",
+            PowerPolicy::On => "Always On",
+            PowerPolicy::Off => "Always Off",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The policy and the power state last chosen per adapter address, which is kept in
+/// `~/.local/state/rustblue/power.json` so it survives restarts.
+#[derive(Debug, Clone, Default)]
+pub struct PowerState {
+    policy: Arc<Mutex<PowerPolicy>>,
+    remembered: Arc<Mutex<HashMap<String, bool>>>,
+}
+
+impl PowerState {
+    fn path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .map(|dir| dir.join("rustblue").join("power.json"))
+    }
+    
+    pub fn load(policy: PowerPolicy) -> Self {
+        let remembered = match Self::path().map(fs::read_to_string) {
+            Some(Ok(contents)) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Failed to parse remembered power state: {}", e);
+                HashMap::new()
+            }),
+            _ => HashMap::new(),
+        };
+        
+        Self {
+            policy: Arc::new(Mutex::new(policy)),
+            remembered: Arc::new(Mutex::new(remembered)),
+        }
+    }
+    
+    fn save(remembered: &HashMap<String, bool>) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("No state directory available"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(remembered)?)?;
+        debug!("Saved power state to {}", path.display());
+        Ok(())
+    }
+    
+    pub fn set_policy(&self, policy: PowerPolicy) {
+        *self.policy.lock().unwrap() = policy;
+    }
+    
+    /// The user switched the adapter with `address` on or off.
+    pub fn remember(&self, address: &str, powered: bool) {
+        let mut remembered = self.remembered.lock().unwrap();
+        if remembered.insert(address.to_string(), powered) != Some(powered) {
+            if let Err(e) = Self::save(&remembered) {
+                warn!("Failed to save power state: {}", e);
+            }
+        }
+    }
+    
+    /// What the adapter with `address` should be powered to, `None` to leave it as it is.
+    pub fn wanted(&self, address: &str) -> Option<bool> {
+        match *self.policy.lock().unwrap() {
+            PowerPolicy::Remember => self.remembered.lock().unwrap().get(address).copied(),
+            PowerPolicy::On => Some(true),
+            PowerPolicy::Off => Some(false),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bluer::Address;
+use log::{debug, info, warn};
+use tokio::sync::broadcast;
+use tokio::task::AbortHandle;
+
+use crate::bluetooth::adapter::Adapter;
+use crate::bluetooth::events::{BluetoothEvent, ReconnectOutcome};
+
+/// Give the device, and BlueZ itself, a moment before the first attempt
+const INITIAL_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(300);
+/// With the delay doubling up to `MAX_DELAY` this keeps trying for about half an hour
+const MAX_ATTEMPTS: u32 = 12;
+
+/// Reconnects trusted devices marked for auto-connect, retrying with exponential backoff.
+#[derive(Debug, Clone)]
+pub struct Reconnector {
+    favourites: Arc<Mutex<HashSet<String>>>,
+    /// Devices the user disconnected on purpose, left alone until they connect again
+    suppressed: Arc<Mutex<HashSet<String>>>,
+    attempts: Arc<Mutex<HashMap<String, AbortHandle>>>,
+    events: broadcast::Sender<BluetoothEvent>,
+}
+
+impl Reconnector {
+    pub fn new(events: broadcast::Sender<BluetoothEvent>) -> Self {
+        Self {
+            favourites: Arc::new(Mutex::new(HashSet::new())),
+            suppressed: Arc::new(Mutex::new(HashSet::new())),
+            attempts: Arc::new(Mutex::new(HashMap::new())),
+            events,
+        }
+    }
+    
+    pub fn set_favourites(&self, addresses: HashSet<String>) {
+        debug!("Auto-connect devices: {:?}", addresses);
+        let mut favourites = self.favourites.lock().unwrap();
+        
+        // Stop retrying devices that are no longer favourites
+        for address in favourites.difference(&addresses) {
+            self.cancel(address);
+        }
+        *favourites = addresses;
+    }
+    
+    pub fn is_reconnecting(&self, address: &str) -> bool {
+        self.attempts.lock().unwrap().contains_key(address)
+    }
+    
+    /// Called before the user disconnects a device, so that is not treated as a dropout.
+    pub fn suppress(&self, address: &str) {
+        self.suppressed.lock().unwrap().insert(address.to_string());
+        self.cancel(address);
+    }
+    
+    pub fn device_connected(&self, address: &str) {
+        self.suppressed.lock().unwrap().remove(address);
+    }
+    
+    /// A device disconnected without the user asking for it.
+    pub fn device_lost(&self, adapter: Adapter, address: &str) {
+        if !self.favourites.lock().unwrap().contains(address) {
+            return;
+        }
+        if self.suppressed.lock().unwrap().contains(address) {
+            debug!("Not reconnecting {}, it was disconnected on purpose", address);
+            return;
+        }
+        
+        self.reconnect(adapter, address);
+    }
+    
+    /// Try to reconnect every favourite that is not connected, e.g. at startup or after power-on.
+    pub async fn reconnect_all(&self, adapter: Adapter) {
+        let favourites: Vec<String> = self.favourites.lock().unwrap().iter().cloned().collect();
+        let suppressed = self.suppressed.lock().unwrap().clone();
+        
+        for address in favourites.iter().filter(|address| !suppressed.contains(*address)) {
+            // Connected ones would only announce an attempt and end it two seconds later
+            let connected = match address.parse() {
+                Ok(addr) => adapter.is_device_connected(addr).await.unwrap_or(false),
+                Err(_) => false,
+            };
+            if !connected {
+                self.reconnect(adapter.clone(), address);
+            }
+        }
+    }
+    
+    pub fn cancel(&self, address: &str) {
+        if let Some(attempt) = self.attempts.lock().unwrap().remove(address) {
+            info!("Stopped reconnecting to {}", address);
+            attempt.abort();
+            let _ = self.events.send(BluetoothEvent::ReconnectStopped {
+                address: address.to_string(),
+                outcome: ReconnectOutcome::Stopped,
+            });
+        }
+    }
+    
+    pub fn cancel_all(&self) {
+        let addresses: Vec<String> = self.attempts.lock().unwrap().keys().cloned().collect();
+        for address in addresses {
+            self.cancel(&address);
+        }
+    }
+    
+    fn reconnect(&self, adapter: Adapter, address: &str) {
+        let addr: Address = match address.parse() {
+            Ok(addr) => addr,
+            Err(e) => {
+                warn!("Invalid address {}: {}", address, e);
+                return;
+            }
+        };
+        
+        // Held until the attempt is recorded, so a quickly finishing task cannot race it
+        let mut attempts = self.attempts.lock().unwrap();
+        if attempts.contains_key(address) {
+            return;
+        }
+        
+        let reconnector = self.clone();
+        let address = address.to_string();
+        let task = tokio::spawn({
+            let address = address.clone();
+            async move {
+                let outcome = reconnector.run(&adapter, addr).await;
+                if reconnector.attempts.lock().unwrap().remove(&address).is_some() {
+                    let _ = reconnector.events.send(BluetoothEvent::ReconnectStopped { address, outcome });
+                }
+            }
+        });
+        
+        attempts.insert(address.clone(), task.abort_handle());
+        let _ = self.events.send(BluetoothEvent::ReconnectStarted { address });
+    }
+    
+    /// Retry until the device is connected.
+    async fn run(&self, adapter: &Adapter, address: Address) -> ReconnectOutcome {
+        match adapter.is_device_trusted(address).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("Not reconnecting {}, it is not trusted", address);
+                return ReconnectOutcome::Stopped;
+            }
+            Err(e) => {
+                warn!("Failed to check whether {} is trusted: {}", address, e);
+                return ReconnectOutcome::Stopped;
+            }
+        }
+        
+        let mut delay = INITIAL_DELAY;
+        for attempt in 1..=MAX_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            
+            if !adapter.is_powered().await.unwrap_or(false) {
+                debug!("Adapter {} is off, giving up on {}", adapter.name(), address);
+                return ReconnectOutcome::Stopped;
+            }
+            if adapter.is_device_connected(address).await.unwrap_or(false) {
+                return ReconnectOutcome::Connected;
+            }
+            
+            info!("Reconnecting to {} (attempt {}/{})", address, attempt, MAX_ATTEMPTS);
+            match adapter.connect_device(address).await {
+                Ok(()) => {
+                    info!("Reconnected to {}", address);
+                    return ReconnectOutcome::Connected;
+                }
+                Err(e) => debug!("Reconnecting to {} failed: {}", address, e),
+            }
+            
+            delay = (delay * 2).min(MAX_DELAY);
+        }
+        
+        warn!("Giving up reconnecting to {} after {} attempts", address, MAX_ATTEMPTS);
+        ReconnectOutcome::GaveUp
+    }
+}
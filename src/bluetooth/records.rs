@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+/// Devices that never connected are forgotten after this long without being seen, LE devices
+/// with random addresses would pile up otherwise
+const FORGET_AFTER: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// `time` relative to now, e.g. "2 min ago".
+pub fn format_ago(time: SystemTime) -> String {
+    let seconds = time.elapsed().unwrap_or_default().as_secs();
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", seconds / 60),
+        3600..=86399 => format!("{} h ago", seconds / 3600),
+        86400..=172799 => "yesterday".to_string(),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
+
+/// A duration in the largest units that matter, e.g. "3 h 12 min".
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match minutes {
+        0 => format!("{} s", duration.as_secs()),
+        1..=59 => format!("{} min", minutes),
+        60..=1439 => format!("{} h {} min", minutes / 60, minutes % 60),
+        _ => format!("{} d {} h", minutes / 1440, minutes % 1440 / 60),
+    }
+}
+
+/// When a device was around and connected, kept across sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceRecord {
+    pub first_seen: Option<SystemTime>,
+    /// Last advertisement or connection change
+    pub last_seen: Option<SystemTime>,
+    /// Last time the device was connected, i.e. when it disconnected or connected last
+    pub last_connected: Option<SystemTime>,
+    pub connections: u32,
+    /// Time spent connected, without the current connection since its last checkpoint
+    pub connected_time: Duration,
+    /// Start of the current connection
+    #[serde(skip)]
+    pub connected_since: Option<SystemTime>,
+}
+
+impl DeviceRecord {
+    /// Time spent connected, including the current connection.
+    pub fn total_connected_time(&self) -> Duration {
+        let current = self.connected_since
+            .and_then(|since| since.elapsed().ok())
+            .unwrap_or_default();
+        self.connected_time + current
+    }
+    
+    fn seen(&mut self, time: SystemTime) {
+        self.first_seen.get_or_insert(time);
+        self.last_seen = Some(time);
+    }
+    
+    fn is_stale(&self, now: SystemTime) -> bool {
+        self.connections == 0
+            && self.last_seen
+                .and_then(|seen| now.duration_since(seen).ok())
+                .is_none_or(|age| age > FORGET_AFTER)
+    }
+}
+
+/// The records of every device by address, in `~/.local/share/rustblue/devices.json`.
+///
+/// Connections are saved right away; sightings only mark the store changed, as devices
+/// advertise many times a second, and are saved by `checkpoint`.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceRecords {
+    records: Arc<Mutex<HashMap<String, DeviceRecord>>>,
+    changed: Arc<AtomicBool>,
+}
+
+impl DeviceRecords {
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("rustblue").join("devices.json"))
+    }
+    
+    pub fn load() -> Self {
+        let mut records: HashMap<String, DeviceRecord> = match Self::path().map(fs::read_to_string) {
+            Some(Ok(contents)) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Failed to parse device records: {}", e);
+                HashMap::new()
+            }),
+            _ => HashMap::new(),
+        };
+        let now = SystemTime::now();
+        records.retain(|_, record| !record.is_stale(now));
+        debug!("Loaded records of {} devices", records.len());
+        
+        Self {
+            records: Arc::new(Mutex::new(records)),
+            changed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    
+    fn save(records: &HashMap<String, DeviceRecord>) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("No data directory available"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_string(records)?)?;
+        debug!("Saved device records to {}", path.display());
+        Ok(())
+    }
+    
+    fn update<F>(&self, address: &str, save: bool, update: F)
+    where
+        F: FnOnce(&mut DeviceRecord, SystemTime),
+    {
+        let mut records = self.records.lock().unwrap();
+        update(records.entry(address.to_string()).or_default(), SystemTime::now());
+        
+        if !save {
+            self.changed.store(true, Ordering::Relaxed);
+        } else if let Err(e) = Self::save(&records) {
+            warn!("Failed to save device records: {}", e);
+        } else {
+            self.changed.store(false, Ordering::Relaxed);
+        }
+    }
+    
+    /// The device advertised.
+    pub fn seen(&self, address: &str) {
+        self.update(address, false, |record, now| record.seen(now));
+    }
+    
+    pub fn connected(&self, address: &str) {
+        self.update(address, true, |record, now| {
+            record.seen(now);
+            record.last_connected = Some(now);
+            record.connections += 1;
+            record.connected_since = Some(now);
+        });
+    }
+    
+    /// The device was connected before RustBlue started, which is not counted as a new connection.
+    pub fn already_connected(&self, address: &str) {
+        self.update(address, false, |record, now| {
+            record.seen(now);
+            record.last_connected = Some(now);
+            record.connected_since.get_or_insert(now);
+        });
+    }
+    
+    pub fn disconnected(&self, address: &str) {
+        self.update(address, true, |record, now| {
+            record.seen(now);
+            record.last_connected = Some(now);
+            if let Some(since) = record.connected_since.take() {
+                record.connected_time += now.duration_since(since).unwrap_or_default();
+            }
+        });
+    }
+    
+    pub fn get(&self, address: &str) -> Option<DeviceRecord> {
+        self.records.lock().unwrap().get(address).cloned()
+    }
+    
+    /// Add the time of ongoing connections to their records and write what changed since the
+    /// last save, so that little is lost if RustBlue or the system stops without disconnecting.
+    pub fn checkpoint(&self) {
+        let mut records = self.records.lock().unwrap();
+        let now = SystemTime::now();
+        let mut changed = self.changed.swap(false, Ordering::Relaxed);
+        for record in records.values_mut() {
+            if let Some(since) = record.connected_since {
+                record.connected_time += now.duration_since(since).unwrap_or_default();
+                record.connected_since = Some(now);
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+        if let Err(e) = Self::save(&records) {
+            warn!("Failed to save device records: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    
+    fn ago(seconds: u64) -> String {
+        format_ago(SystemTime::now() - Duration::from_secs(seconds))
+    }
+    
+    #[test]
+    fn formats_ago() {
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(30), "just now");
+        assert_eq!(ago(2 * MINUTE + 10), "2 min ago");
+        assert_eq!(ago(59 * MINUTE + 10), "59 min ago");
+        assert_eq!(ago(HOUR + 10), "1 h ago");
+        assert_eq!(ago(23 * HOUR + 10), "23 h ago");
+        assert_eq!(ago(DAY + 10), "yesterday");
+        assert_eq!(ago(2 * DAY - MINUTE), "yesterday");
+        assert_eq!(ago(2 * DAY + 10), "2 days ago");
+        assert_eq!(ago(45 * DAY), "45 days ago");
+    }
+    
+    #[test]
+    fn future_is_just_now() {
+        // The clock may have been set back since
+        assert_eq!(format_ago(SystemTime::now() + Duration::from_secs(HOUR)), "just now");
+    }
+    
+    #[test]
+    fn formats_durations() {
+        let duration = |seconds| format_duration(Duration::from_secs(seconds));
+        assert_eq!(duration(0), "0 s");
+        assert_eq!(duration(59), "59 s");
+        assert_eq!(duration(MINUTE), "1 min");
+        assert_eq!(duration(59 * MINUTE + 59), "59 min");
+        assert_eq!(duration(HOUR), "1 h 0 min");
+        assert_eq!(duration(3 * HOUR + 12 * MINUTE), "3 h 12 min");
+        assert_eq!(duration(DAY - MINUTE), "23 h 59 min");
+        assert_eq!(duration(DAY), "1 d 0 h");
+        assert_eq!(duration(3 * DAY + 5 * HOUR + 30 * MINUTE), "3 d 5 h");
+    }
+    
+    #[test]
+    fn keeps_first_sighting() {
+        let first = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let later = first + Duration::from_secs(HOUR);
+        let mut record = DeviceRecord::default();
+        record.seen(first);
+        record.seen(later);
+        assert_eq!(record.first_seen, Some(first));
+        assert_eq!(record.last_seen, Some(later));
+    }
+    
+    #[test]
+    fn forgets_devices_that_never_connected() {
+        let now = SystemTime::now();
+        let seen = |age: Duration| DeviceRecord { last_seen: Some(now - age), ..Default::default() };
+        
+        assert!(!seen(Duration::from_secs(DAY)).is_stale(now));
+        assert!(seen(FORGET_AFTER + Duration::from_secs(1)).is_stale(now));
+        assert!(DeviceRecord::default().is_stale(now));
+        
+        let connected = DeviceRecord { connections: 1, ..seen(FORGET_AFTER * 2) };
+        assert!(!connected.is_stale(now));
+    }
+    
+    #[test]
+    fn adds_current_connection() {
+        let record = DeviceRecord {
+            connected_time: Duration::from_secs(HOUR),
+            connected_since: Some(SystemTime::now() - Duration::from_secs(MINUTE)),
+            ..Default::default()
+        };
+        let total = record.total_connected_time();
+        assert!(total >= Duration::from_secs(HOUR + MINUTE) && total < Duration::from_secs(HOUR + 2 * MINUTE), "{:?}", total);
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::thread;
+
+use anyhow::Result;
+use log::debug;
+use tokio::sync::mpsc;
+
+const RFKILL_DEVICE: &str = "/dev/rfkill";
+/// `struct rfkill_event` of `linux/rfkill.h`: idx (u32), type, op, soft, hard (u8 each).
+/// Newer kernels append fields, reading this much still returns the original layout.
+const EVENT_SIZE: usize = 8;
+const TYPE_BLUETOOTH: u8 = 2;
+const OP_DEL: u8 = 1;
+
+/// Receives a message every time a Bluetooth rfkill switch goes from blocked to unblocked,
+/// soft (e.g. airplane mode) or hard.
+pub fn watch_unblock() -> Result<mpsc::UnboundedReceiver<()>> {
+    let mut device = File::open(RFKILL_DEVICE)?;
+    let (sender, receiver) = mpsc::unbounded_channel();
+    
+    // Reads block, and opening the device reports the current state of every switch first
+    thread::spawn(move || {
+        let mut blocked: HashMap<u32, bool> = HashMap::new();
+        let mut event = [0u8; EVENT_SIZE];
+        while device.read_exact(&mut event).is_ok() {
+            let index = u32::from_ne_bytes([event[0], event[1], event[2], event[3]]);
+            let (kind, op, soft, hard) = (event[4], event[5], event[6], event[7]);
+            if kind != TYPE_BLUETOOTH {
+                continue;
+            }
+            if op == OP_DEL {
+                blocked.remove(&index);
+                continue;
+            }
+            
+            let is_blocked = soft != 0 || hard != 0;
+            debug!("rfkill {}: {}", index, if is_blocked { "blocked" } else { "unblocked" });
+            if blocked.insert(index, is_blocked) == Some(true) && !is_blocked && sender.send(()).is_err() {
+                break;
+            }
+        }
+        debug!("Stopped watching {}", RFKILL_DEVICE);
+    });
+    
+    Ok(receiver)
+}
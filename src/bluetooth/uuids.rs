@@ -0,0 +1,327 @@
+/// 16-bit UUIDs are aliases within the Bluetooth base UUID 0000xxxx-0000-1000-8000-00805f9b34fb
+const BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805f9b34fb";
+
+/// The 16-bit alias of a UUID in the Bluetooth base range.
+pub fn short_uuid(uuid: &str) -> Option<u16> {
+    let uuid = uuid.to_lowercase();
+    let alias = uuid.strip_suffix(BASE_UUID_SUFFIX)?.strip_prefix("0000")?;
+    u16::from_str_radix(alias, 16).ok()
+}
+
+/// Name of an assigned service, characteristic or descriptor UUID, or a well-known vendor one.
+pub fn name(uuid: &str) -> Option<&'static str> {
+    match short_uuid(uuid) {
+        Some(alias) => assigned_name(alias),
+        None => vendor_name(&uuid.to_lowercase()),
+    }
+}
+
+/// "Battery Service (0x180F)", or the UUID itself when it is unknown.
+pub fn label(uuid: &str) -> String {
+    match (name(uuid), short_uuid(uuid)) {
+        (Some(name), Some(alias)) => format!("{} (0x{:04X})", name, alias),
+        (Some(name), None) => name.to_string(),
+        (None, Some(alias)) => format!("0x{:04X}", alias),
+        (None, None) => uuid.to_string(),
+    }
+}
+
+fn assigned_name(alias: u16) -> Option<&'static str> {
+    let name = match alias {
+        // Service classes and profiles
+        0x1000 => "Service Discovery Server",
+        0x1001 => "Browse Group Descriptor",
+        0x1101 => "Serial Port",
+        0x1102 => "LAN Access Using PPP",
+        0x1103 => "Dialup Networking",
+        0x1104 => "IrMC Sync",
+        0x1105 => "OBEX Object Push",
+        0x1106 => "OBEX File Transfer",
+        0x1107 => "IrMC Sync Command",
+        0x1108 => "Headset",
+        0x1109 => "Cordless Telephony",
+        0x110a => "Audio Source",
+        0x110b => "Audio Sink",
+        0x110c => "A/V Remote Control Target",
+        0x110d => "Advanced Audio Distribution",
+        0x110e => "A/V Remote Control",
+        0x110f => "A/V Remote Control Controller",
+        0x1110 => "Intercom",
+        0x1111 => "Fax",
+        0x1112 => "Headset Audio Gateway",
+        0x1113 => "WAP",
+        0x1114 => "WAP Client",
+        0x1115 => "PAN User",
+        0x1116 => "Network Access Point",
+        0x1117 => "Group Ad-hoc Network",
+        0x1118 => "Direct Printing",
+        0x1119 => "Reference Printing",
+        0x111a => "Basic Imaging Profile",
+        0x111b => "Imaging Responder",
+        0x111c => "Imaging Automatic Archive",
+        0x111d => "Imaging Referenced Objects",
+        0x111e => "Handsfree",
+        0x111f => "Handsfree Audio Gateway",
+        0x1120 => "Direct Printing Reference Objects",
+        0x1121 => "Reflected UI",
+        0x1122 => "Basic Printing",
+        0x1123 => "Printing Status",
+        0x1124 => "Human Interface Device",
+        0x1125 => "Hardcopy Cable Replacement",
+        0x1126 => "HCR Print",
+        0x1127 => "HCR Scan",
+        0x1128 => "Common ISDN Access",
+        0x112d => "SIM Access",
+        0x112e => "Phonebook Access Client",
+        0x112f => "Phonebook Access Server",
+        0x1130 => "Phonebook Access",
+        0x1131 => "Headset HS",
+        0x1132 => "Message Access Server",
+        0x1133 => "Message Notification Server",
+        0x1134 => "Message Access Profile",
+        0x1135 => "GNSS",
+        0x1136 => "GNSS Server",
+        0x1200 => "PnP Information",
+        0x1201 => "Generic Networking",
+        0x1202 => "Generic File Transfer",
+        0x1203 => "Generic Audio",
+        0x1204 => "Generic Telephony",
+        0x1303 => "Video Source",
+        0x1304 => "Video Sink",
+        0x1305 => "Video Distribution",
+        0x1400 => "Health Device",
+        0x1401 => "Health Device Source",
+        0x1402 => "Health Device Sink",
+        
+        // GATT services
+        0x1800 => "Generic Access",
+        0x1801 => "Generic Attribute",
+        0x1802 => "Immediate Alert",
+        0x1803 => "Link Loss",
+        0x1804 => "Tx Power",
+        0x1805 => "Current Time",
+        0x1806 => "Reference Time Update",
+        0x1807 => "Next DST Change",
+        0x1808 => "Glucose",
+        0x1809 => "Health Thermometer",
+        0x180a => "Device Information",
+        0x180d => "Heart Rate",
+        0x180e => "Phone Alert Status",
+        0x180f => "Battery Service",
+        0x1810 => "Blood Pressure",
+        0x1811 => "Alert Notification",
+        0x1812 => "Human Interface Device",
+        0x1813 => "Scan Parameters",
+        0x1814 => "Running Speed and Cadence",
+        0x1815 => "Automation IO",
+        0x1816 => "Cycling Speed and Cadence",
+        0x1818 => "Cycling Power",
+        0x1819 => "Location and Navigation",
+        0x181a => "Environmental Sensing",
+        0x181b => "Body Composition",
+        0x181c => "User Data",
+        0x181d => "Weight Scale",
+        0x181e => "Bond Management",
+        0x181f => "Continuous Glucose Monitoring",
+        0x1820 => "Internet Protocol Support",
+        0x1821 => "Indoor Positioning",
+        0x1822 => "Pulse Oximeter",
+        0x1823 => "HTTP Proxy",
+        0x1824 => "Transport Discovery",
+        0x1825 => "Object Transfer",
+        0x1826 => "Fitness Machine",
+        0x1827 => "Mesh Provisioning",
+        0x1828 => "Mesh Proxy",
+        0x1829 => "Reconnection Configuration",
+        0x183a => "Insulin Delivery",
+        0x183b => "Binary Sensor",
+        0x183c => "Emergency Configuration",
+        0x183e => "Physical Activity Monitor",
+        0x1843 => "Audio Input Control",
+        0x1844 => "Volume Control",
+        0x1845 => "Volume Offset Control",
+        0x1846 => "Coordinated Set Identification",
+        0x1847 => "Device Time",
+        0x1848 => "Media Control",
+        0x1849 => "Generic Media Control",
+        0x184a => "Constant Tone Extension",
+        0x184b => "Telephone Bearer",
+        0x184c => "Generic Telephone Bearer",
+        0x184d => "Microphone Control",
+        0x184e => "Audio Stream Control",
+        0x184f => "Broadcast Audio Scan",
+        0x1850 => "Published Audio Capabilities",
+        0x1851 => "Basic Audio Announcement",
+        0x1852 => "Broadcast Audio Announcement",
+        0x1853 => "Common Audio",
+        0x1854 => "Hearing Access",
+        0x1855 => "Telephony and Media Audio",
+        0x1856 => "Public Broadcast Announcement",
+        
+        // Descriptors
+        0x2900 => "Characteristic Extended Properties",
+        0x2901 => "Characteristic User Description",
+        0x2902 => "Client Characteristic Configuration",
+        0x2903 => "Server Characteristic Configuration",
+        0x2904 => "Characteristic Presentation Format",
+        0x2905 => "Characteristic Aggregate Format",
+        0x2906 => "Valid Range",
+        0x2907 => "External Report Reference",
+        0x2908 => "Report Reference",
+        0x2909 => "Number of Digitals",
+        0x290a => "Value Trigger Setting",
+        0x290b => "Environmental Sensing Configuration",
+        0x290c => "Environmental Sensing Measurement",
+        0x290d => "Environmental Sensing Trigger Setting",
+        0x290e => "Time Trigger Setting",
+        
+        // Characteristics
+        0x2a00 => "Device Name",
+        0x2a01 => "Appearance",
+        0x2a02 => "Peripheral Privacy Flag",
+        0x2a03 => "Reconnection Address",
+        0x2a04 => "Peripheral Preferred Connection Parameters",
+        0x2a05 => "Service Changed",
+        0x2a06 => "Alert Level",
+        0x2a07 => "Tx Power Level",
+        0x2a08 => "Date Time",
+        0x2a09 => "Day of Week",
+        0x2a0a => "Day Date Time",
+        0x2a0c => "Exact Time 256",
+        0x2a0d => "DST Offset",
+        0x2a0e => "Time Zone",
+        0x2a0f => "Local Time Information",
+        0x2a11 => "Time with DST",
+        0x2a12 => "Time Accuracy",
+        0x2a13 => "Time Source",
+        0x2a14 => "Reference Time Information",
+        0x2a16 => "Time Update Control Point",
+        0x2a17 => "Time Update State",
+        0x2a18 => "Glucose Measurement",
+        0x2a19 => "Battery Level",
+        0x2a1c => "Temperature Measurement",
+        0x2a1d => "Temperature Type",
+        0x2a1e => "Intermediate Temperature",
+        0x2a21 => "Measurement Interval",
+        0x2a22 => "Boot Keyboard Input Report",
+        0x2a23 => "System ID",
+        0x2a24 => "Model Number String",
+        0x2a25 => "Serial Number String",
+        0x2a26 => "Firmware Revision String",
+        0x2a27 => "Hardware Revision String",
+        0x2a28 => "Software Revision String",
+        0x2a29 => "Manufacturer Name String",
+        0x2a2a => "IEEE 11073-20601 Regulatory Certification Data List",
+        0x2a2b => "Current Time",
+        0x2a31 => "Scan Refresh",
+        0x2a32 => "Boot Keyboard Output Report",
+        0x2a33 => "Boot Mouse Input Report",
+        0x2a34 => "Glucose Measurement Context",
+        0x2a35 => "Blood Pressure Measurement",
+        0x2a36 => "Intermediate Cuff Pressure",
+        0x2a37 => "Heart Rate Measurement",
+        0x2a38 => "Body Sensor Location",
+        0x2a39 => "Heart Rate Control Point",
+        0x2a3f => "Alert Status",
+        0x2a40 => "Ringer Control Point",
+        0x2a41 => "Ringer Setting",
+        0x2a42 => "Alert Category ID Bit Mask",
+        0x2a43 => "Alert Category ID",
+        0x2a44 => "Alert Notification Control Point",
+        0x2a45 => "Unread Alert Status",
+        0x2a46 => "New Alert",
+        0x2a47 => "Supported New Alert Category",
+        0x2a48 => "Supported Unread Alert Category",
+        0x2a49 => "Blood Pressure Feature",
+        0x2a4a => "HID Information",
+        0x2a4b => "Report Map",
+        0x2a4c => "HID Control Point",
+        0x2a4d => "Report",
+        0x2a4e => "Protocol Mode",
+        0x2a4f => "Scan Interval Window",
+        0x2a50 => "PnP ID",
+        0x2a51 => "Glucose Feature",
+        0x2a52 => "Record Access Control Point",
+        0x2a53 => "RSC Measurement",
+        0x2a54 => "RSC Feature",
+        0x2a55 => "SC Control Point",
+        0x2a5b => "CSC Measurement",
+        0x2a5c => "CSC Feature",
+        0x2a5d => "Sensor Location",
+        0x2a63 => "Cycling Power Measurement",
+        0x2a65 => "Cycling Power Feature",
+        0x2a66 => "Cycling Power Control Point",
+        0x2a67 => "Location and Speed",
+        0x2a68 => "Navigation",
+        0x2a6d => "Pressure",
+        0x2a6e => "Temperature",
+        0x2a6f => "Humidity",
+        0x2a98 => "Weight",
+        0x2a9d => "Weight Measurement",
+        0x2a9e => "Weight Scale Feature",
+        0x2aa6 => "Central Address Resolution",
+        0x2ac9 => "Resolvable Private Address Only",
+        0x2b29 => "Client Supported Features",
+        0x2b2a => "Database Hash",
+        0x2b3a => "Server Supported Features",
+        0x2b7d => "Volume State",
+        0x2b7e => "Volume Control Point",
+        0x2b7f => "Volume Flags",
+        
+        // Services of Bluetooth SIG members
+        0xfd6f => "Exposure Notification",
+        0xfe03 => "Amazon Alexa",
+        0xfe2c => "Google Fast Pair",
+        0xfe59 => "Nordic Secure DFU",
+        0xfe95 => "Xiaomi",
+        0xfeaa => "Eddystone",
+        0xfeec => "Tile",
+        0xfeed => "Tile",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn vendor_name(uuid: &str) -> Option<&'static str> {
+    let name = match uuid {
+        "6e400001-b5a3-f393-e0a9-e50e24dcca9e" => "Nordic UART Service",
+        "6e400002-b5a3-f393-e0a9-e50e24dcca9e" => "Nordic UART RX",
+        "6e400003-b5a3-f393-e0a9-e50e24dcca9e" => "Nordic UART TX",
+        "8ec90003-f315-4f60-9fb8-838830daea50" => "Nordic Buttonless DFU",
+        "00001523-1212-efde-1523-785feabcd123" => "Nordic LED Button Service",
+        "7905f431-b5ce-4e99-a40f-4b1e122d00d0" => "Apple Notification Center Service",
+        "89d3502b-0f36-433a-8ef4-c502ad55f8dc" => "Apple Media Service",
+        "d0611e78-bbb4-4591-a5f8-487910ae4366" => "Apple Continuity",
+        "fe2c1233-8366-4814-8eb0-01de32100bea" => "Fast Pair Model ID",
+        "fe2c1234-8366-4814-8eb0-01de32100bea" => "Fast Pair Key-based Pairing",
+        "fe2c1235-8366-4814-8eb0-01de32100bea" => "Fast Pair Passkey",
+        "fe2c1236-8366-4814-8eb0-01de32100bea" => "Fast Pair Account Key",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn short_uuids() {
+        assert_eq!(short_uuid("0000180f-0000-1000-8000-00805f9b34fb"), Some(0x180f));
+        assert_eq!(short_uuid("0000180F-0000-1000-8000-00805F9B34FB"), Some(0x180f));
+        // 32-bit aliases and other bases are not 16-bit ones
+        assert_eq!(short_uuid("1234180f-0000-1000-8000-00805f9b34fb"), None);
+        assert_eq!(short_uuid("6e400001-b5a3-f393-e0a9-e50e24dcca9e"), None);
+        assert_eq!(short_uuid("0000zzzz-0000-1000-8000-00805f9b34fb"), None);
+    }
+    
+    #[test]
+    fn labels() {
+        assert_eq!(label("0000180f-0000-1000-8000-00805f9b34fb"), "Battery Service (0x180F)");
+        assert_eq!(label("0000110b-0000-1000-8000-00805f9b34fb"), "Audio Sink (0x110B)");
+        assert_eq!(label("00002a19-0000-1000-8000-00805f9b34fb"), "Battery Level (0x2A19)");
+        assert_eq!(label("6E400001-B5A3-F393-E0A9-E50E24DCCA9E"), "Nordic UART Service");
+        assert_eq!(label("0000fff0-0000-1000-8000-00805f9b34fb"), "0xFFF0");
+        assert_eq!(label("12345678-1234-1234-1234-123456789abc"), "12345678-1234-1234-1234-123456789abc");
+    }
+}
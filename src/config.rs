@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::bluetooth::power::PowerPolicy;
+
+/// Settings RustBlue keeps for a single device, keyed by address in [`Settings`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceSettings {
+    /// Make the device the default audio output/input while it is connected
+    pub default_audio_on_connect: bool,
+    /// Keep reconnecting the device when it drops out, see [`crate::bluetooth::reconnect`]
+    pub auto_connect: bool,
+    /// Lock the session when the device, usually a phone, goes out of range, see [`crate::proximity`]
+    pub lock_when_away: bool,
+    /// Commands run on events of this device, in addition to the global ones
+    pub hooks: Vec<Hook>,
+}
+
+/// Device event a [`Hook`] runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    Connected,
+    Disconnected,
+    /// BlueZ found a new device nearby
+    Appeared,
+    Paired,
+    /// The battery level dropped below this percentage
+    BatteryBelow(u8),
+}
+
+impl HookEvent {
+    /// `RUSTBLUE_EVENT` of the command
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::Connected => "connected",
+            HookEvent::Disconnected => "disconnected",
+            HookEvent::Appeared => "appeared",
+            HookEvent::Paired => "paired",
+            HookEvent::BatteryBelow(_) => "battery-below",
+        }
+    }
+}
+
+/// A user command run through `sh -c` when a device event happens, see [`crate::hooks`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hook {
+    pub event: HookEvent,
+    pub command: String,
+}
+
+/// Application-wide preferences, edited on the preferences page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Where received files are stored, the XDG download directory when unset
+    pub download_dir: Option<PathBuf>,
+    /// Accept incoming files from trusted devices without asking
+    pub auto_accept_trusted: bool,
+    /// Whether adapters are powered when they appear
+    pub power_policy: PowerPolicy,
+    pub notifications: NotificationSettings,
+    pub proximity: ProximitySettings,
+}
+
+impl Preferences {
+    pub fn download_dir(&self) -> PathBuf {
+        self.download_dir.clone()
+            .or_else(dirs::download_dir)
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+/// Which device events show a desktop notification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Off by default, the individual events only apply once enabled
+    pub enabled: bool,
+    pub connected: bool,
+    pub disconnected: bool,
+    pub paired: bool,
+    /// Failed connection and pairing attempts, and auto-connect giving up
+    pub failed: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            connected: true,
+            disconnected: true,
+            paired: true,
+            failed: true,
+        }
+    }
+}
+
+/// When the devices marked [`DeviceSettings::lock_when_away`] count as away.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProximitySettings {
+    /// Weakest signal, in dBm, at which a device still counts as near
+    pub rssi_threshold: i16,
+    /// How long the devices must be away before the session is locked
+    pub away_seconds: u32,
+    /// Show a notification when a device returns; the session stays locked
+    #[serde(alias = "unlock_on_return")]
+    pub notify_on_return: bool,
+}
+
+impl Default for ProximitySettings {
+    fn default() -> Self {
+        Self {
+            rssi_threshold: -80,
+            away_seconds: 30,
+            notify_on_return: false,
+        }
+    }
+}
+
+/// Sort order of the device list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeviceSort {
+    #[default]
+    Name,
+    /// Strongest signal first
+    Rssi,
+    /// Most recently advertising or connected first
+    LastSeen,
+    LastConnected,
+    Type,
+}
+
+impl DeviceSort {
+    pub const ALL: [DeviceSort; 5] = [DeviceSort::Name, DeviceSort::Rssi, DeviceSort::LastSeen, DeviceSort::LastConnected, DeviceSort::Type];
+}
+
+impl fmt::Display for DeviceSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DeviceSort::Name => "Name",
+            DeviceSort::Rssi => "Signal Strength",
+            DeviceSort::LastSeen => "Last Seen",
+            DeviceSort::LastConnected => "Last Connected",
+            DeviceSort::Type => "Type",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Filters and sort order of the device list, remembered across sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceListSettings {
+    pub paired_only: bool,
+    pub connected_only: bool,
+    /// Only devices currently in range, i.e. with a known RSSI
+    pub nearby_only: bool,
+    /// Only devices of this type, one of [`crate::bluetooth::device::BluetoothDevice::TYPES`]
+    pub device_type: Option<String>,
+    pub hide_unnamed: bool,
+    pub sort: DeviceSort,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub preferences: Preferences,
+    pub device_list: DeviceListSettings,
+    pub devices: HashMap<String, DeviceSettings>,
+    /// Hooks for every device
+    pub hooks: Vec<Hook>,
+}
+
+impl Settings {
+    /// `~/.config/rustblue/settings.json`
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rustblue").join("settings.json"))
+    }
+    
+    pub fn load() -> Self {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => {
+                warn!("No config directory available, using default settings");
+                return Self::default();
+            }
+        };
+        
+        match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str(&contents) {
+                Ok(settings) => {
+                    debug!("Loaded settings from {}", path.display());
+                    settings
+                }
+                Err(e) => {
+                    warn!("Failed to parse {}: {}", path.display(), e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+    
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("No config directory available"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        debug!("Saved settings to {}", path.display());
+        Ok(())
+    }
+    
+    pub fn device(&self, address: &str) -> DeviceSettings {
+        self.devices.get(address).cloned().unwrap_or_default()
+    }
+    
+    pub fn auto_connect_devices(&self) -> HashSet<String> {
+        self.devices.iter()
+            .filter(|(_, settings)| settings.auto_connect)
+            .map(|(address, _)| address.clone())
+            .collect()
+    }
+    
+    pub fn lock_when_away_devices(&self) -> HashSet<String> {
+        self.devices.iter()
+            .filter(|(_, settings)| settings.lock_when_away)
+            .map(|(address, _)| address.clone())
+            .collect()
+    }
+    
+    /// Per-device hooks by address, for [`crate::hooks::Hooks::set`].
+    pub fn device_hooks(&self) -> HashMap<String, Vec<Hook>> {
+        self.devices.iter()
+            .filter(|(_, settings)| !settings.hooks.is_empty())
+            .map(|(address, settings)| (address.clone(), settings.hooks.clone()))
+            .collect()
+    }
+    
+    pub fn set_device(&mut self, address: &str, settings: DeviceSettings) {
+        if settings == DeviceSettings::default() {
+            self.devices.remove(address);
+        } else {
+            self.devices.insert(address.to_string(), settings);
+        }
+    }
+}
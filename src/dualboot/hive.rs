@@ -0,0 +1,339 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+/// The hive bins, which all cell offsets are relative to, start after the base block
+const BASE_BLOCK_SIZE: usize = 4096;
+/// Offset of the root key's cell in the base block
+const ROOT_CELL_OFFSET: usize = 0x24;
+/// Key and value names stored as Latin-1 rather than UTF-16
+const KEY_COMP_NAME: u16 = 0x20;
+const VALUE_COMP_NAME: u16 = 0x01;
+/// Set in a value's data size when the data sits in the data offset field itself
+const DATA_IN_OFFSET: u32 = 0x8000_0000;
+/// Larger values are split into "db" big data cells, which keys never need
+const MAX_CELL_DATA: usize = 16344;
+
+pub const REG_BINARY: u32 = 3;
+pub const REG_DWORD: u32 = 4;
+pub const REG_QWORD: u32 = 11;
+
+fn slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    data.get(offset..offset + len).ok_or_else(|| anyhow!("Hive is truncated"))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = slice(data, offset, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = slice(data, offset, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn decode_name(raw: &[u8], compressed: bool) -> String {
+    if compressed {
+        raw.iter().map(|&byte| byte as char).collect()
+    } else {
+        let units: Vec<u16> = raw.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+        String::from_utf16_lossy(&units)
+    }
+}
+
+/// An offline Windows registry hive (`regf`), e.g. `Windows/System32/config/SYSTEM`, read just
+/// far enough to walk keys and read values. Transaction logs are not replayed, so changes
+/// Windows did not flush before shutting down are missing.
+pub struct Hive {
+    data: Vec<u8>,
+}
+
+impl Hive {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::parse(fs::read(path)?)
+    }
+    
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        if data.len() < BASE_BLOCK_SIZE || &data[..4] != b"regf" {
+            return Err(anyhow!("Not a Windows registry hive"));
+        }
+        Ok(Self { data })
+    }
+    
+    /// The data of the cell at `offset`, without its size field.
+    fn cell(&self, offset: u32) -> Result<&[u8]> {
+        let start = BASE_BLOCK_SIZE + offset as usize;
+        // Cells in use have a negative size, which counts the size field itself
+        let size = (read_u32(&self.data, start)? as i32).unsigned_abs() as usize;
+        if size < 4 {
+            return Err(anyhow!("Invalid cell at {:#x}", offset));
+        }
+        slice(&self.data, start + 4, size - 4)
+    }
+    
+    pub fn root(&self) -> Result<Key<'_>> {
+        Key::new(self, read_u32(&self.data, ROOT_CELL_OFFSET)?)
+    }
+}
+
+/// A value of a [`Key`], with its raw data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value {
+    pub name: String,
+    pub kind: u32,
+    pub data: Vec<u8>,
+}
+
+impl Value {
+    pub fn as_u32(&self) -> Option<u32> {
+        match (self.kind, self.data.as_slice()) {
+            (REG_DWORD, [a, b, c, d]) => Some(u32::from_le_bytes([*a, *b, *c, *d])),
+            _ => None,
+        }
+    }
+    
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.kind {
+            REG_QWORD => self.data.as_slice().try_into().ok().map(u64::from_le_bytes),
+            _ => None,
+        }
+    }
+    
+    /// Binary data of exactly `N` bytes, as link keys and the like are stored.
+    pub fn as_bytes<const N: usize>(&self) -> Option<[u8; N]> {
+        match self.kind {
+            REG_BINARY => self.data.as_slice().try_into().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// A registry key ("nk" cell) of a [`Hive`].
+#[derive(Clone, Copy)]
+pub struct Key<'a> {
+    hive: &'a Hive,
+    cell: &'a [u8],
+}
+
+impl<'a> Key<'a> {
+    fn new(hive: &'a Hive, offset: u32) -> Result<Self> {
+        let cell = hive.cell(offset)?;
+        if cell.get(..2) != Some(b"nk") {
+            return Err(anyhow!("No key at {:#x}", offset));
+        }
+        Ok(Self { hive, cell })
+    }
+    
+    pub fn name(&self) -> Result<String> {
+        let flags = read_u16(self.cell, 0x02)?;
+        let len = read_u16(self.cell, 0x48)? as usize;
+        Ok(decode_name(slice(self.cell, 0x4c, len)?, flags & KEY_COMP_NAME != 0))
+    }
+    
+    pub fn subkeys(&self) -> Result<Vec<Key<'a>>> {
+        let mut keys = Vec::new();
+        if read_u32(self.cell, 0x14)? > 0 {
+            self.read_subkey_list(read_u32(self.cell, 0x1c)?, &mut keys, 0)?;
+        }
+        Ok(keys)
+    }
+    
+    /// Subkey lists come as "lf"/"lh" (offset and name hash), "li" (offsets only) or "ri",
+    /// a list of further lists for keys with many subkeys.
+    fn read_subkey_list(&self, offset: u32, keys: &mut Vec<Key<'a>>, depth: usize) -> Result<()> {
+        let list = self.hive.cell(offset)?;
+        let count = read_u16(list, 0x02)? as usize;
+        match list.get(..2) {
+            Some(b"lf") | Some(b"lh") => {
+                for i in 0..count {
+                    keys.push(Key::new(self.hive, read_u32(list, 4 + i * 8)?)?);
+                }
+            }
+            Some(b"li") => {
+                for i in 0..count {
+                    keys.push(Key::new(self.hive, read_u32(list, 4 + i * 4)?)?);
+                }
+            }
+            Some(b"ri") if depth == 0 => {
+                for i in 0..count {
+                    self.read_subkey_list(read_u32(list, 4 + i * 4)?, keys, depth + 1)?;
+                }
+            }
+            _ => return Err(anyhow!("Invalid subkey list at {:#x}", offset)),
+        }
+        Ok(())
+    }
+    
+    /// The subkey called `name`, ignoring case like Windows does.
+    pub fn subkey(&self, name: &str) -> Result<Option<Key<'a>>> {
+        for key in self.subkeys()? {
+            if key.name()?.eq_ignore_ascii_case(name) {
+                return Ok(Some(key));
+            }
+        }
+        Ok(None)
+    }
+    
+    /// The key at a backslash separated `path` below this one.
+    pub fn path(&self, path: &str) -> Result<Key<'a>> {
+        let mut key = *self;
+        for name in path.split('\\') {
+            key = key.subkey(name)?.ok_or_else(|| anyhow!("Key {} not found", path))?;
+        }
+        Ok(key)
+    }
+    
+    pub fn values(&self) -> Result<Vec<Value>> {
+        let count = read_u32(self.cell, 0x24)? as usize;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        
+        let list = self.hive.cell(read_u32(self.cell, 0x28)?)?;
+        (0..count)
+            .map(|i| self.read_value(read_u32(list, i * 4)?))
+            .collect()
+    }
+    
+    fn read_value(&self, offset: u32) -> Result<Value> {
+        let cell = self.hive.cell(offset)?;
+        if cell.get(..2) != Some(b"vk") {
+            return Err(anyhow!("No value at {:#x}", offset));
+        }
+        
+        let name_len = read_u16(cell, 0x02)? as usize;
+        let size = read_u32(cell, 0x04)?;
+        let kind = read_u32(cell, 0x0c)?;
+        let flags = read_u16(cell, 0x10)?;
+        let name = decode_name(slice(cell, 0x14, name_len)?, flags & VALUE_COMP_NAME != 0);
+        
+        let data = if size & DATA_IN_OFFSET != 0 {
+            let len = ((size & !DATA_IN_OFFSET) as usize).min(4);
+            slice(cell, 0x08, len)?.to_vec()
+        } else if size as usize > MAX_CELL_DATA {
+            return Err(anyhow!("Value {} is too large", name));
+        } else {
+            let data = self.hive.cell(read_u32(cell, 0x08)?)?;
+            slice(data, 0, size as usize)?.to_vec()
+        };
+        
+        Ok(Value { name, kind, data })
+    }
+    
+    pub fn value(&self, name: &str) -> Result<Option<Value>> {
+        Ok(self.values()?.into_iter().find(|value| value.name.eq_ignore_ascii_case(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Written by scripts/make-test-hive.py
+    const SYSTEM: &[u8] = include_bytes!("../../tests/data/SYSTEM.hive");
+    const KEYS: &str = r"ControlSet001\Services\BTHPORT\Parameters\Keys";
+    
+    fn hive() -> Hive {
+        Hive::parse(SYSTEM.to_vec()).unwrap()
+    }
+    
+    /// Offset of the data of the cell at `offset` in the file.
+    fn cell_start(offset: u32) -> usize {
+        BASE_BLOCK_SIZE + offset as usize + 4
+    }
+    
+    fn root_offset() -> u32 {
+        read_u32(SYSTEM, ROOT_CELL_OFFSET).unwrap()
+    }
+    
+    #[test]
+    fn rejects_other_files() {
+        assert!(Hive::parse(b"regf".to_vec()).is_err());
+        assert!(Hive::parse(vec![0; BASE_BLOCK_SIZE]).is_err());
+    }
+    
+    #[test]
+    fn walks_keys() {
+        let hive = hive();
+        let root = hive.root().unwrap();
+        assert_eq!(root.name().unwrap(), "ROOT");
+        let names: Vec<String> = root.subkeys().unwrap().iter().map(|key| key.name().unwrap()).collect();
+        assert_eq!(names, ["ControlSet001", "Select"]);
+        
+        // "li" and "ri" lists, names in any case
+        let keys = root.path(&KEYS.to_lowercase()).unwrap();
+        assert_eq!(keys.name().unwrap(), "Keys");
+        // UTF-16 name with an "lh" list
+        let adapter = keys.subkey("001A7DDA7113").unwrap().unwrap();
+        assert_eq!(adapter.subkeys().unwrap().len(), 3);
+        
+        assert!(root.subkey("Missing").unwrap().is_none());
+        assert!(root.path(r"ControlSet001\Missing").is_err());
+    }
+    
+    #[test]
+    fn reads_values() {
+        let hive = hive();
+        let root = hive.root().unwrap();
+        let current = root.path("Select").unwrap().value("current").unwrap().unwrap();
+        assert_eq!(current.as_u32(), Some(1));
+        assert_eq!(current.as_u64(), None);
+        
+        let device = root.path(&format!(r"{}\001a7dda7113\aabbccddeeff", KEYS)).unwrap();
+        let ltk = device.value("LTK").unwrap().unwrap();
+        assert_eq!(ltk.kind, REG_BINARY);
+        assert_eq!(ltk.as_bytes::<16>().unwrap()[0], 0x10);
+        assert_eq!(ltk.as_bytes::<8>(), None);
+        assert_eq!(device.value("ERand").unwrap().unwrap().as_u64(), Some(0x0102030405060708));
+        assert_eq!(device.value("EDIV").unwrap().unwrap().as_u32(), Some(0x1234));
+    }
+    
+    #[test]
+    fn value_data_outside_the_hive() {
+        let hive = hive();
+        let device = hive.root().unwrap().path(&format!(r"{}\001a7dda7113\112233445566", KEYS)).unwrap();
+        assert!(device.values().is_err());
+    }
+    
+    #[test]
+    fn truncated() {
+        let hive = Hive::parse(SYSTEM[..cell_start(root_offset()) + 8].to_vec()).unwrap();
+        assert!(hive.root().is_err());
+        
+        let hive = Hive::parse(SYSTEM[..BASE_BLOCK_SIZE].to_vec()).unwrap();
+        assert!(hive.root().is_err());
+    }
+    
+    #[test]
+    fn malformed_cells() {
+        let root = cell_start(root_offset());
+        
+        // Cell sizes smaller than the size field
+        let mut data = SYSTEM.to_vec();
+        data[root - 4..root].copy_from_slice(&(-2i32).to_le_bytes());
+        assert!(Hive::parse(data).unwrap().root().is_err());
+        
+        // Not a key
+        let mut data = SYSTEM.to_vec();
+        data[root..root + 2].copy_from_slice(b"vk");
+        assert!(Hive::parse(data).unwrap().root().is_err());
+        
+        // Name longer than the cell
+        let mut data = SYSTEM.to_vec();
+        data[root + 0x48..root + 0x4a].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(Hive::parse(data).unwrap().root().unwrap().name().is_err());
+        
+        // Unknown subkey list
+        let mut data = SYSTEM.to_vec();
+        let list = cell_start(read_u32(&data, root + 0x1c).unwrap());
+        data[list..list + 2].copy_from_slice(b"xx");
+        assert!(Hive::parse(data).unwrap().root().unwrap().subkeys().is_err());
+        
+        // More subkeys than the list holds
+        let mut data = SYSTEM.to_vec();
+        let list = cell_start(read_u32(&data, root + 0x1c).unwrap());
+        data[list + 2..list + 4].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(Hive::parse(data).unwrap().root().unwrap().subkeys().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Entries the helper may write, so it cannot be used to change anything but pairing keys
+const ALLOWED: [(&str, &[&str]); 5] = [
+    ("General", &["AddressType"]),
+    ("LinkKey", &["Key", "Type", "PINLength"]),
+    ("LongTermKey", &["Key", "Authenticated", "EncSize", "EDiv", "Rand"]),
+    ("IdentityResolvingKey", &["Key"]),
+    ("LocalSignatureKey", &["Key", "Counter", "Authenticated"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InfoEntry {
+    pub key: String,
+    pub value: String,
+    /// Only written when the file has no value yet, e.g. the type of a link key
+    pub only_if_missing: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InfoSection {
+    pub name: String,
+    pub entries: Vec<InfoEntry>,
+}
+
+/// Sections the pairing helper merges into `/var/lib/bluetooth/<adapter>/<device>/info`.
+///
+/// This file is built into `rustblue-pairing-helper` as well, so it only depends on std, serde and anyhow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InfoUpdate {
+    pub adapter: String,
+    pub device: String,
+    pub sections: Vec<InfoSection>,
+}
+
+fn is_address(address: &str) -> bool {
+    address.len() == 17
+        && address.split(':').count() == 6
+        && address.split(':').all(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_lowercase()))
+}
+
+impl InfoUpdate {
+    /// Addresses that are safe to use as paths, and only allowed sections with plain values.
+    pub fn validate(&self) -> Result<()> {
+        for address in [&self.adapter, &self.device] {
+            if !is_address(address) {
+                return Err(anyhow!("Invalid address {:?}", address));
+            }
+        }
+        
+        for section in &self.sections {
+            let keys = ALLOWED.iter()
+                .find(|(name, _)| *name == section.name)
+                .map(|(_, keys)| *keys)
+                .ok_or_else(|| anyhow!("Section {} is not allowed", section.name))?;
+            for entry in &section.entries {
+                if !keys.contains(&entry.key.as_str()) {
+                    return Err(anyhow!("{}.{} is not allowed", section.name, entry.key));
+                }
+                if entry.value.is_empty() || !entry.value.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(anyhow!("Invalid value for {}.{}", section.name, entry.key));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn update(adapter: &str, device: &str, section: &str, key: &str, value: &str) -> InfoUpdate {
+        InfoUpdate {
+            adapter: adapter.to_string(),
+            device: device.to_string(),
+            sections: vec![InfoSection {
+                name: section.to_string(),
+                entries: vec![InfoEntry { key: key.to_string(), value: value.to_string(), only_if_missing: false }],
+            }],
+        }
+    }
+    
+    fn link_key(value: &str) -> InfoUpdate {
+        update("00:1A:7D:DA:71:13", "AA:BB:CC:DD:EE:FF", "LinkKey", "Key", value)
+    }
+    
+    #[test]
+    fn accepts_keys() {
+        link_key("606162636465666768696A6B6C6D6E6F").validate().unwrap();
+    }
+    
+    #[test]
+    fn rejects_addresses() {
+        for address in ["../../etc", "AA:BB:CC:DD:EE", "aa:bb:cc:dd:ee:ff", "AA:BB:CC:DD:EE:F/", "AA:BB:CC:DD:EE:FF/..", ""] {
+            assert!(update(address, "AA:BB:CC:DD:EE:FF", "LinkKey", "Key", "00").validate().is_err(), "{}", address);
+            assert!(update("00:1A:7D:DA:71:13", address, "LinkKey", "Key", "00").validate().is_err(), "{}", address);
+        }
+    }
+    
+    #[test]
+    fn rejects_values() {
+        for value in ["", "00 11", "00\n[General]", "Key=00", "../x", "é"] {
+            assert!(link_key(value).validate().is_err(), "{:?}", value);
+        }
+    }
+    
+    #[test]
+    fn rejects_other_entries() {
+        assert!(update("00:1A:7D:DA:71:13", "AA:BB:CC:DD:EE:FF", "General", "Name", "x").validate().is_err());
+        assert!(update("00:1A:7D:DA:71:13", "AA:BB:CC:DD:EE:FF", "Services", "Key", "x").validate().is_err());
+    }
+}
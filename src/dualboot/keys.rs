@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+
+use crate::dualboot::hive::{Hive, Key};
+use crate::dualboot::info::{InfoEntry, InfoSection, InfoUpdate};
+
+/// Where Windows keeps pairing keys, below the current control set
+const KEYS_PATH: &str = r"Services\BTHPORT\Parameters\Keys";
+/// Bit of `AuthReq` telling the LE pairing was protected against MITM
+const AUTH_REQ_MITM: u32 = 0x04;
+
+/// Keys of a Bluetooth LE device, from its subkey below the adapter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LeKeys {
+    pub ltk: Option<[u8; 16]>,
+    pub key_length: Option<u32>,
+    pub ediv: Option<u32>,
+    pub erand: Option<u64>,
+    pub irk: Option<[u8; 16]>,
+    pub csrk: Option<[u8; 16]>,
+    /// 0 for a public address, 1 for a random static one
+    pub address_type: Option<u32>,
+    pub authenticated: bool,
+}
+
+/// What Windows stored for one device paired with one of its adapters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceKeys {
+    pub adapter: String,
+    pub address: String,
+    /// BR/EDR link key, stored as a value named after the device
+    pub link_key: Option<[u8; 16]>,
+    pub le: Option<LeKeys>,
+}
+
+/// `001a7dda7113` as `00:1A:7D:DA:71:13`, the way Windows names keys and values after addresses.
+fn address(name: &str) -> Option<String> {
+    if name.len() != 12 || !name.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let parts: Vec<String> = (0..6).map(|i| name[i * 2..i * 2 + 2].to_ascii_uppercase()).collect();
+    Some(parts.join(":"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn read_le_keys(key: &Key) -> Result<LeKeys> {
+    let mut keys = LeKeys::default();
+    for value in key.values()? {
+        match value.name.as_str() {
+            "LTK" => keys.ltk = value.as_bytes(),
+            "KeyLength" => keys.key_length = value.as_u32(),
+            "EDIV" => keys.ediv = value.as_u32(),
+            "ERand" => keys.erand = value.as_u64(),
+            "IRK" => keys.irk = value.as_bytes(),
+            "CSRK" => keys.csrk = value.as_bytes(),
+            "AddressType" => keys.address_type = value.as_u32(),
+            "AuthReq" => keys.authenticated = value.as_u32().is_some_and(|auth| auth & AUTH_REQ_MITM != 0),
+            _ => {}
+        }
+    }
+    Ok(keys)
+}
+
+fn device_entry<'a>(devices: &'a mut BTreeMap<(String, String), DeviceKeys>, adapter: &str, device: String) -> &'a mut DeviceKeys {
+    devices.entry((adapter.to_string(), device.clone())).or_insert_with(|| DeviceKeys {
+        adapter: adapter.to_string(),
+        address: device,
+        link_key: None,
+        le: None,
+    })
+}
+
+/// The pairing keys of every adapter in a Windows `SYSTEM` hive.
+pub fn read_keys(path: &Path) -> Result<Vec<DeviceKeys>> {
+    let hive = Hive::open(path)?;
+    let root = hive.root()?;
+    
+    let current = root.path("Select")?.value("Current")?
+        .and_then(|value| value.as_u32())
+        .unwrap_or(1);
+    let control_set = format!("ControlSet{:03}", current);
+    let adapters = root.path(&format!(r"{}\{}", control_set, KEYS_PATH))
+        .map_err(|_| anyhow!("No Bluetooth pairing keys in {}", path.display()))?;
+    
+    // Devices paired over both BR/EDR and LE have a value and a subkey
+    let mut devices: BTreeMap<(String, String), DeviceKeys> = BTreeMap::new();
+    for adapter_key in adapters.subkeys()? {
+        let Some(adapter) = address(&adapter_key.name()?) else {
+            continue;
+        };
+        for value in adapter_key.values()? {
+            if let (Some(device), Some(link_key)) = (address(&value.name), value.as_bytes()) {
+                device_entry(&mut devices, &adapter, device).link_key = Some(link_key);
+            }
+        }
+        for device_key in adapter_key.subkeys()? {
+            let Some(device) = address(&device_key.name()?) else {
+                continue;
+            };
+            match read_le_keys(&device_key) {
+                Ok(le) => device_entry(&mut devices, &adapter, device).le = Some(le),
+                Err(e) => warn!("Failed to read LE keys of {}: {}", device, e),
+            }
+        }
+    }
+    
+    debug!("Found keys of {} devices in {}", devices.len(), path.display());
+    Ok(devices.into_values().collect())
+}
+
+fn entry(key: &str, value: String) -> InfoEntry {
+    InfoEntry { key: key.to_string(), value, only_if_missing: false }
+}
+
+fn default_entry(key: &str, value: &str) -> InfoEntry {
+    InfoEntry { key: key.to_string(), value: value.to_string(), only_if_missing: true }
+}
+
+fn section(name: &str, entries: Vec<InfoEntry>) -> InfoSection {
+    InfoSection { name: name.to_string(), entries }
+}
+
+impl DeviceKeys {
+    /// The keys in BlueZ's `info` format.
+    pub fn info_update(&self) -> InfoUpdate {
+        let mut sections = Vec::new();
+        
+        if let Some(link_key) = &self.link_key {
+            // Windows does not store the key type, an unauthenticated combination key is the
+            // safe guess when BlueZ has none yet
+            sections.push(section("LinkKey", vec![
+                entry("Key", hex(link_key)),
+                default_entry("Type", "4"),
+                default_entry("PINLength", "0"),
+            ]));
+        }
+        
+        if let Some(le) = &self.le {
+            if let Some(address_type) = le.address_type {
+                let address_type = if address_type == 0 { "public" } else { "static" };
+                sections.push(section("General", vec![entry("AddressType", address_type.to_string())]));
+            }
+            if let Some(ltk) = &le.ltk {
+                sections.push(section("LongTermKey", vec![
+                    entry("Key", hex(ltk)),
+                    entry("Authenticated", (le.authenticated as u8).to_string()),
+                    entry("EncSize", le.key_length.unwrap_or(16).to_string()),
+                    entry("EDiv", le.ediv.unwrap_or(0).to_string()),
+                    entry("Rand", le.erand.unwrap_or(0).to_string()),
+                ]));
+            }
+            if let Some(irk) = &le.irk {
+                sections.push(section("IdentityResolvingKey", vec![entry("Key", hex(irk))]));
+            }
+            if let Some(csrk) = &le.csrk {
+                sections.push(section("LocalSignatureKey", vec![
+                    entry("Key", hex(csrk)),
+                    default_entry("Counter", "0"),
+                    default_entry("Authenticated", "false"),
+                ]));
+            }
+        }
+        
+        InfoUpdate {
+            adapter: self.adapter.clone(),
+            device: self.address.clone(),
+            sections,
+        }
+    }
+    
+    /// "BR/EDR", "LE" or both, depending on the keys found.
+    pub fn kind(&self) -> &'static str {
+        match (self.link_key.is_some(), self.le.is_some()) {
+            (true, true) => "BR/EDR and LE",
+            (false, true) => "LE",
+            _ => "BR/EDR",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Written by scripts/make-test-hive.py
+    const SYSTEM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/SYSTEM.hive");
+    
+    fn key_bytes(first: u8) -> [u8; 16] {
+        std::array::from_fn(|i| first + i as u8)
+    }
+    
+    fn device(address: &str) -> DeviceKeys {
+        let devices = read_keys(Path::new(SYSTEM)).unwrap();
+        devices.into_iter().find(|device| device.address == address).unwrap()
+    }
+    
+    #[test]
+    fn addresses() {
+        assert_eq!(address("001a7dda7113").as_deref(), Some("00:1A:7D:DA:71:13"));
+        assert_eq!(address("CentralIRK"), None);
+        assert_eq!(address("001a7dda711"), None);
+        assert_eq!(address("001a7dda711g"), None);
+    }
+    
+    #[test]
+    fn reads_keys() {
+        let devices = read_keys(Path::new(SYSTEM)).unwrap();
+        // The device whose LE keys cannot be read is left out
+        let addresses: Vec<&str> = devices.iter().map(|device| device.address.as_str()).collect();
+        assert_eq!(addresses, ["AA:BB:CC:DD:EE:FF", "C0:FF:EE:12:34:56"]);
+        assert!(devices.iter().all(|device| device.adapter == "00:1A:7D:DA:71:13"));
+        
+        let both = device("AA:BB:CC:DD:EE:FF");
+        assert_eq!(both.kind(), "BR/EDR and LE");
+        assert_eq!(both.link_key, Some(key_bytes(0x60)));
+        assert_eq!(both.le, Some(LeKeys {
+            ltk: Some(key_bytes(0x10)),
+            key_length: Some(16),
+            ediv: Some(0x1234),
+            erand: Some(0x0102030405060708),
+            address_type: Some(0),
+            authenticated: true,
+            ..LeKeys::default()
+        }));
+        
+        let le = device("C0:FF:EE:12:34:56");
+        assert_eq!(le.kind(), "LE");
+        assert_eq!(le.link_key, None);
+        let le = le.le.unwrap();
+        assert_eq!(le.irk, Some(key_bytes(0x30)));
+        assert_eq!(le.csrk, Some(key_bytes(0x40)));
+        assert!(!le.authenticated);
+    }
+    
+    #[test]
+    fn not_a_hive() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/pactl-cards.json");
+        assert!(read_keys(Path::new(path)).is_err());
+    }
+    
+    #[test]
+    fn info_update() {
+        let update = device("AA:BB:CC:DD:EE:FF").info_update();
+        assert_eq!(update.adapter, "00:1A:7D:DA:71:13");
+        assert_eq!(update.device, "AA:BB:CC:DD:EE:FF");
+        update.validate().unwrap();
+        
+        let names: Vec<&str> = update.sections.iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(names, ["LinkKey", "General", "LongTermKey"]);
+        let link_key = &update.sections[0];
+        assert_eq!(link_key.entries[0], entry("Key", "606162636465666768696A6B6C6D6E6F".to_string()));
+        assert!(link_key.entries[1..].iter().all(|entry| entry.only_if_missing));
+        let entries: Vec<(&str, &str)> = update.sections[2].entries.iter()
+            .map(|entry| (entry.key.as_str(), entry.value.as_str()))
+            .collect();
+        assert_eq!(entries, [
+            ("Key", "101112131415161718191A1B1C1D1E1F"),
+            ("Authenticated", "1"),
+            ("EncSize", "16"),
+            ("EDiv", "4660"),
+            ("Rand", "72623859790382856"),
+        ]);
+        
+        let update = device("C0:FF:EE:12:34:56").info_update();
+        update.validate().unwrap();
+        let names: Vec<&str> = update.sections.iter().map(|section| section.name.as_str()).collect();
+        assert_eq!(names, ["General", "LongTermKey", "IdentityResolvingKey", "LocalSignatureKey"]);
+        assert_eq!(update.sections[0].entries[0].value, "static");
+    }
+}
@@ -0,0 +1,74 @@
+pub mod hive;
+pub mod info;
+pub mod keys;
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use anyhow::{anyhow, Result};
+use log::info;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use info::InfoUpdate;
+
+const HELPER_NAME: &str = "rustblue-pairing-helper";
+/// Environment variable that replaces `pkexec rustblue-pairing-helper`, e.g. with a script
+/// that saves what it is given, for testing without root.
+const HELPER_ENV: &str = "RUSTBLUE_PAIRING_HELPER";
+/// What `pkexec` exits with when the password dialog is dismissed or the password is wrong
+const PKEXEC_NOT_AUTHORIZED: [i32; 2] = [126, 127];
+
+/// The helper is installed next to the RustBlue binary.
+fn helper_path() -> Result<PathBuf> {
+    let path = env::current_exe()?.with_file_name(HELPER_NAME);
+    if !path.exists() {
+        return Err(anyhow!("{} is not installed next to {}", HELPER_NAME, path.parent().map(|dir| dir.display().to_string()).unwrap_or_default()));
+    }
+    Ok(path)
+}
+
+/// Write `updates` into BlueZ's storage through the helper, which runs as root with polkit's
+/// `pkexec` and restarts bluetoothd so it loads the keys.
+pub async fn install(updates: &[InfoUpdate]) -> Result<()> {
+    for update in updates {
+        update.validate()?;
+    }
+    
+    let input = serde_json::to_vec(updates)?;
+    let pkexec = env::var(HELPER_ENV).is_err();
+    let mut command = match env::var(HELPER_ENV) {
+        Ok(helper) => Command::new(helper),
+        Err(_) => {
+            let mut command = Command::new("pkexec");
+            command.arg(helper_path()?);
+            command
+        }
+    };
+    info!("Installing pairing keys of {} devices", updates.len());
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    
+    // pkexec exits without reading its input when not authorized, its status says more
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(&input).await,
+        None => Ok(()),
+    };
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        if pkexec && output.status.code().is_some_and(|code| PKEXEC_NOT_AUTHORIZED.contains(&code)) {
+            return Err(anyhow!("Authorization was cancelled or denied, no keys were written"));
+        }
+        let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if error.is_empty() {
+            return Err(anyhow!("{} exited with {}", HELPER_NAME, output.status));
+        }
+        return Err(anyhow!(error));
+    }
+    written?;
+    Ok(())
+}
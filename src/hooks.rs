@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use log::{debug, info, warn};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+
+use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::events::BluetoothEvent;
+use crate::bluetooth::manager::BluetoothManager;
+use crate::config::{Hook, HookEvent};
+
+/// The configured hooks, shared between the settings and the task running them.
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    global: Arc<Mutex<Vec<Hook>>>,
+    devices: Arc<Mutex<HashMap<String, Vec<Hook>>>>,
+}
+
+impl Hooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn set(&self, global: Vec<Hook>, devices: HashMap<String, Vec<Hook>>) {
+        *self.global.lock().unwrap() = global;
+        *self.devices.lock().unwrap() = devices;
+    }
+    
+    /// Commands of the hooks for `address` accepting `matches`, global ones first.
+    fn commands(&self, address: &str, matches: impl Fn(&HookEvent) -> bool) -> Vec<String> {
+        let global = self.global.lock().unwrap();
+        let devices = self.devices.lock().unwrap();
+        global.iter()
+            .chain(devices.get(address).into_iter().flatten())
+            .filter(|hook| matches(&hook.event))
+            .map(|hook| hook.command.clone())
+            .collect()
+    }
+}
+
+/// Whether a `hook` runs for `event`. For battery changes `event` holds the new level, and hooks
+/// only run when it drops below their threshold, not on every change below it.
+fn triggers(hook: &HookEvent, event: &HookEvent, previous_battery: Option<u8>) -> bool {
+    match (hook, event) {
+        (HookEvent::BatteryBelow(threshold), HookEvent::BatteryBelow(percentage)) => {
+            percentage < threshold && previous_battery.is_none_or(|previous| previous >= *threshold)
+        }
+        _ => hook == event,
+    }
+}
+
+/// Run `command` with the device details in its environment, without waiting for it.
+fn run(command: &str, event: &str, address: &str, device: Option<&BluetoothDevice>, battery: Option<u8>) {
+    info!("Running {} hook for {}: {}", event, address, command);
+    
+    let mut process = Command::new("sh");
+    process.arg("-c").arg(command)
+        .stdin(Stdio::null())
+        .env("RUSTBLUE_EVENT", event)
+        .env("RUSTBLUE_ADDRESS", address)
+        .env("RUSTBLUE_NAME", device.map(|device| device.name.as_str()).unwrap_or(""))
+        .env("RUSTBLUE_TYPE", device.map(|device| device.device_type.as_str()).unwrap_or(""));
+    if let Some(battery) = battery {
+        process.env("RUSTBLUE_BATTERY", battery.to_string());
+    }
+    
+    let command = command.to_string();
+    match process.spawn() {
+        Ok(mut child) => {
+            tokio::spawn(async move {
+                match child.wait().await {
+                    Ok(status) if status.success() => debug!("Hook finished: {}", command),
+                    Ok(status) => warn!("Hook {} exited with {}", command, status),
+                    Err(e) => warn!("Failed to wait for hook {}: {}", command, e),
+                }
+            });
+        }
+        Err(e) => warn!("Failed to run hook {}: {}", command, e),
+    }
+}
+
+/// Run the hooks matching the manager's events until it goes away.
+pub fn watch(manager: &BluetoothManager, hooks: Hooks) {
+    let mut receiver = manager.subscribe();
+    let manager = manager.clone();
+    
+    tokio::spawn(async move {
+        // Last battery level per device, to run battery hooks once when it drops below
+        let mut batteries: HashMap<String, u8> = HashMap::new();
+        
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            
+            // Battery changes carry the new level
+            let (address, hook_event, previous_battery) = match &event {
+                BluetoothEvent::DeviceConnected { address, .. } => (address, HookEvent::Connected, None),
+                BluetoothEvent::DeviceDisconnected { address, .. } => (address, HookEvent::Disconnected, None),
+                BluetoothEvent::DeviceAdded { address, .. } => (address, HookEvent::Appeared, None),
+                BluetoothEvent::DevicePaired { address, .. } => (address, HookEvent::Paired, None),
+                BluetoothEvent::BatteryChanged { address, percentage, .. } => {
+                    let previous = batteries.insert(address.clone(), *percentage);
+                    (address, HookEvent::BatteryBelow(*percentage), previous)
+                }
+                BluetoothEvent::DeviceRemoved { address, .. } => {
+                    batteries.remove(address);
+                    continue;
+                }
+                _ => continue,
+            };
+            
+            let commands = hooks.commands(address, |hook| triggers(hook, &hook_event, previous_battery));
+            if commands.is_empty() {
+                continue;
+            }
+            
+            let device = match manager.get_devices().await {
+                Ok(devices) => devices.into_iter().find(|device| &device.address == address),
+                Err(e) => {
+                    warn!("Failed to look up {} for its hooks: {}", address, e);
+                    None
+                }
+            };
+            let battery = match hook_event {
+                HookEvent::BatteryBelow(percentage) => Some(percentage),
+                _ => None,
+            };
+            for command in commands {
+                run(&command, hook_event.name(), address, device.as_ref(), battery);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn hook(event: HookEvent, command: &str) -> Hook {
+        Hook { event, command: command.to_string() }
+    }
+    
+    #[test]
+    fn battery_hooks_run_when_crossing_the_threshold() {
+        let hook = HookEvent::BatteryBelow(20);
+        // First reading already below
+        assert!(triggers(&hook, &HookEvent::BatteryBelow(15), None));
+        assert!(triggers(&hook, &HookEvent::BatteryBelow(19), Some(20)));
+        assert!(triggers(&hook, &HookEvent::BatteryBelow(5), Some(80)));
+        // Still below, or not below yet
+        assert!(!triggers(&hook, &HookEvent::BatteryBelow(18), Some(19)));
+        assert!(!triggers(&hook, &HookEvent::BatteryBelow(20), Some(25)));
+        assert!(!triggers(&hook, &HookEvent::BatteryBelow(25), None));
+    }
+    
+    #[test]
+    fn other_hooks_match_their_event() {
+        assert!(triggers(&HookEvent::Connected, &HookEvent::Connected, None));
+        assert!(!triggers(&HookEvent::Connected, &HookEvent::Disconnected, None));
+        assert!(!triggers(&HookEvent::Connected, &HookEvent::BatteryBelow(10), Some(50)));
+        assert!(!triggers(&HookEvent::BatteryBelow(20), &HookEvent::Connected, None));
+    }
+    
+    #[test]
+    fn global_hooks_come_first() {
+        let hooks = Hooks::new();
+        hooks.set(
+            vec![hook(HookEvent::Connected, "global"), hook(HookEvent::Paired, "paired")],
+            HashMap::from([("AA:BB:CC:DD:EE:FF".to_string(), vec![hook(HookEvent::Connected, "device")])]),
+        );
+        let matches = |event: &HookEvent| *event == HookEvent::Connected;
+        assert_eq!(hooks.commands("AA:BB:CC:DD:EE:FF", matches), ["global", "device"]);
+        assert_eq!(hooks.commands("11:22:33:44:55:66", matches), ["global"]);
+    }
+}
@@ -0,0 +1,85 @@
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use dbus::arg::OwnedFd;
+use dbus::message::MatchRule;
+use dbus::nonblock::{Proxy, SyncConnection};
+use futures::StreamExt;
+use log::{debug, error, info};
+use tokio::sync::mpsc;
+
+const LOGIND_SERVICE: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Client of systemd-logind (`org.freedesktop.login1`) on the system bus.
+#[derive(Clone)]
+pub struct Logind {
+    connection: Arc<SyncConnection>,
+}
+
+impl fmt::Debug for Logind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logind").finish_non_exhaustive()
+    }
+}
+
+impl Logind {
+    pub fn new() -> Result<Self> {
+        let (resource, connection) = dbus_tokio::connection::new_system_sync()?;
+        tokio::spawn(async move {
+            let err = resource.await;
+            error!("Lost connection to the system bus: {}", err);
+        });
+        
+        Ok(Self { connection })
+    }
+    
+    fn manager(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(LOGIND_SERVICE, LOGIND_PATH, TIMEOUT, &*self.connection)
+    }
+    
+    /// The session RustBlue runs in; logind takes an empty id as the caller's session.
+    fn session_id() -> String {
+        env::var("XDG_SESSION_ID").unwrap_or_default()
+    }
+    
+    pub async fn lock_session(&self) -> Result<()> {
+        info!("Locking the session");
+        self.manager().method_call::<(), _, _, _>(MANAGER_INTERFACE, "LockSession", (Self::session_id(),)).await?;
+        Ok(())
+    }
+    
+    /// Delay suspending until the returned file descriptor is dropped, so there is time to
+    /// prepare once `PrepareForSleep` arrives. logind waits for at most `InhibitDelayMaxSec`.
+    pub async fn inhibit_sleep(&self, why: &str) -> Result<OwnedFd> {
+        let (fd,): (OwnedFd,) = self.manager()
+            .method_call(MANAGER_INTERFACE, "Inhibit", ("sleep", "RustBlue", why, "delay"))
+            .await?;
+        debug!("Holding a sleep delay lock: {}", why);
+        Ok(fd)
+    }
+    
+    /// Receives `true` right before the system suspends and `false` once it has resumed.
+    pub async fn watch_sleep(&self) -> Result<mpsc::UnboundedReceiver<bool>> {
+        let rule = MatchRule::new_signal(MANAGER_INTERFACE, "PrepareForSleep").with_path(LOGIND_PATH);
+        let (signal_match, mut signals) = self.connection.add_match(rule).await?.stream::<(bool,)>();
+        
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let connection = self.connection.clone();
+        tokio::spawn(async move {
+            while let Some((_, (sleeping,))) = signals.next().await {
+                if sender.send(sleeping).is_err() {
+                    break;
+                }
+            }
+            let _ = connection.remove_match(signal_match.token()).await;
+        });
+        
+        Ok(receiver)
+    }
+}
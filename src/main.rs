@@ -1,30 +1,157 @@
+mod api;
+mod audio;
+mod backup;
 mod bluetooth;
+mod config;
+mod dualboot;
+mod hooks;
+mod logind;
+mod obex;
+mod proximity;
 mod ui;
 
+use std::cell::{Cell, RefCell};
 use std::env;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use anyhow::Result;
 use gio::prelude::*;
 use gtk::{prelude::*, Application};
-use log::{debug, info};
+use log::{debug, error, info};
 
 use ui::window::RustBlueWindow;
 
 const APP_ID: &str = "org.rustblue.Manager";
 
+/// An application action with its parameter, see `RustBlueWindow::install_app_actions`
+type Action = (String, Option<glib::Variant>);
+
+/// Options that trigger an application action, in the running instance if there is one.
+fn add_action_options(app: &Application) {
+    app.add_main_option("connect", glib::Char::from(b'c'), glib::OptionFlags::NONE, glib::OptionArg::String, "Connect a device", Some("ADDRESS"));
+    app.add_main_option("disconnect", glib::Char::from(b'd'), glib::OptionFlags::NONE, glib::OptionArg::String, "Disconnect a device", Some("ADDRESS"));
+    app.add_main_option("toggle-power", glib::Char::from(b'p'), glib::OptionFlags::NONE, glib::OptionArg::None, "Turn Bluetooth on or off", None);
+    app.add_main_option("scan", glib::Char::from(b's'), glib::OptionFlags::NONE, glib::OptionArg::None, "Scan for devices", None);
+    app.add_main_option("show", glib::Char::from(0), glib::OptionFlags::NONE, glib::OptionArg::None, "Show the window", None);
+    app.add_main_option("quit", glib::Char::from(b'q'), glib::OptionFlags::NONE, glib::OptionArg::None, "Quit, also when running in the background", None);
+    app.add_main_option("daemon", glib::Char::from(0), glib::OptionFlags::NONE, glib::OptionArg::None, "Keep running in the background, without showing the window", None);
+    app.add_main_option("hidden", glib::Char::from(0), glib::OptionFlags::NONE, glib::OptionArg::None, "Same as --daemon", None);
+    app.add_main_option("list-windows-keys", glib::Char::from(0), glib::OptionFlags::NONE, glib::OptionArg::Filename, "Print the pairing keys in a Windows SYSTEM hive as BlueZ would store them", Some("HIVE"));
+}
+
+/// Print what importing the pairing keys of a Windows hive would write, without writing it.
+fn list_windows_keys(path: &Path) -> i32 {
+    let keys = match dualboot::keys::read_keys(path) {
+        Ok(keys) => keys,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return 1;
+        }
+    };
+    
+    for device in keys {
+        let update = device.info_update();
+        println!("# {}/{} ({})", update.adapter, update.device, device.kind());
+        for section in update.sections {
+            println!("[{}]", section.name);
+            for entry in section.entries {
+                println!("{}={}", entry.key, entry.value);
+            }
+        }
+        println!();
+    }
+    0
+}
+
+fn command_line_actions(options: &glib::VariantDict) -> Vec<Action> {
+    let mut actions = Vec::new();
+    
+    for name in ["connect", "disconnect"] {
+        if let Ok(Some(address)) = options.lookup::<String>(name) {
+            actions.push((name.to_string(), Some(address.to_variant())));
+        }
+    }
+    for name in ["toggle-power", "scan", "show", "quit"] {
+        if options.contains(name) {
+            actions.push((name.to_string(), None));
+        }
+    }
+    actions
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
     env_logger::init();
     info!("Starting RustBlue");
-
+    
     // Initialize GTK
     let app = Application::builder()
         .application_id(APP_ID)
         .build();
-
-    app.connect_activate(build_ui);
-
+    
+    // libadwaita widgets need the library initialized once GTK is up
+    app.connect_startup(|_| {
+        if let Err(e) = adw::init() {
+            log::warn!("Failed to initialize libadwaita: {}", e);
+        }
+    });
+    
+    // GApplication keeps a single instance: a second `rustblue` hands its actions to the
+    // running one over D-Bus and exits, or just activates it, which shows its window
+    add_action_options(&app);
+    let quit_action = gio::SimpleAction::new("quit", None);
+    quit_action.connect_activate(glib::clone!(@weak app => move |_, _| {
+        app.quit();
+    }));
+    app.add_action(&quit_action);
+    
+    let pending_actions: Rc<RefCell<Vec<Action>>> = Rc::new(RefCell::new(Vec::new()));
+    let daemon = Rc::new(Cell::new(false));
+    app.connect_handle_local_options(glib::clone!(@strong pending_actions, @strong daemon => move |app, options| {
+        if let Ok(Some(path)) = options.lookup::<PathBuf>("list-windows-keys") {
+            return list_windows_keys(&path);
+        }
+        
+        let actions = command_line_actions(options);
+        daemon.set(options.contains("daemon") || options.contains("hidden"));
+        if actions.is_empty() && !daemon.get() {
+            return -1;
+        }
+        
+        if let Err(e) = app.register(gio::Cancellable::NONE) {
+            error!("Failed to register application: {}", e);
+            return 1;
+        }
+        if app.is_remote() {
+            if daemon.get() {
+                info!("RustBlue is already running");
+            }
+            if !actions.is_empty() {
+                info!("Forwarding {} action(s) to the running instance", actions.len());
+            }
+            for (name, target) in actions {
+                app.activate_action(&name, target.as_ref());
+            }
+            return 0;
+        }
+        
+        // First instance, run them once the window is up
+        pending_actions.borrow_mut().extend(actions);
+        -1
+    }));
+    
+    // Keeps the application running while its window is hidden
+    let hold_guard: RefCell<Option<gio::ApplicationHoldGuard>> = RefCell::new(None);
+    app.connect_activate(move |app| {
+        if daemon.take() {
+            info!("Running in the background");
+            hold_guard.replace(Some(app.hold()));
+        }
+        build_ui(app, pending_actions.take(), hold_guard.borrow().is_some());
+    });
+    
     // Set up command line arguments
     let args: Vec<String> = env::args().collect();
     
@@ -35,12 +162,26 @@ async fn main() -> Result<()> {
     std::process::exit(exit_code.into());
 }
 
-fn build_ui(app: &Application) {
+/// Create the window, hidden when running in the `background`.
+fn build_ui(app: &Application, actions: Vec<Action>, background: bool) {
+    // Activated again by a second `rustblue`, or from the background
+    if let Some(window) = app.windows().first() {
+        window.present();
+        return;
+    }
+    
     debug!("Building UI");
     
     // Create the main window
     let window = RustBlueWindow::new(app);
+    for (name, target) in actions {
+        window.activate_when_ready(&name, target);
+    }
     
-    // Present the window
-    window.present();
+    if background {
+        // Closing the window only hides it, Bluetooth handling carries on
+        window.set_hide_on_close(true);
+    } else {
+        window.present();
+    }
 }
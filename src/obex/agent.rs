@@ -0,0 +1,181 @@
+use std::ffi::CString;
+use std::fmt;
+use std::path::{Path as FilePath, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::MatchRule;
+use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties;
+use dbus::nonblock::{Proxy, SyncConnection};
+use dbus::{Message, Path};
+use log::{debug, info, warn};
+use tokio::sync::mpsc;
+
+use crate::obex::client::{OBEX_PATH, OBEX_SERVICE, TIMEOUT};
+use crate::obex::transfer::{Transfer, TRANSFER_INTERFACE};
+
+const AGENT_PATH: &str = "/org/rustblue/obex/agent";
+const AGENT_INTERFACE: &str = "org.bluez.obex.Agent1";
+const AGENT_MANAGER_INTERFACE: &str = "org.bluez.obex.AgentManager1";
+const SESSION_INTERFACE: &str = "org.bluez.obex.Session1";
+
+/// What obexd asks the agent to do.
+#[derive(Debug)]
+pub enum AgentRequest {
+    AuthorizePush(PushRequest),
+    /// The pending push was cancelled by the sender before it was answered
+    Cancel,
+}
+
+/// An incoming Object Push waiting for the user to accept or reject it.
+pub struct PushRequest {
+    connection: Arc<SyncConnection>,
+    message: Message,
+    pub transfer: Transfer,
+    pub address: Option<String>,
+    pub file_name: String,
+    pub size: Option<u64>,
+}
+
+impl fmt::Debug for PushRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PushRequest")
+            .field("transfer", &self.transfer)
+            .field("address", &self.address)
+            .field("file_name", &self.file_name)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl PushRequest {
+    /// Accept the file, storing it at `destination`.
+    pub fn accept(self, destination: &FilePath) -> Result<()> {
+        info!("Accepting {} into {}", self.file_name, destination.display());
+        let destination = destination.to_str()
+            .ok_or_else(|| anyhow!("Invalid destination: {}", destination.display()))?;
+        
+        self.connection.send(self.message.method_return().append1(destination))
+            .map_err(|_| anyhow!("Failed to reply to obexd"))?;
+        Ok(())
+    }
+    
+    pub fn reject(self) -> Result<()> {
+        info!("Rejecting {}", self.file_name);
+        self.connection.send(rejection(&self.message, "Rejected by user"))
+            .map_err(|_| anyhow!("Failed to reply to obexd"))?;
+        Ok(())
+    }
+}
+
+/// A path in `dir` for the received `file_name` that does not overwrite an existing file.
+pub fn unique_destination(dir: &FilePath, file_name: &str) -> PathBuf {
+    // The name comes from the remote device, never let it point outside `dir`
+    let file_name = FilePath::new(file_name).file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "received-file".to_string());
+    
+    let candidate = dir.join(&file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    
+    let name = FilePath::new(&file_name);
+    let stem = name.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = name.extension().map(|extension| format!(".{}", extension.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+fn rejection(message: &Message, reason: &str) -> Message {
+    let reason = CString::new(reason).unwrap_or_default();
+    message.error(&"org.bluez.obex.Error.Rejected".into(), &reason)
+}
+
+/// Look up what is being pushed, and by whom, for an `AuthorizePush` call.
+async fn push_details(connection: &SyncConnection, path: &Path<'static>) -> Result<(String, Option<u64>, Option<String>)> {
+    let proxy = Proxy::new(OBEX_SERVICE, path.clone(), TIMEOUT, connection);
+    let file_name: String = proxy.get(TRANSFER_INTERFACE, "Name").await?;
+    let size: Option<u64> = proxy.get(TRANSFER_INTERFACE, "Size").await.ok();
+    let session: Path<'static> = proxy.get(TRANSFER_INTERFACE, "Session").await?;
+    
+    let session_proxy = Proxy::new(OBEX_SERVICE, session, TIMEOUT, connection);
+    let address: Option<String> = session_proxy.get(SESSION_INTERFACE, "Destination").await.ok();
+    
+    Ok((file_name, size, address))
+}
+
+/// Export an `org.bluez.obex.Agent1` object and register it with obexd.
+///
+/// Incoming pushes are handed out through the returned channel; whoever receives a
+/// [`PushRequest`] must accept or reject it.
+pub async fn register(connection: Arc<SyncConnection>) -> Result<mpsc::UnboundedReceiver<AgentRequest>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    
+    let handler_connection = connection.clone();
+    connection.start_receive(
+        MatchRule::new_method_call().with_path(AGENT_PATH).with_interface(AGENT_INTERFACE),
+        Box::new(move |message, conn| {
+            let member = message.member().map(|member| member.to_string()).unwrap_or_default();
+            debug!("OBEX agent call: {}", member);
+            
+            match member.as_str() {
+                "AuthorizePush" => {
+                    let path: Path<'static> = match message.read1() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            warn!("Invalid AuthorizePush call: {}", e);
+                            let _ = conn.send(rejection(&message, "Invalid transfer path"));
+                            return true;
+                        }
+                    };
+                    let connection = handler_connection.clone();
+                    let sender = sender.clone();
+                    tokio::spawn(async move {
+                        match push_details(&connection, &path).await {
+                            Ok((file_name, size, address)) => {
+                                let _ = sender.send(AgentRequest::AuthorizePush(PushRequest {
+                                    transfer: Transfer::new(connection.clone(), path),
+                                    connection,
+                                    message,
+                                    address,
+                                    file_name,
+                                    size,
+                                }));
+                            }
+                            Err(e) => {
+                                warn!("Failed to read incoming transfer: {}", e);
+                                let _ = connection.send(rejection(&message, "Unreadable transfer"));
+                            }
+                        }
+                    });
+                }
+                "Cancel" => {
+                    let _ = sender.send(AgentRequest::Cancel);
+                    let _ = conn.send(message.method_return());
+                }
+                "Release" => {
+                    info!("OBEX agent released by obexd");
+                    let _ = conn.send(message.method_return());
+                }
+                _ => {
+                    if let Some(reply) = dbus::channel::default_reply(&message) {
+                        let _ = conn.send(reply);
+                    }
+                }
+            }
+            true
+        }),
+    );
+    
+    let proxy = Proxy::new(OBEX_SERVICE, OBEX_PATH, TIMEOUT, &*connection);
+    proxy.method_call::<(), _, _, _>(AGENT_MANAGER_INTERFACE, "RegisterAgent", (Path::from(AGENT_PATH),))
+        .await
+        .map_err(|e| anyhow!("Failed to register OBEX agent (is obexd running?): {}", e))?;
+    
+    info!("Registered OBEX agent at {}", AGENT_PATH);
+    Ok(receiver)
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path as FilePath;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::nonblock::{Proxy, SyncConnection};
+use dbus::Path;
+use log::{error, info};
+use tokio::sync::mpsc;
+
+use crate::obex::agent::{self, AgentRequest};
+use crate::obex::transfer::Transfer;
+
+pub const OBEX_SERVICE: &str = "org.bluez.obex";
+pub const OBEX_PATH: &str = "/org/bluez/obex";
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+const CLIENT_INTERFACE: &str = "org.bluez.obex.Client1";
+const OBJECT_PUSH_INTERFACE: &str = "org.bluez.obex.ObjectPush1";
+
+/// Client side of BlueZ obexd (`org.bluez.obex.Client1`) on the session bus.
+#[derive(Clone)]
+pub struct ObexClient {
+    connection: Arc<SyncConnection>,
+}
+
+impl fmt::Debug for ObexClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObexClient").finish_non_exhaustive()
+    }
+}
+
+impl ObexClient {
+    pub fn new() -> Result<Self> {
+        let (resource, connection) = dbus_tokio::connection::new_session_sync()?;
+        tokio::spawn(async move {
+            let err = resource.await;
+            error!("Lost connection to the session bus: {}", err);
+        });
+        
+        Ok(Self { connection })
+    }
+    
+    /// Register the agent that authorizes incoming Object Push transfers.
+    pub async fn register_agent(&self) -> Result<mpsc::UnboundedReceiver<AgentRequest>> {
+        agent::register(self.connection.clone()).await
+    }
+    
+    /// Open an Object Push session to the device, e.g. a phone.
+    pub async fn create_push_session(&self, address: &str) -> Result<PushSession> {
+        info!("Creating OBEX Object Push session to {}", address);
+        let proxy = Proxy::new(OBEX_SERVICE, OBEX_PATH, TIMEOUT, &*self.connection);
+        
+        let mut args: PropMap = HashMap::new();
+        args.insert("Target".to_string(), Variant(Box::new("opp".to_string()) as Box<dyn RefArg>));
+        
+        let (path,): (Path<'static>,) = proxy
+            .method_call(CLIENT_INTERFACE, "CreateSession", (address, args))
+            .await
+            .map_err(|e| anyhow!("Failed to create OBEX session (is obexd running?): {}", e))?;
+        
+        Ok(PushSession {
+            connection: self.connection.clone(),
+            path,
+        })
+    }
+}
+
+/// An Object Push session, see `org.bluez.obex.ObjectPush1`.
+pub struct PushSession {
+    connection: Arc<SyncConnection>,
+    path: Path<'static>,
+}
+
+impl PushSession {
+    /// Queue a file, returning the transfer and its initial properties.
+    pub async fn send_file(&self, file: &FilePath) -> Result<(Transfer, PropMap)> {
+        info!("Sending {} over OBEX", file.display());
+        let file = file.to_str().ok_or_else(|| anyhow!("Invalid file name: {}", file.display()))?;
+        
+        let proxy = Proxy::new(OBEX_SERVICE, self.path.clone(), TIMEOUT, &*self.connection);
+        let (path, properties): (Path<'static>, PropMap) = proxy
+            .method_call(OBJECT_PUSH_INTERFACE, "SendFile", (file,))
+            .await?;
+        
+        Ok((Transfer::new(self.connection.clone(), path), properties))
+    }
+    
+    pub async fn close(self) -> Result<()> {
+        info!("Closing OBEX session {}", self.path);
+        let proxy = Proxy::new(OBEX_SERVICE, OBEX_PATH, TIMEOUT, &*self.connection);
+        proxy.method_call::<(), _, _, _>(CLIENT_INTERFACE, "RemoveSession", (self.path,)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use dbus::arg::{prop_cast, PropMap};
+use dbus::message::MatchRule;
+use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties;
+use dbus::nonblock::{Proxy, SyncConnection};
+use dbus::Path;
+use futures::StreamExt;
+use log::{debug, info, warn};
+use tokio::sync::mpsc;
+
+use crate::obex::client::{OBEX_SERVICE, TIMEOUT};
+
+pub const TRANSFER_INTERFACE: &str = "org.bluez.obex.Transfer1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferStatus {
+    Queued,
+    Active,
+    Suspended,
+    Complete,
+    Error,
+}
+
+impl TransferStatus {
+    pub fn from_obex(status: &str) -> Self {
+        match status {
+            "queued" => TransferStatus::Queued,
+            "active" => TransferStatus::Active,
+            "suspended" => TransferStatus::Suspended,
+            "complete" => TransferStatus::Complete,
+            _ => TransferStatus::Error,
+        }
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        matches!(self, TransferStatus::Complete | TransferStatus::Error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferProgress {
+    pub status: TransferStatus,
+    pub name: String,
+    pub size: Option<u64>,
+    pub transferred: u64,
+    /// Average rate in bytes per second since the transfer became active
+    pub rate: Option<f64>,
+}
+
+impl TransferProgress {
+    fn update(&mut self, properties: &PropMap) {
+        if let Some(status) = prop_cast::<String>(properties, "Status") {
+            self.status = TransferStatus::from_obex(status);
+        }
+        if let Some(name) = prop_cast::<String>(properties, "Name") {
+            self.name = name.clone();
+        }
+        if let Some(size) = prop_cast::<u64>(properties, "Size") {
+            self.size = Some(*size);
+        }
+        if let Some(transferred) = prop_cast::<u64>(properties, "Transferred") {
+            self.transferred = *transferred;
+        }
+    }
+}
+
+/// An `org.bluez.obex.Transfer1` object created by obexd for a queued file.
+#[derive(Clone)]
+pub struct Transfer {
+    connection: Arc<SyncConnection>,
+    path: Path<'static>,
+}
+
+impl fmt::Debug for Transfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transfer").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+impl Transfer {
+    pub fn new(connection: Arc<SyncConnection>, path: Path<'static>) -> Self {
+        Self { connection, path }
+    }
+    
+    /// The object path, unique while the transfer exists.
+    pub fn id(&self) -> String {
+        self.path.to_string()
+    }
+    
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(OBEX_SERVICE, self.path.clone(), TIMEOUT, &*self.connection)
+    }
+    
+    pub async fn cancel(&self) -> Result<()> {
+        info!("Cancelling transfer {}", self.path);
+        self.proxy().method_call::<(), _, _, _>(TRANSFER_INTERFACE, "Cancel", ()).await?;
+        Ok(())
+    }
+    
+    /// Progress updates until the transfer completes or fails.
+    ///
+    /// obexd removes the object as soon as the transfer is finished, so progress is
+    /// followed through `PropertiesChanged` signals rather than by polling.
+    pub async fn watch(&self, initial: &PropMap) -> Result<mpsc::UnboundedReceiver<TransferProgress>> {
+        let mut progress = TransferProgress {
+            status: TransferStatus::Queued,
+            name: String::new(),
+            size: None,
+            transferred: 0,
+            rate: None,
+        };
+        progress.update(initial);
+        
+        let rule = MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged")
+            .with_path(self.path.clone());
+        let (signal_match, mut signals) = self.connection.add_match(rule).await?
+            .stream::<(String, PropMap, Vec<String>)>();
+        
+        // The transfer may have progressed before the match was in place, or even finished,
+        // in which case obexd already removed it and whether it worked is lost
+        match self.proxy().get_all(TRANSFER_INTERFACE).await {
+            Ok(properties) => progress.update(&properties),
+            Err(e) if !progress.status.is_finished() => {
+                warn!("Transfer {} ended before it could be followed: {}", self.path, e);
+                progress.status = TransferStatus::Error;
+            }
+            Err(_) => {}
+        }
+        
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let _ = sender.send(progress.clone());
+        
+        let connection = self.connection.clone();
+        let path = self.path.clone();
+        tokio::spawn(async move {
+            let mut started = if progress.status == TransferStatus::Active { Some(Instant::now()) } else { None };
+            
+            while !progress.status.is_finished() {
+                let (_, (interface, changed, _)) = match signals.next().await {
+                    Some(signal) => signal,
+                    None => break,
+                };
+                if interface != TRANSFER_INTERFACE {
+                    continue;
+                }
+                
+                progress.update(&changed);
+                if progress.status == TransferStatus::Active && started.is_none() {
+                    started = Some(Instant::now());
+                }
+                progress.rate = started
+                    .map(|started| started.elapsed())
+                    .filter(|elapsed| *elapsed >= Duration::from_millis(500))
+                    .map(|elapsed| progress.transferred as f64 / elapsed.as_secs_f64());
+                
+                debug!("Transfer {}: {:?}", path, progress);
+                if sender.send(progress.clone()).is_err() {
+                    break;
+                }
+            }
+            
+            let _ = connection.remove_match(signal_match.token()).await;
+        });
+        
+        Ok(receiver)
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::bluetooth::adapter::DiscoveryOwner;
+use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::events::BluetoothEvent;
+use crate::bluetooth::manager::BluetoothManager;
+use crate::config::ProximitySettings;
+use crate::logind::Logind;
+
+/// How often the RSSI of the devices is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How much weaker than the threshold the signal must get before a device counts as away,
+/// so a phone at the edge of the threshold does not flap between near and away
+const HYSTERESIS: i16 = 10;
+/// How long an advertisement monitor waits below the threshold before reporting a device lost,
+/// the away delay of the settings comes on top
+const MONITOR_LOST_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often to retry registering an advertisement monitor after it failed
+const MONITOR_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Presence {
+    Near,
+    Away,
+    /// Between the near and away thresholds, keeps the previous state
+    Unsure,
+}
+
+fn presence(device: Option<&BluetoothDevice>, settings: &ProximitySettings) -> Presence {
+    let device = match device {
+        Some(device) => device,
+        None => return Presence::Away,
+    };
+    
+    match device.rssi {
+        Some(rssi) if rssi >= settings.rssi_threshold => Presence::Near,
+        Some(rssi) if rssi < settings.rssi_threshold - HYSTERESIS => Presence::Away,
+        Some(_) => Presence::Unsure,
+        // BlueZ only reports the RSSI of advertising devices, a connected phone is in range
+        None if device.connected => Presence::Near,
+        None => Presence::Away,
+    }
+}
+
+/// Presence of a device watched by an advertisement monitor, `present` being its last report.
+fn monitored_presence(device: Option<&BluetoothDevice>, present: Option<bool>) -> Presence {
+    if present == Some(true) || device.is_some_and(|device| device.connected) {
+        Presence::Near
+    } else {
+        Presence::Away
+    }
+}
+
+/// Any device near is enough, all of them have to be away.
+fn combined(presences: impl Iterator<Item = Presence>) -> Presence {
+    let mut all_away = true;
+    for presence in presences {
+        match presence {
+            Presence::Near => return Presence::Near,
+            Presence::Unsure => all_away = false,
+            Presence::Away => {}
+        }
+    }
+    if all_away { Presence::Away } else { Presence::Unsure }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transition {
+    Left,
+    Returned,
+}
+
+/// Decides when to lock: once the devices have been away for the whole delay, and only once
+/// until they come back.
+#[derive(Debug, Default)]
+struct Tracker {
+    away_since: Option<Instant>,
+    locked: bool,
+}
+
+impl Tracker {
+    fn update(&mut self, presence: Presence, delay: Duration, now: Instant) -> Option<Transition> {
+        match presence {
+            Presence::Near => {
+                self.away_since = None;
+                if self.locked {
+                    self.locked = false;
+                    return Some(Transition::Returned);
+                }
+            }
+            Presence::Away => {
+                let since = *self.away_since.get_or_insert(now);
+                if !self.locked && now.duration_since(since) >= delay {
+                    self.locked = true;
+                    return Some(Transition::Left);
+                }
+            }
+            Presence::Unsure => {}
+        }
+        None
+    }
+}
+
+/// The devices to watch and the thresholds, shared between the settings and the task watching.
+#[derive(Debug, Clone, Default)]
+pub struct ProximityLock {
+    devices: Arc<Mutex<HashSet<String>>>,
+    settings: Arc<Mutex<ProximitySettings>>,
+}
+
+impl ProximityLock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn set(&self, devices: HashSet<String>, settings: ProximitySettings) {
+        *self.devices.lock().unwrap() = devices;
+        *self.settings.lock().unwrap() = settings;
+    }
+}
+
+/// Lock the session through logind when the devices of `lock` leave, for as long as any are set.
+/// The returned receiver gets a message when they come back, if the settings ask for one.
+///
+/// Presence comes from an advertisement monitor where BlueZ supports one, otherwise from the
+/// RSSI of a discovery session.
+pub fn watch(manager: &BluetoothManager, lock: ProximityLock) -> mpsc::UnboundedReceiver<()> {
+    let mut receiver = manager.subscribe();
+    let manager = manager.clone();
+    let (returned_sender, returned) = mpsc::unbounded_channel();
+    
+    tokio::spawn(async move {
+        let logind = match Logind::new() {
+            Ok(logind) => logind,
+            Err(e) => {
+                warn!("Proximity lock is unavailable: {}", e);
+                return;
+            }
+        };
+        
+        let mut tracker = Tracker::default();
+        let mut discovering = false;
+        // What the running monitor was registered for, and when registering last failed
+        let mut monitored: Option<(HashSet<String>, ProximitySettings)> = None;
+        let mut monitor_failed: Option<Instant> = None;
+        let mut present: HashMap<String, bool> = HashMap::new();
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            
+            loop {
+                match receiver.try_recv() {
+                    Ok(BluetoothEvent::PresenceChanged { address, present: is_present, .. }) => {
+                        present.insert(address, is_present);
+                    }
+                    Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+                    Err(_) => break,
+                }
+            }
+            
+            let devices = lock.devices.lock().unwrap().clone();
+            let settings = lock.settings.lock().unwrap().clone();
+            // Turning Bluetooth off should not lock the session
+            if devices.is_empty() || !manager.is_adapter_powered().await.unwrap_or(false) {
+                if monitored.take().is_some() {
+                    manager.stop_presence_monitor();
+                }
+                if std::mem::take(&mut discovering) {
+                    if let Err(e) = manager.stop_discovery(DiscoveryOwner::Proximity).await {
+                        warn!("Failed to stop discovery for the proximity lock: {}", e);
+                    }
+                }
+                tracker = Tracker::default();
+                present.clear();
+                continue;
+            }
+            
+            // Both are stopped while the system sleeps
+            if monitored.is_some() && !manager.is_monitoring_presence() {
+                debug!("Advertisement monitor stopped, registering it again");
+                monitored = None;
+                present.clear();
+            }
+            if discovering && !manager.is_discovering(DiscoveryOwner::Proximity).await {
+                discovering = false;
+            }
+            
+            let wanted = (devices.clone(), settings.clone());
+            let retry = monitor_failed.is_none_or(|failed| failed.elapsed() >= MONITOR_RETRY_INTERVAL);
+            if monitored.as_ref() != Some(&wanted) && retry {
+                let rssi_low = settings.rssi_threshold - HYSTERESIS;
+                match manager.monitor_presence(&devices, settings.rssi_threshold, rssi_low, MONITOR_LOST_TIMEOUT).await {
+                    Ok(()) => {
+                        info!("Watching proximity devices with an advertisement monitor");
+                        monitored = Some(wanted);
+                        monitor_failed = None;
+                        present.clear();
+                        // The discovery used while the monitor was failing is not needed anymore
+                        if discovering {
+                            if let Err(e) = manager.stop_discovery(DiscoveryOwner::Proximity).await {
+                                warn!("Failed to stop discovery for the proximity lock: {}", e);
+                            }
+                            discovering = false;
+                        }
+                    }
+                    Err(e) => {
+                        debug!("No advertisement monitor, using discovery instead: {}", e);
+                        monitored = None;
+                        monitor_failed = Some(Instant::now());
+                    }
+                }
+            }
+            
+            // Without a monitor, only advertising devices found by discovery report an RSSI
+            if monitored.is_none() && !discovering {
+                if let Err(e) = manager.start_discovery(DiscoveryOwner::Proximity).await {
+                    warn!("Failed to start discovery for the proximity lock: {}", e);
+                    continue;
+                }
+                discovering = true;
+            }
+            
+            let known = match manager.get_devices().await {
+                Ok(known) => known,
+                Err(e) => {
+                    warn!("Failed to read devices for the proximity lock: {}", e);
+                    continue;
+                }
+            };
+            let presence = combined(devices.iter().map(|address| {
+                let device = known.iter().find(|device| &device.address == address);
+                match monitored {
+                    Some(_) => monitored_presence(device, present.get(address).copied()),
+                    None => presence(device, &settings),
+                }
+            }));
+            debug!("Proximity devices are {:?}", presence);
+            
+            let delay = Duration::from_secs(settings.away_seconds as u64);
+            match tracker.update(presence, delay, Instant::now()) {
+                Some(Transition::Left) => {
+                    info!("Proximity devices are away, locking");
+                    if let Err(e) = logind.lock_session().await {
+                        warn!("Failed to lock the session: {}", e);
+                    }
+                }
+                Some(Transition::Returned) => {
+                    info!("Proximity devices are back");
+                    // Only a hint, unlocking stays with the user as anyone could bring them back
+                    if settings.notify_on_return {
+                        let _ = returned_sender.send(());
+                    }
+                }
+                None => {}
+            }
+        }
+    });
+    
+    returned
+}
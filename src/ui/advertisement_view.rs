@@ -0,0 +1,187 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use adw::prelude::*;
+use gtk::{
+    glib, subclass::prelude::*, Box as GtkBox, Label, ListBox, Orientation, SelectionMode, Widget,
+};
+
+use crate::bluetooth::advertisement::{self, Advertisement};
+use crate::bluetooth::beacon;
+
+glib::wrapper! {
+    pub struct AdvertisementView(ObjectSubclass<imp::AdvertisementView>)
+        @extends GtkBox, Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+fn format_time(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0);
+    glib::DateTime::from_unix_local(seconds as i64)
+        .and_then(|time| time.format("%H:%M:%S"))
+        .map(|time| time.to_string())
+        .unwrap_or_default()
+}
+
+fn data_row(title: &str, data: &[u8]) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(advertisement::hex(data))
+        .build();
+    row.add_css_class("property");
+    row
+}
+
+impl AdvertisementView {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+    
+    /// Show the latest advertisement, `None` hides the view for devices that never advertised.
+    pub fn set_advertisement(&self, advertisement: Option<&Advertisement>) {
+        let imp = self.imp();
+        
+        for row in imp.data_rows.borrow_mut().drain(..) {
+            imp.data_group.remove(&row);
+        }
+        while let Some(child) = imp.history_list.first_child() {
+            imp.history_list.remove(&child);
+        }
+        
+        let advertisement = match advertisement {
+            Some(advertisement) => advertisement,
+            None => {
+                self.set_visible(false);
+                return;
+            }
+        };
+        self.set_visible(true);
+        
+        let flags = advertisement::flag_names(&advertisement.flags);
+        imp.flags_row.set_subtitle(&if advertisement.flags.is_empty() {
+            "Not advertised".to_string()
+        } else if flags.is_empty() {
+            advertisement::hex(&advertisement.flags)
+        } else {
+            format!("{} ({})", flags.join(", "), advertisement::hex(&advertisement.flags))
+        });
+        imp.tx_power_row.set_subtitle(&match advertisement.tx_power {
+            Some(power) => format!("{} dBm", power),
+            None => "Not advertised".to_string(),
+        });
+        imp.rssi_row.set_subtitle(&match advertisement.rssi {
+            Some(rssi) => format!("{} dBm", rssi),
+            None => "Unavailable".to_string(),
+        });
+        imp.last_seen_row.set_subtitle(&advertisement.last_seen.map(format_time).unwrap_or_default());
+        
+        let mut rows = Vec::new();
+        for beacon in beacon::decode(advertisement) {
+            let row = data_row(beacon.kind(), &[]);
+            row.set_subtitle(&beacon.to_string());
+            rows.push(row);
+        }
+        if let Some(distance) = beacon::distance(advertisement) {
+            let row = data_row("Estimated Distance", &[]);
+            row.set_subtitle(&format!("{:.1} m", distance));
+            rows.push(row);
+        }
+        for (company, data) in &advertisement.manufacturer_data {
+            rows.push(data_row(&format!("Manufacturer {}", advertisement::company_label(*company)), data));
+        }
+        for (uuid, data) in &advertisement.service_data {
+            rows.push(data_row(&format!("Service {}", uuid), data));
+        }
+        for (ad_type, data) in &advertisement.data {
+            rows.push(data_row(&format!("AD Type 0x{:02X}", ad_type), data));
+        }
+        for row in rows {
+            imp.data_group.add(&row);
+            imp.data_rows.borrow_mut().push(row);
+        }
+        
+        // Newest first
+        for record in advertisement.history.iter().rev() {
+            let label = Label::new(Some(&format!("{}  {}", format_time(record.time), record.field)));
+            label.set_halign(gtk::Align::Start);
+            label.set_selectable(true);
+            label.set_wrap(true);
+            label.set_xalign(0.0);
+            label.add_css_class("monospace");
+            label.set_margin_top(4);
+            label.set_margin_bottom(4);
+            label.set_margin_start(12);
+            label.set_margin_end(12);
+            imp.history_list.append(&label);
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+    use std::cell::RefCell;
+
+    pub struct AdvertisementView {
+        pub data_group: adw::PreferencesGroup,
+        pub flags_row: adw::ActionRow,
+        pub tx_power_row: adw::ActionRow,
+        pub rssi_row: adw::ActionRow,
+        pub last_seen_row: adw::ActionRow,
+        pub data_rows: RefCell<Vec<adw::ActionRow>>,
+        pub history_list: ListBox,
+    }
+    
+    impl Default for AdvertisementView {
+        fn default() -> Self {
+            Self {
+                data_group: adw::PreferencesGroup::new(),
+                flags_row: data_row("Flags", &[]),
+                tx_power_row: data_row("TX Power", &[]),
+                rssi_row: data_row("RSSI", &[]),
+                last_seen_row: data_row("Last Seen", &[]),
+                data_rows: RefCell::new(Vec::new()),
+                history_list: ListBox::new(),
+            }
+        }
+    }
+    
+    #[glib::object_subclass]
+    impl ObjectSubclass for AdvertisementView {
+        const NAME: &'static str = "AdvertisementView";
+        type Type = super::AdvertisementView;
+        type ParentType = GtkBox;
+    }
+    
+    impl ObjectImpl for AdvertisementView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            
+            let obj = self.obj();
+            obj.set_orientation(Orientation::Vertical);
+            obj.set_spacing(24);
+            obj.set_margin_bottom(16);
+            obj.set_margin_start(16);
+            obj.set_margin_end(16);
+            // Only shown for devices that were seen advertising
+            obj.set_visible(false);
+            
+            // Latest advertised values
+            self.data_group.set_title("Advertisement");
+            self.data_group.add(&self.flags_row);
+            self.data_group.add(&self.tx_power_row);
+            self.data_group.add(&self.rssi_row);
+            self.data_group.add(&self.last_seen_row);
+            obj.append(&self.data_group);
+            
+            // Everything received, as it arrived
+            let history_group = adw::PreferencesGroup::new();
+            history_group.set_title("Advertisement Log");
+            self.history_list.set_selection_mode(SelectionMode::None);
+            self.history_list.add_css_class("boxed-list");
+            history_group.add(&self.history_list);
+            obj.append(&history_group);
+        }
+    }
+    
+    impl WidgetImpl for AdvertisementView {}
+    impl BoxImpl for AdvertisementView {}
+}
@@ -0,0 +1,410 @@
+use adw::prelude::*;
+use gtk::{
+    glib, subclass::prelude::*, Box as GtkBox, DropDown, Label, Orientation, Switch, Widget,
+};
+
+use crate::audio::pulse::AudioCard;
+use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::records::{self, DeviceRecord};
+use crate::bluetooth::uuids;
+use crate::config::DeviceSettings;
+
+glib::wrapper! {
+    pub struct DeviceDetailsView(ObjectSubclass<imp::DeviceDetailsView>)
+        @extends GtkBox, Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl DeviceDetailsView {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+    
+    /// Called with the card name and the profile name when the user picks a profile.
+    pub fn set_profile_callback<F>(&self, callback: F)
+    where
+        F: Fn(String, String) + 'static,
+    {
+        let imp = self.imp();
+        *imp.profile_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    /// Called with the device address and its updated settings whenever a setting is toggled.
+    pub fn set_settings_callback<F>(&self, callback: F)
+    where
+        F: Fn(String, DeviceSettings) + 'static,
+    {
+        let imp = self.imp();
+        *imp.settings_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    pub fn address(&self) -> Option<String> {
+        self.imp().address.borrow().clone()
+    }
+    
+    pub fn set_device(&self, device: &BluetoothDevice) {
+        let imp = self.imp();
+        
+        if imp.address.borrow().as_deref() != Some(device.address.as_str()) {
+            // A different device, forget the audio card of the previous one
+            self.set_audio_card(None);
+        }
+        imp.address.replace(Some(device.address.clone()));
+        
+        imp.title_label.set_markup(&format!("<big><b>{}</b></big>", glib::markup_escape_text(&device.name)));
+        imp.address_row.set_subtitle(&device.address);
+        imp.type_row.set_subtitle(&device.device_type);
+        imp.vendor_row.set_subtitle(&glib::markup_escape_text(device.vendor.as_deref().unwrap_or("Unknown")));
+        imp.status_row.set_subtitle(if device.connected {
+            "Connected"
+        } else if device.reconnecting {
+            "Reconnecting…"
+        } else {
+            "Disconnected"
+        });
+        imp.paired_row.set_subtitle(if device.paired { "Yes" } else { "No" });
+        imp.trusted_row.set_subtitle(if device.trusted { "Yes" } else { "No" });
+        imp.rssi_row.set_subtitle(&match device.rssi {
+            Some(rssi) => format!("{} dBm", rssi),
+            None => "Unavailable".to_string(),
+        });
+        imp.battery_row.set_subtitle(&match device.battery {
+            Some(percentage) => format!("{}%", percentage),
+            None => "Unavailable".to_string(),
+        });
+        
+        for row in imp.service_rows.borrow_mut().drain(..) {
+            imp.services_group.remove(&row);
+        }
+        for uuid in &device.uuids {
+            let row = info_row(uuids::name(uuid).unwrap_or("Unknown Service"));
+            row.set_subtitle(uuid);
+            imp.services_group.add(&row);
+            imp.service_rows.borrow_mut().push(row);
+        }
+        imp.services_group.set_visible(!device.uuids.is_empty());
+        
+        self.set_record(device.record.as_ref(), device.connected);
+    }
+    
+    fn set_record(&self, record: Option<&DeviceRecord>, connected: bool) {
+        let imp = self.imp();
+        
+        let Some(record) = record else {
+            imp.history_group.set_visible(false);
+            return;
+        };
+        let ago = |time: Option<_>| time.map(records::format_ago).unwrap_or_else(|| "Never".to_string());
+        imp.first_seen_row.set_subtitle(&ago(record.first_seen));
+        imp.last_seen_row.set_subtitle(&ago(record.last_seen));
+        imp.last_connected_row.set_subtitle(&if connected { "Now".to_string() } else { ago(record.last_connected) });
+        imp.connections_row.set_subtitle(&record.connections.to_string());
+        imp.connected_time_row.set_subtitle(&records::format_duration(record.total_connected_time()));
+        imp.history_group.set_visible(true);
+    }
+    
+    pub fn set_audio_card(&self, card: Option<&AudioCard>) {
+        let imp = self.imp();
+        
+        let card = match card {
+            Some(card) => card,
+            None => {
+                imp.audio_group.set_visible(false);
+                imp.card_name.replace(None);
+                imp.profile_names.borrow_mut().clear();
+                return;
+            }
+        };
+        
+        // Changing the model fires selected-notify, which must not switch the profile
+        imp.updating.set(true);
+        
+        let descriptions: Vec<String> = card.profiles.iter()
+            .map(|profile| {
+                if profile.available {
+                    profile.description.clone()
+                } else {
+                    format!("{} (unavailable)", profile.description)
+                }
+            })
+            .collect();
+        let descriptions: Vec<&str> = descriptions.iter().map(String::as_str).collect();
+        imp.profile_dropdown.set_model(Some(&gtk::StringList::new(&descriptions)));
+        
+        if let Some(position) = card.profiles.iter().position(|profile| profile.name == card.active_profile) {
+            imp.profile_dropdown.set_selected(position as u32);
+        }
+        
+        imp.codec_row.set_subtitle(&match &card.codec {
+            Some(codec) => codec.to_string(),
+            None => "Unknown".to_string(),
+        });
+        
+        imp.card_name.replace(Some(card.name.clone()));
+        *imp.profile_names.borrow_mut() = card.profiles.iter().map(|profile| profile.name.clone()).collect();
+        imp.audio_group.set_visible(true);
+        
+        imp.updating.set(false);
+    }
+    
+    pub fn set_settings(&self, settings: &DeviceSettings) {
+        let imp = self.imp();
+        
+        imp.updating.set(true);
+        imp.default_audio_switch.set_active(settings.default_audio_on_connect);
+        imp.auto_connect_switch.set_active(settings.auto_connect);
+        imp.lock_when_away_switch.set_active(settings.lock_when_away);
+        imp.updating.set(false);
+        
+        imp.settings.replace(settings.clone());
+    }
+    
+    fn on_settings_changed(&self) {
+        let imp = self.imp();
+        if imp.updating.get() {
+            return;
+        }
+        
+        let address = match imp.address.borrow().clone() {
+            Some(address) => address,
+            None => return,
+        };
+        
+        let settings = {
+            let mut settings = imp.settings.borrow_mut();
+            settings.default_audio_on_connect = imp.default_audio_switch.is_active();
+            settings.auto_connect = imp.auto_connect_switch.is_active();
+            settings.lock_when_away = imp.lock_when_away_switch.is_active();
+            settings.clone()
+        };
+        
+        let callback = imp.settings_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(address, settings);
+        }
+    }
+    
+    fn on_profile_selected(&self) {
+        let imp = self.imp();
+        if imp.updating.get() {
+            return;
+        }
+        
+        let card = match imp.card_name.borrow().clone() {
+            Some(card) => card,
+            None => return,
+        };
+        let profile = match imp.profile_names.borrow().get(imp.profile_dropdown.selected() as usize) {
+            Some(profile) => profile.clone(),
+            None => return,
+        };
+        
+        log::info!("Profile {} selected for card {}", profile, card);
+        let callback = imp.profile_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(card, profile);
+        }
+    }
+}
+
+fn info_row(title: &str) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .build();
+    row.add_css_class("property");
+    row
+}
+
+mod imp {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    type ProfileCallbackFn = Box<dyn Fn(String, String)>;
+    type SettingsCallbackFn = Box<dyn Fn(String, DeviceSettings)>;
+    
+    pub struct DeviceDetailsView {
+        pub address: RefCell<Option<String>>,
+        pub title_label: Label,
+        pub address_row: adw::ActionRow,
+        pub type_row: adw::ActionRow,
+        pub vendor_row: adw::ActionRow,
+        pub status_row: adw::ActionRow,
+        pub paired_row: adw::ActionRow,
+        pub trusted_row: adw::ActionRow,
+        pub rssi_row: adw::ActionRow,
+        pub battery_row: adw::ActionRow,
+        pub history_group: adw::PreferencesGroup,
+        pub first_seen_row: adw::ActionRow,
+        pub last_seen_row: adw::ActionRow,
+        pub last_connected_row: adw::ActionRow,
+        pub connections_row: adw::ActionRow,
+        pub connected_time_row: adw::ActionRow,
+        pub services_group: adw::PreferencesGroup,
+        pub service_rows: RefCell<Vec<adw::ActionRow>>,
+        pub audio_group: adw::PreferencesGroup,
+        pub profile_dropdown: DropDown,
+        pub codec_row: adw::ActionRow,
+        pub card_name: RefCell<Option<String>>,
+        pub profile_names: RefCell<Vec<String>>,
+        pub default_audio_switch: Switch,
+        pub auto_connect_switch: Switch,
+        pub lock_when_away_switch: Switch,
+        pub settings: RefCell<DeviceSettings>,
+        pub updating: Cell<bool>,
+        pub profile_callback: RefCell<Option<ProfileCallbackFn>>,
+        pub settings_callback: RefCell<Option<SettingsCallbackFn>>,
+    }
+    
+    impl Default for DeviceDetailsView {
+        fn default() -> Self {
+            Self {
+                address: RefCell::new(None),
+                title_label: Label::new(None),
+                address_row: info_row("Address"),
+                type_row: info_row("Type"),
+                vendor_row: info_row("Manufacturer"),
+                status_row: info_row("Status"),
+                paired_row: info_row("Paired"),
+                trusted_row: info_row("Trusted"),
+                rssi_row: info_row("Signal Strength"),
+                battery_row: info_row("Battery"),
+                history_group: adw::PreferencesGroup::new(),
+                first_seen_row: info_row("First Seen"),
+                last_seen_row: info_row("Last Seen"),
+                last_connected_row: info_row("Last Connected"),
+                connections_row: info_row("Connections"),
+                connected_time_row: info_row("Time Connected"),
+                services_group: adw::PreferencesGroup::new(),
+                service_rows: RefCell::new(Vec::new()),
+                audio_group: adw::PreferencesGroup::new(),
+                profile_dropdown: DropDown::from_strings(&[]),
+                codec_row: info_row("Codec"),
+                card_name: RefCell::new(None),
+                profile_names: RefCell::new(Vec::new()),
+                default_audio_switch: Switch::new(),
+                auto_connect_switch: Switch::new(),
+                lock_when_away_switch: Switch::new(),
+                settings: RefCell::new(DeviceSettings::default()),
+                updating: Cell::new(false),
+                profile_callback: RefCell::new(None),
+                settings_callback: RefCell::new(None),
+            }
+        }
+    }
+    
+    #[glib::object_subclass]
+    impl ObjectSubclass for DeviceDetailsView {
+        const NAME: &'static str = "DeviceDetailsView";
+        type Type = super::DeviceDetailsView;
+        type ParentType = GtkBox;
+    }
+    
+    impl ObjectImpl for DeviceDetailsView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            
+            let obj = self.obj();
+            obj.set_orientation(Orientation::Vertical);
+            obj.set_spacing(24);
+            obj.set_margin_top(16);
+            obj.set_margin_bottom(16);
+            obj.set_margin_start(16);
+            obj.set_margin_end(16);
+            
+            self.title_label.set_halign(gtk::Align::Start);
+            obj.append(&self.title_label);
+            
+            // General device information
+            let info_group = adw::PreferencesGroup::new();
+            info_group.set_title("Device");
+            info_group.add(&self.address_row);
+            info_group.add(&self.type_row);
+            info_group.add(&self.vendor_row);
+            info_group.add(&self.status_row);
+            info_group.add(&self.paired_row);
+            info_group.add(&self.trusted_row);
+            info_group.add(&self.rssi_row);
+            info_group.add(&self.battery_row);
+            obj.append(&info_group);
+            
+            // When the device was around, recorded across sessions
+            self.history_group.set_title("History");
+            self.history_group.add(&self.first_seen_row);
+            self.history_group.add(&self.last_seen_row);
+            self.history_group.add(&self.last_connected_row);
+            self.history_group.add(&self.connections_row);
+            self.history_group.add(&self.connected_time_row);
+            self.history_group.set_visible(false);
+            obj.append(&self.history_group);
+            
+            // Profiles and services the device advertises
+            self.services_group.set_title("Services");
+            self.services_group.set_visible(false);
+            obj.append(&self.services_group);
+            
+            // Audio profile and codec, only shown when the sound server has a card for the device
+            self.audio_group.set_title("Audio");
+            self.audio_group.set_visible(false);
+            
+            let profile_row = adw::ActionRow::builder()
+                .title("Profile")
+                .build();
+            self.profile_dropdown.set_valign(gtk::Align::Center);
+            profile_row.add_suffix(&self.profile_dropdown);
+            self.audio_group.add(&profile_row);
+            self.audio_group.add(&self.codec_row);
+            obj.append(&self.audio_group);
+            
+            // Per-device settings stored by RustBlue
+            let settings_group = adw::PreferencesGroup::new();
+            settings_group.set_title("Settings");
+            
+            let default_audio_row = adw::ActionRow::builder()
+                .title("Default Audio Device")
+                .subtitle("Use as default output and input while connected")
+                .build();
+            self.default_audio_switch.set_valign(gtk::Align::Center);
+            default_audio_row.add_suffix(&self.default_audio_switch);
+            default_audio_row.set_activatable_widget(Some(&self.default_audio_switch));
+            settings_group.add(&default_audio_row);
+            
+            let auto_connect_row = adw::ActionRow::builder()
+                .title("Reconnect Automatically")
+                .subtitle("Keep trying to connect when the trusted device drops out")
+                .build();
+            self.auto_connect_switch.set_valign(gtk::Align::Center);
+            auto_connect_row.add_suffix(&self.auto_connect_switch);
+            auto_connect_row.set_activatable_widget(Some(&self.auto_connect_switch));
+            settings_group.add(&auto_connect_row);
+            
+            let lock_when_away_row = adw::ActionRow::builder()
+                .title("Lock When Away")
+                .subtitle("Lock the session when this phone leaves, see Preferences")
+                .build();
+            self.lock_when_away_switch.set_valign(gtk::Align::Center);
+            lock_when_away_row.add_suffix(&self.lock_when_away_switch);
+            lock_when_away_row.set_activatable_widget(Some(&self.lock_when_away_switch));
+            settings_group.add(&lock_when_away_row);
+            obj.append(&settings_group);
+            
+            self.profile_dropdown.connect_selected_notify(glib::clone!(@weak obj => move |_| {
+                obj.on_profile_selected();
+            }));
+            
+            self.default_audio_switch.connect_active_notify(glib::clone!(@weak obj => move |_| {
+                obj.on_settings_changed();
+            }));
+            
+            self.auto_connect_switch.connect_active_notify(glib::clone!(@weak obj => move |_| {
+                obj.on_settings_changed();
+            }));
+            
+            self.lock_when_away_switch.connect_active_notify(glib::clone!(@weak obj => move |_| {
+                obj.on_settings_changed();
+            }));
+        }
+    }
+    
+    impl WidgetImpl for DeviceDetailsView {}
+    impl BoxImpl for DeviceDetailsView {}
+}
@@ -1,9 +1,18 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use adw::prelude::*;
 use gtk::{
-    glib, prelude::*, subclass::prelude::*, Box as GtkBox, Button, Label, ListBox, Orientation,
-    SelectionMode, Widget, ScrolledWindow,
+    gdk, gio, glib, subclass::prelude::*, Box as GtkBox, Button, DropDown, FileChooserAction,
+    FileChooserNative, Label, ListBox, Orientation, ResponseType, SearchBar, SearchEntry, SelectionMode,
+    ToggleButton, Widget, ScrolledWindow,
 };
 
 use crate::bluetooth::device::BluetoothDevice;
+use crate::bluetooth::records;
+use crate::config::{DeviceListSettings, DeviceSort};
 
 glib::wrapper! {
     pub struct DeviceListView(ObjectSubclass<imp::DeviceListView>)
@@ -11,6 +20,78 @@ glib::wrapper! {
         @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
 }
 
+fn status_text(device: &BluetoothDevice) -> &'static str {
+    if device.connected {
+        "Connected"
+    } else if device.reconnecting {
+        "Reconnecting…"
+    } else {
+        "Disconnected"
+    }
+}
+
+/// Address, plus the beacon type and estimated distance when one was recognised and when a
+/// disconnected device was last seen.
+fn detail_text(device: &BluetoothDevice) -> String {
+    let mut parts = vec![device.address.clone()];
+    if let Some(beacon) = &device.beacon {
+        parts.push(beacon.clone());
+    }
+    if let Some(distance) = device.distance {
+        parts.push(format!("~{:.1} m", distance));
+    }
+    if let (false, Some(last_seen)) = (device.connected, device.last_seen) {
+        parts.push(format!("seen {}", records::format_ago(last_seen)));
+    }
+    parts.join(" · ")
+}
+
+/// Signal bars for an RSSI, from the standard cellular signal icons.
+fn signal_icon_name(rssi: i16) -> &'static str {
+    match rssi {
+        -55.. => "network-cellular-signal-excellent-symbolic",
+        -67.. => "network-cellular-signal-good-symbolic",
+        -75.. => "network-cellular-signal-ok-symbolic",
+        -85.. => "network-cellular-signal-weak-symbolic",
+        _ => "network-cellular-signal-none-symbolic",
+    }
+}
+
+/// Whether the device passes the filters and matches the search text, which must be lowercase.
+fn matches(device: &BluetoothDevice, settings: &DeviceListSettings, query: &str) -> bool {
+    if (settings.paired_only && !device.paired)
+        || (settings.connected_only && !device.connected)
+        || (settings.nearby_only && device.rssi.is_none())
+        || (settings.hide_unnamed && !device.named)
+    {
+        return false;
+    }
+    if settings.device_type.as_ref().is_some_and(|device_type| *device_type != device.device_type) {
+        return false;
+    }
+    
+    query.is_empty() || [Some(&device.name), device.alias.as_ref(), Some(&device.address)]
+        .into_iter()
+        .flatten()
+        .any(|text| text.to_lowercase().contains(query))
+}
+
+fn last_connected(device: &BluetoothDevice) -> Option<SystemTime> {
+    device.record.as_ref().and_then(|record| record.last_connected)
+}
+
+fn compare(a: &BluetoothDevice, b: &BluetoothDevice, sort: DeviceSort) -> Ordering {
+    let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+    match sort {
+        DeviceSort::Name => by_name(),
+        // Descending, devices without a value last
+        DeviceSort::Rssi => b.rssi.cmp(&a.rssi).then_with(by_name),
+        DeviceSort::LastSeen => b.last_seen.cmp(&a.last_seen).then_with(by_name),
+        DeviceSort::LastConnected => last_connected(b).cmp(&last_connected(a)).then_with(by_name),
+        DeviceSort::Type => a.device_type.cmp(&b.device_type).then_with(by_name),
+    }
+}
+
 impl DeviceListView {
     pub fn new() -> Self {
         glib::Object::builder().build()
@@ -40,154 +121,243 @@ impl DeviceListView {
         *imp.forget_callback.borrow_mut() = Some(Box::new(callback));
     }
     
-    pub fn add_device(&self, device: &BluetoothDevice) {
-        log::info!("Adding device to UI: {} ({})", device.name, device.address);
+    pub fn set_details_callback<F>(&self, callback: F) 
+    where
+        F: Fn(String) + 'static,
+    {
+        let imp = self.imp();
+        *imp.details_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    /// Called with the device address and the files picked or dropped onto its row.
+    pub fn set_send_files_callback<F>(&self, callback: F) 
+    where
+        F: Fn(String, Vec<PathBuf>) + 'static,
+    {
+        let imp = self.imp();
+        *imp.send_files_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    /// Called with the new filters and sort order whenever the user changes them.
+    pub fn set_list_settings_callback<F>(&self, callback: F)
+    where
+        F: Fn(DeviceListSettings) + 'static,
+    {
+        let imp = self.imp();
+        *imp.list_settings_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    pub fn set_list_settings(&self, settings: &DeviceListSettings) {
         let imp = self.imp();
         
-        // Create a compact device row with horizontal layout
-        let device_row = GtkBox::new(Orientation::Horizontal, 8);
-        device_row.set_margin_top(6);
-        device_row.set_margin_bottom(6);
-        device_row.set_margin_start(12);
-        device_row.set_margin_end(12);
-        device_row.set_hexpand(true);
-        
-        // Left side - device info
-        let info_box = GtkBox::new(Orientation::Vertical, 2);
-        info_box.set_hexpand(true);
-        
-        let name_label = Label::new(Some(&device.name));
-        name_label.set_halign(gtk::Align::Start);
-        name_label.set_markup(&format!("<b>{}</b>", device.name));
-        name_label.set_hexpand(true);
-        
-        let address_label = Label::new(Some(&device.address));
-        address_label.set_halign(gtk::Align::Start);
-        address_label.set_markup(&format!("<small>{}</small>", device.address));
-        address_label.add_css_class("dim-label");
-        address_label.set_hexpand(true);
-        
-        let status_label = Label::new(Some(if device.connected { "Connected" } else { "Disconnected" }));
-        status_label.set_halign(gtk::Align::Start);
-        status_label.set_markup(&format!("<small><i>{}</i></small>", 
-            if device.connected { "Connected" } else { "Disconnected" }));
-        status_label.add_css_class(if device.connected { "success" } else { "warning" });
-        status_label.set_hexpand(true);
-        
-        info_box.append(&name_label);
-        info_box.append(&address_label);
-        info_box.append(&status_label);
-        
-        // Right side - action buttons (compact spacing)
-        let button_box = GtkBox::new(Orientation::Horizontal, 4);
-        button_box.set_halign(gtk::Align::End);
-        button_box.set_valign(gtk::Align::Center);
+        imp.updating.set(true);
+        imp.paired_button.set_active(settings.paired_only);
+        imp.connected_button.set_active(settings.connected_only);
+        imp.nearby_button.set_active(settings.nearby_only);
+        imp.unnamed_button.set_active(settings.hide_unnamed);
+        // The first entry of the type filter is "All Types"
+        let type_position = settings.device_type.as_ref()
+            .and_then(|device_type| BluetoothDevice::TYPES.iter().position(|t| t == device_type))
+            .map(|position| position + 1)
+            .unwrap_or(0);
+        imp.type_dropdown.set_selected(type_position as u32);
+        let sort_position = DeviceSort::ALL.iter().position(|sort| *sort == settings.sort).unwrap_or(0);
+        imp.sort_dropdown.set_selected(sort_position as u32);
+        imp.updating.set(false);
+        
+        imp.list_settings.replace(settings.clone());
+        self.render_devices();
+    }
+    
+    fn on_list_settings_changed(&self) {
+        let imp = self.imp();
+        if imp.updating.get() {
+            return;
+        }
         
-        // Connect/Disconnect button
-        let connection_button = if device.connected {
-            Button::with_label("Disconnect")
-        } else {
-            Button::with_label("Connect")
+        let type_position = imp.type_dropdown.selected() as usize;
+        let settings = DeviceListSettings {
+            paired_only: imp.paired_button.is_active(),
+            connected_only: imp.connected_button.is_active(),
+            nearby_only: imp.nearby_button.is_active(),
+            device_type: type_position.checked_sub(1)
+                .and_then(|index| BluetoothDevice::TYPES.get(index))
+                .map(|device_type| device_type.to_string()),
+            hide_unnamed: imp.unnamed_button.is_active(),
+            sort: DeviceSort::ALL.get(imp.sort_dropdown.selected() as usize).copied().unwrap_or_default(),
         };
+        imp.list_settings.replace(settings.clone());
+        self.render_devices();
         
-        if device.connected {
-            connection_button.add_css_class("destructive-action");
-        } else {
-            connection_button.add_css_class("suggested-action");
+        let callback = imp.list_settings_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(settings);
+        }
+    }
+    
+    fn send_files(&self, address: String, files: Vec<PathBuf>) {
+        if files.is_empty() {
+            return;
         }
         
-        // Clone device address for button callbacks
-        let device_address = device.address.clone();
-        let device_connected = device.connected;
-        
-        connection_button.connect_clicked(glib::clone!(@weak self as device_list => move |_| {
-            log::info!("Connection button clicked for device: {}", device_address);
-            if device_connected {
-                log::info!("Attempting to disconnect device: {}", device_address);
-                let imp = device_list.imp();
-                let callback = imp.disconnect_callback.borrow();
-                if let Some(ref cb) = *callback {
-                    cb(device_address.clone());
-                }
-            } else {
-                log::info!("Attempting to connect device: {}", device_address);
-                let imp = device_list.imp();
-                let callback = imp.connect_callback.borrow();
-                if let Some(ref cb) = *callback {
-                    cb(device_address.clone());
-                }
+        log::info!("Sending {} files to device: {}", files.len(), address);
+        let callback = self.imp().send_files_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(address, files);
+        }
+    }
+    
+    fn choose_files_to_send(&self, address: String) {
+        let parent = self.root().and_downcast::<gtk::Window>();
+        let file_chooser = FileChooserNative::new(
+            Some("Send Files"),
+            parent.as_ref(),
+            FileChooserAction::Open,
+            Some("Send"),
+            Some("Cancel"),
+        );
+        file_chooser.set_select_multiple(true);
+        
+        file_chooser.connect_response(glib::clone!(@weak self as device_list => move |file_chooser, response| {
+            if response == ResponseType::Accept {
+                let files = file_chooser.files();
+                let paths = (0..files.n_items())
+                    .filter_map(|i| files.item(i).and_downcast::<gio::File>())
+                    .filter_map(|file| file.path())
+                    .collect();
+                device_list.send_files(address.clone(), paths);
             }
+            device_list.imp().file_chooser.replace(None);
         }));
         
-        // Forget button (only show for paired devices)
-        let forget_button = Button::with_label("Forget");
-        forget_button.add_css_class("destructive-action");
-        
-        let device_address_forget = device.address.clone();
-        forget_button.connect_clicked(glib::clone!(@weak self as device_list => move |_| {
-            log::info!("Forget button clicked for device: {}", device_address_forget);
-            let imp = device_list.imp();
-            let callback = imp.forget_callback.borrow();
-            if let Some(ref cb) = *callback {
-                cb(device_address_forget.clone());
+        file_chooser.show();
+        // Native dialogs are not kept alive by GTK while they are shown
+        self.imp().file_chooser.replace(Some(file_chooser));
+    }
+    
+    fn add_send_files_support(&self, device_row: &GtkBox, button_box: &GtkBox, address: &str) {
+        // "Send files…" button opening a file chooser
+        let send_button = Button::from_icon_name("document-send-symbolic");
+        send_button.set_tooltip_text(Some("Send files…"));
+        send_button.add_css_class("flat");
+        
+        let device_address = address.to_string();
+        send_button.connect_clicked(glib::clone!(@weak self as device_list => move |_| {
+            device_list.choose_files_to_send(device_address.clone());
+        }));
+        button_box.append(&send_button);
+        
+        // Dropping files onto the row sends them as well
+        let drop_target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+        let device_address = address.to_string();
+        drop_target.connect_drop(glib::clone!(@weak self as device_list => @default-return false, move |_, value, _, _| {
+            match value.get::<gdk::FileList>() {
+                Ok(file_list) => {
+                    let paths = file_list.files().iter().filter_map(|file| file.path()).collect();
+                    device_list.send_files(device_address.clone(), paths);
+                    true
+                }
+                Err(_) => false,
             }
         }));
-        
-        button_box.append(&connection_button);
-        if device.paired {
-            button_box.append(&forget_button);
-        }
-        
-        device_row.append(&info_box);
-        device_row.append(&button_box);
-        
-        let list_box = imp.list_box.borrow();
-        list_box.append(&device_row);
-        log::info!("Device added to list box with action buttons");
+        device_row.add_controller(drop_target);
     }
     
     pub fn clear_devices(&self) {
         log::debug!("Clearing all devices from UI");
         let imp = self.imp();
-        let list_box = imp.list_box.borrow();
-        while let Some(child) = list_box.first_child() {
-            list_box.remove(&child);
+        // Only rows, removing the placeholder would unset it
+        for list_box in [&imp.connected_list, &imp.paired_list, &imp.nearby_list] {
+            while let Some(row) = list_box.row_at_index(0) {
+                list_box.remove(&row);
+            }
         }
+        imp.signal_icons.borrow_mut().clear();
         log::debug!("All devices cleared");
     }
     
     pub fn update_devices_efficiently(&self, devices: Vec<BluetoothDevice>) {
         log::debug!("Efficiently updating device list with {} devices", devices.len());
+        self.imp().devices.replace(devices);
+        self.render_devices();
+        log::debug!("Efficient device list update complete");
+    }
+    
+    /// The last known state of a device, as shown in the list.
+    pub fn device(&self, address: &str) -> Option<BluetoothDevice> {
+        self.imp().devices.borrow().iter().find(|device| device.address == address).cloned()
+    }
+    
+    /// Update the signal bars of a device without rebuilding the list.
+    pub fn set_rssi(&self, address: &str, rssi: Option<i16>) {
+        if let Some(icon) = self.imp().signal_icons.borrow().get(address) {
+            Self::show_rssi(icon, rssi);
+        }
+    }
+    
+    fn show_rssi(icon: &gtk::Image, rssi: Option<i16>) {
+        match rssi {
+            Some(rssi) => {
+                icon.set_icon_name(Some(signal_icon_name(rssi)));
+                icon.set_tooltip_text(Some(&format!("Signal strength: {} dBm", rssi)));
+                icon.set_visible(true);
+            }
+            None => icon.set_visible(false),
+        }
+    }
+    
+    /// Nearby devices are only listed while the adapter is discovering.
+    pub fn set_discovering(&self, discovering: bool) {
         let imp = self.imp();
-        
-        let list_box = imp.list_box.borrow();
-        
-        // Clear existing devices
-        while let Some(child) = list_box.first_child() {
-            list_box.remove(&child);
+        if imp.discovering.replace(discovering) != discovering {
+            self.render_devices();
         }
+    }
+    
+    /// Connected, My Devices (paired) or Nearby section the device belongs in.
+    fn section_list(&self, device: &BluetoothDevice) -> ListBox {
+        let imp = self.imp();
+        if device.connected {
+            imp.connected_list.clone()
+        } else if device.paired {
+            imp.paired_list.clone()
+        } else {
+            imp.nearby_list.clone()
+        }
+    }
+    
+    /// Rebuild the rows from the last known devices with the current search, filters and sort order.
+    fn render_devices(&self) {
+        let imp = self.imp();
         
-        // Sort devices: connected devices first, then by name
-        let mut sorted_devices = devices;
-        sorted_devices.sort_by(|a, b| {
-            match (a.connected, b.connected) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a.name.cmp(&b.name),
+        self.clear_devices();
+        
+        let settings = imp.list_settings.borrow().clone();
+        let query = imp.search_entry.text().to_lowercase();
+        let mut devices: Vec<BluetoothDevice> = imp.devices.borrow().iter()
+            .filter(|device| matches(device, &settings, &query))
+            .cloned()
+            .collect();
+        devices.sort_by(|a, b| compare(a, b, settings.sort));
+        
+        let discovering = imp.discovering.get();
+        for device in &devices {
+            let list_box = self.section_list(device);
+            if list_box == imp.nearby_list && !discovering {
+                continue;
             }
-        });
-        
-        // Add devices efficiently
-        for device in sorted_devices {
-            self.add_device_internal(&device);
+            self.add_device_internal(&list_box, device);
         }
         
-        log::debug!("Efficient device list update complete");
+        let connected = imp.connected_list.row_at_index(0).is_some();
+        let paired = imp.paired_list.row_at_index(0).is_some();
+        imp.connected_group.set_visible(connected);
+        imp.paired_group.set_visible(paired);
+        imp.nearby_group.set_visible(discovering);
+        imp.empty_label.set_visible(!connected && !paired && !discovering);
     }
     
-    fn add_device_internal(&self, device: &BluetoothDevice) {
-        let imp = self.imp();
-        
+    fn add_device_internal(&self, list_box: &ListBox, device: &BluetoothDevice) {
         // Create a compact device row with horizontal layout (optimized)
         let device_row = GtkBox::new(Orientation::Horizontal, 8);
         device_row.set_margin_top(6); // Reduced margin for compact view
@@ -202,19 +372,19 @@ impl DeviceListView {
         
         let name_label = Label::new(Some(&device.name));
         name_label.set_halign(gtk::Align::Start);
-        name_label.set_markup(&format!("<b>{}</b>", device.name));
+        name_label.set_markup(&format!("<b>{}</b>", glib::markup_escape_text(&device.name)));
         name_label.set_hexpand(true);
         
         let address_label = Label::new(Some(&device.address));
         address_label.set_halign(gtk::Align::Start);
-        address_label.set_markup(&format!("<small>{}</small>", device.address));
+        address_label.set_markup(&format!("<small>{}</small>", glib::markup_escape_text(&detail_text(device))));
         address_label.add_css_class("dim-label");
         address_label.set_hexpand(true);
         
-        let status_label = Label::new(Some(if device.connected { "Connected" } else { "Disconnected" }));
+        let status = status_text(device);
+        let status_label = Label::new(Some(status));
         status_label.set_halign(gtk::Align::Start);
-        status_label.set_markup(&format!("<small><i>{}</i></small>", 
-            if device.connected { "Connected" } else { "Disconnected" }));
+        status_label.set_markup(&format!("<small><i>{}</i></small>", status));
         status_label.add_css_class(if device.connected { "success" } else { "warning" });
         status_label.set_hexpand(true);
         
@@ -227,6 +397,13 @@ impl DeviceListView {
         button_box.set_halign(gtk::Align::End);
         button_box.set_valign(gtk::Align::Center);
         
+        // Signal bars, kept up to date from advertisements by set_rssi()
+        let signal_icon = gtk::Image::new();
+        signal_icon.set_margin_end(4);
+        button_box.append(&signal_icon);
+        self.imp().signal_icons.borrow_mut().insert(device.address.clone(), signal_icon.clone());
+        Self::show_rssi(&signal_icon, device.rssi);
+        
         // Connect/Disconnect button
         let connection_button = if device.connected {
             Button::with_label("Disconnect")
@@ -270,50 +447,123 @@ impl DeviceListView {
             }
         }));
         
+        // Details button
+        let details_button = Button::from_icon_name("go-next-symbolic");
+        details_button.set_tooltip_text(Some("Device details"));
+        details_button.add_css_class("flat");
+        
+        let device_address_details = device.address.clone();
+        details_button.connect_clicked(glib::clone!(@weak self as device_list => move |_| {
+            let callback = device_list.imp().details_callback.borrow();
+            if let Some(ref cb) = *callback {
+                cb(device_address_details.clone());
+            }
+        }));
+        
         button_box.append(&connection_button);
         if device.paired {
             button_box.append(&forget_button);
+            self.add_send_files_support(&device_row, &button_box, &device.address);
         }
+        button_box.append(&details_button);
         
         device_row.append(&info_box);
         device_row.append(&button_box);
         
-        let list_box = imp.list_box.borrow();
         list_box.append(&device_row);
     }
 }
 
 mod imp {
     use super::*;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
     type CallbackFn = Box<dyn Fn(String)>;
-
+    type SendFilesCallbackFn = Box<dyn Fn(String, Vec<PathBuf>)>;
+    type ListSettingsCallbackFn = Box<dyn Fn(DeviceListSettings)>;
+    
     pub struct DeviceListView {
-        pub list_box: RefCell<ListBox>,
+        pub connected_group: adw::PreferencesGroup,
+        pub connected_list: ListBox,
+        pub paired_group: adw::PreferencesGroup,
+        pub paired_list: ListBox,
+        pub nearby_group: adw::PreferencesGroup,
+        pub nearby_list: ListBox,
+        pub nearby_expanded: ToggleButton,
+        /// Shown instead of the sections when none has devices
+        pub empty_label: Label,
+        pub discovering: Cell<bool>,
+        /// Signal bars of the listed devices by address
+        pub signal_icons: RefCell<HashMap<String, gtk::Image>>,
+        /// Everything from the last update, before filtering
+        pub devices: RefCell<Vec<BluetoothDevice>>,
+        pub list_settings: RefCell<DeviceListSettings>,
+        pub search_bar: SearchBar,
+        pub search_entry: SearchEntry,
+        pub paired_button: ToggleButton,
+        pub connected_button: ToggleButton,
+        pub nearby_button: ToggleButton,
+        pub unnamed_button: ToggleButton,
+        pub type_dropdown: DropDown,
+        pub sort_dropdown: DropDown,
+        pub updating: Cell<bool>,
+        pub list_settings_callback: RefCell<Option<ListSettingsCallbackFn>>,
         pub connect_callback: RefCell<Option<CallbackFn>>,
         pub disconnect_callback: RefCell<Option<CallbackFn>>,
         pub forget_callback: RefCell<Option<CallbackFn>>,
+        pub details_callback: RefCell<Option<CallbackFn>>,
+        pub send_files_callback: RefCell<Option<SendFilesCallbackFn>>,
+        pub file_chooser: RefCell<Option<FileChooserNative>>,
     }
-
+    
     impl Default for DeviceListView {
         fn default() -> Self {
             Self {
-                list_box: RefCell::new(ListBox::new()),
+                connected_group: adw::PreferencesGroup::new(),
+                connected_list: ListBox::new(),
+                paired_group: adw::PreferencesGroup::new(),
+                paired_list: ListBox::new(),
+                nearby_group: adw::PreferencesGroup::new(),
+                nearby_list: ListBox::new(),
+                nearby_expanded: ToggleButton::new(),
+                empty_label: Label::new(Some("No matching devices")),
+                discovering: Cell::new(false),
+                signal_icons: RefCell::new(HashMap::new()),
+                devices: RefCell::new(Vec::new()),
+                list_settings: RefCell::new(DeviceListSettings::default()),
+                search_bar: SearchBar::new(),
+                search_entry: SearchEntry::new(),
+                paired_button: ToggleButton::with_label("Paired"),
+                connected_button: ToggleButton::with_label("Connected"),
+                nearby_button: ToggleButton::with_label("Nearby"),
+                unnamed_button: ToggleButton::with_label("Hide Unnamed"),
+                type_dropdown: DropDown::from_strings(&[]),
+                sort_dropdown: DropDown::from_strings(&[]),
+                updating: Cell::new(false),
+                list_settings_callback: RefCell::new(None),
                 connect_callback: RefCell::new(None),
                 disconnect_callback: RefCell::new(None),
                 forget_callback: RefCell::new(None),
+                details_callback: RefCell::new(None),
+                send_files_callback: RefCell::new(None),
+                file_chooser: RefCell::new(None),
             }
         }
     }
-
+    
     #[glib::object_subclass]
     impl ObjectSubclass for DeviceListView {
         const NAME: &'static str = "DeviceListView";
         type Type = super::DeviceListView;
         type ParentType = GtkBox;
+        
+        fn class_init(klass: &mut Self::Class) {
+            klass.install_action("device-list.search", None, |device_list, _, _| {
+                device_list.imp().search_bar.set_search_mode(true);
+            });
+        }
     }
-
+    
     impl ObjectImpl for DeviceListView {
         fn constructed(&self) {
             self.parent_constructed();
@@ -335,14 +585,111 @@ mod imp {
             header.set_margin_end(16);
             obj.append(&header);
             
-            // Create list box with visible styling
-            let list_box = ListBox::new();
-            list_box.set_selection_mode(SelectionMode::Single);
-            list_box.add_css_class("navigation-sidebar");
-            list_box.set_hexpand(true);
-            list_box.set_vexpand(true);
+            // Search by name, alias or address, with Ctrl+F or by just typing
+            self.search_entry.set_placeholder_text(Some("Search by name or address"));
+            self.search_bar.set_child(Some(&self.search_entry));
+            self.search_bar.connect_entry(&self.search_entry);
+            self.search_bar.set_key_capture_widget(Some(&*obj));
+            obj.append(&self.search_bar);
+            
+            let shortcuts = gtk::ShortcutController::new();
+            shortcuts.set_scope(gtk::ShortcutScope::Global);
+            shortcuts.add_shortcut(gtk::Shortcut::new(
+                gtk::ShortcutTrigger::parse_string("<Control>f"),
+                gtk::ShortcutAction::parse_string("action(device-list.search)"),
+            ));
+            obj.add_controller(shortcuts);
+            
+            // Filter chips and sort order
+            let filter_box = GtkBox::new(Orientation::Horizontal, 6);
+            filter_box.set_margin_start(16);
+            filter_box.set_margin_end(16);
+            
+            let search_button = ToggleButton::new();
+            search_button.set_icon_name("system-search-symbolic");
+            search_button.set_tooltip_text(Some("Search (Ctrl+F)"));
+            search_button.bind_property("active", &self.search_bar, "search-mode-enabled")
+                .bidirectional()
+                .build();
+            filter_box.append(&search_button);
+            
+            for button in [&self.paired_button, &self.connected_button, &self.nearby_button, &self.unnamed_button] {
+                filter_box.append(button);
+                button.connect_toggled(glib::clone!(@weak obj => move |_| {
+                    obj.on_list_settings_changed();
+                }));
+            }
+            self.nearby_button.set_tooltip_text(Some("Only devices currently in range"));
+            
+            let types: Vec<&str> = std::iter::once("All Types").chain(BluetoothDevice::TYPES).collect();
+            self.type_dropdown.set_model(Some(&gtk::StringList::new(&types)));
+            self.type_dropdown.set_tooltip_text(Some("Device type"));
+            filter_box.append(&self.type_dropdown);
+            
+            let spacer = GtkBox::new(Orientation::Horizontal, 0);
+            spacer.set_hexpand(true);
+            filter_box.append(&spacer);
+            
+            let sorts: Vec<String> = DeviceSort::ALL.iter().map(|sort| sort.to_string()).collect();
+            let sorts: Vec<&str> = sorts.iter().map(String::as_str).collect();
+            self.sort_dropdown.set_model(Some(&gtk::StringList::new(&sorts)));
+            self.sort_dropdown.set_tooltip_text(Some("Sort by"));
+            filter_box.append(&Label::new(Some("Sort by")));
+            filter_box.append(&self.sort_dropdown);
+            obj.append(&filter_box);
             
-            // Create scrolled window to contain the list box
+            for dropdown in [&self.type_dropdown, &self.sort_dropdown] {
+                dropdown.connect_selected_notify(glib::clone!(@weak obj => move |_| {
+                    obj.on_list_settings_changed();
+                }));
+            }
+            self.search_entry.connect_search_changed(glib::clone!(@weak obj => move |_| {
+                obj.render_devices();
+            }));
+            
+            // Sections like GNOME Settings: connected, paired, and everything else nearby
+            let sections_box = GtkBox::new(Orientation::Vertical, 18);
+            sections_box.set_margin_top(8);
+            for (group, list_box, title) in [
+                (&self.connected_group, &self.connected_list, "Connected"),
+                (&self.paired_group, &self.paired_list, "My Devices"),
+                (&self.nearby_group, &self.nearby_list, "Nearby Devices"),
+            ] {
+                group.set_title(title);
+                group.set_visible(false);
+                list_box.set_selection_mode(SelectionMode::None);
+                list_box.add_css_class("boxed-list");
+                group.add(list_box);
+                sections_box.append(group);
+            }
+            
+            // Nearby devices are only listed while scanning and can be collapsed
+            self.nearby_group.set_description(Some("Devices found while scanning"));
+            let placeholder = Label::new(Some("Looking for devices…"));
+            placeholder.add_css_class("dim-label");
+            placeholder.set_margin_top(12);
+            placeholder.set_margin_bottom(12);
+            self.nearby_list.set_placeholder(Some(&placeholder));
+            
+            self.nearby_expanded.set_icon_name("pan-down-symbolic");
+            self.nearby_expanded.set_tooltip_text(Some("Show or hide nearby devices"));
+            self.nearby_expanded.set_valign(gtk::Align::Center);
+            self.nearby_expanded.add_css_class("flat");
+            self.nearby_expanded.set_active(true);
+            self.nearby_expanded.bind_property("active", &self.nearby_list, "visible")
+                .sync_create()
+                .build();
+            self.nearby_expanded.connect_toggled(|button| {
+                button.set_icon_name(if button.is_active() { "pan-down-symbolic" } else { "pan-end-symbolic" });
+            });
+            self.nearby_group.set_header_suffix(Some(&self.nearby_expanded));
+            
+            self.empty_label.add_css_class("dim-label");
+            self.empty_label.set_margin_top(24);
+            self.empty_label.set_margin_bottom(24);
+            sections_box.append(&self.empty_label);
+            
+            // Create scrolled window to contain the sections
             let scrolled_window = ScrolledWindow::new();
             scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
             scrolled_window.set_hexpand(true);
@@ -352,19 +699,14 @@ mod imp {
             scrolled_window.set_margin_bottom(16);
             scrolled_window.set_min_content_height(300);
             scrolled_window.set_max_content_height(600);
-            
-            // Add list box to scrolled window
-            scrolled_window.set_child(Some(&list_box));
+            scrolled_window.set_child(Some(&sections_box));
             
             obj.append(&scrolled_window);
             
-            // Store the list_box reference
-            *self.list_box.borrow_mut() = list_box;
-            
             log::info!("DeviceListView constructed with proper sizing");
         }
     }
-
+    
     impl WidgetImpl for DeviceListView {}
     impl BoxImpl for DeviceListView {}
 }
@@ -0,0 +1,517 @@
+use std::collections::{HashMap, HashSet};
+
+use adw::prelude::*;
+use gtk::{
+    glib, subclass::prelude::*, Box as GtkBox, Button, DropDown, Entry, Label, ListBox,
+    Orientation, SelectionMode, Switch, Widget,
+};
+
+use crate::bluetooth::gatt::{GattHandle, GattService, ValueFormat};
+use crate::bluetooth::uuids;
+
+/// Notifications kept per characteristic, older ones are dropped
+const LOG_LIMIT: usize = 200;
+
+glib::wrapper! {
+    pub struct GattView(ObjectSubclass<imp::GattView>)
+        @extends GtkBox, Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+/// What the inspector needs to know about a characteristic or descriptor.
+#[derive(Debug, Clone)]
+pub struct AttributeInfo {
+    title: String,
+    can_read: bool,
+    can_write: bool,
+    can_notify: bool,
+}
+
+impl GattView {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+    
+    /// Called when the user asks to enumerate the services again.
+    pub fn set_refresh_callback<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        let imp = self.imp();
+        *imp.refresh_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    pub fn set_read_callback<F>(&self, callback: F)
+    where
+        F: Fn(GattHandle) + 'static,
+    {
+        let imp = self.imp();
+        *imp.read_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    pub fn set_write_callback<F>(&self, callback: F)
+    where
+        F: Fn(GattHandle, Vec<u8>) + 'static,
+    {
+        let imp = self.imp();
+        *imp.write_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    /// Called with `true` to subscribe to notifications and `false` to unsubscribe.
+    pub fn set_notify_callback<F>(&self, callback: F)
+    where
+        F: Fn(GattHandle, bool) + 'static,
+    {
+        let imp = self.imp();
+        *imp.notify_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    /// Forget everything shown, e.g. when another device is selected.
+    pub fn clear(&self) {
+        let imp = self.imp();
+        
+        for row in imp.service_rows.borrow_mut().drain(..) {
+            imp.services_group.remove(&row);
+        }
+        imp.attributes.borrow_mut().clear();
+        imp.values.borrow_mut().clear();
+        imp.logs.borrow_mut().clear();
+        imp.subscribed.borrow_mut().clear();
+        self.select(None);
+        self.set_status(Some("Connect the device to browse its services"));
+    }
+    
+    /// Show a message in place of the services, or hide it with `None`.
+    pub fn set_status(&self, status: Option<&str>) {
+        let imp = self.imp();
+        match status {
+            Some(status) => {
+                imp.status_row.set_title(status);
+                imp.status_row.set_visible(true);
+            }
+            None => imp.status_row.set_visible(false),
+        }
+    }
+    
+    pub fn set_services(&self, services: &[GattService]) {
+        let imp = self.imp();
+        
+        for row in imp.service_rows.borrow_mut().drain(..) {
+            imp.services_group.remove(&row);
+        }
+        imp.attributes.borrow_mut().clear();
+        self.set_status(if services.is_empty() { Some("The device has no GATT services") } else { None });
+        
+        for service in services {
+            let service_row = adw::ExpanderRow::builder()
+                .title(uuids::label(&service.uuid))
+                .subtitle(if service.primary { "Primary service" } else { "Secondary service" })
+                .build();
+            
+            for characteristic in &service.characteristics {
+                let info = AttributeInfo {
+                    title: uuids::label(&characteristic.uuid),
+                    can_read: characteristic.can_read(),
+                    can_write: characteristic.can_write(),
+                    can_notify: characteristic.can_notify(),
+                };
+                let row = self.attribute_row(characteristic.handle, &info.title, &characteristic.flags.join(", "));
+                service_row.add_row(&row);
+                imp.attributes.borrow_mut().insert(characteristic.handle, info);
+                
+                for descriptor in &characteristic.descriptors {
+                    let info = AttributeInfo {
+                        title: uuids::label(&descriptor.uuid),
+                        can_read: true,
+                        can_write: true,
+                        can_notify: false,
+                    };
+                    let row = self.attribute_row(descriptor.handle, &info.title, &format!("Descriptor of {}", uuids::label(&characteristic.uuid)));
+                    row.set_margin_start(24);
+                    service_row.add_row(&row);
+                    imp.attributes.borrow_mut().insert(descriptor.handle, info);
+                }
+            }
+            
+            imp.services_group.add(&service_row);
+            imp.service_rows.borrow_mut().push(service_row);
+        }
+        
+        // Keep the selection if the attribute still exists
+        let selected = *imp.selected.borrow();
+        self.select(selected.filter(|handle| imp.attributes.borrow().contains_key(handle)));
+    }
+    
+    fn attribute_row(&self, handle: GattHandle, title: &str, subtitle: &str) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .subtitle(subtitle)
+            .activatable(true)
+            .build();
+        row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+        row.connect_activated(glib::clone!(@weak self as gatt_view => move |_| {
+            gatt_view.select(Some(handle));
+        }));
+        row
+    }
+    
+    /// Show the value of an attribute after it was read.
+    pub fn set_value(&self, handle: GattHandle, value: &[u8]) {
+        let imp = self.imp();
+        imp.values.borrow_mut().insert(handle, value.to_vec());
+        if *imp.selected.borrow() == Some(handle) {
+            self.render_value();
+        }
+    }
+    
+    pub fn add_notification(&self, handle: GattHandle, value: &[u8]) {
+        let imp = self.imp();
+        let time = glib::DateTime::now_local()
+            .and_then(|now| now.format("%H:%M:%S"))
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        
+        {
+            let mut logs = imp.logs.borrow_mut();
+            let log = logs.entry(handle).or_default();
+            log.push((time, value.to_vec()));
+            if log.len() > LOG_LIMIT {
+                log.remove(0);
+            }
+        }
+        
+        self.set_value(handle, value);
+        if *imp.selected.borrow() == Some(handle) {
+            self.render_log();
+        }
+    }
+    
+    /// Reflect the subscription state, e.g. after subscribing failed.
+    pub fn set_subscribed(&self, handle: GattHandle, subscribed: bool) {
+        let imp = self.imp();
+        if subscribed {
+            imp.subscribed.borrow_mut().insert(handle);
+        } else {
+            imp.subscribed.borrow_mut().remove(&handle);
+        }
+        
+        if *imp.selected.borrow() == Some(handle) {
+            imp.updating.set(true);
+            imp.notify_switch.set_active(subscribed);
+            imp.updating.set(false);
+        }
+    }
+    
+    fn select(&self, handle: Option<GattHandle>) {
+        let imp = self.imp();
+        imp.selected.replace(handle);
+        
+        let info = handle.and_then(|handle| imp.attributes.borrow().get(&handle).cloned());
+        let (handle, info) = match (handle, info) {
+            (Some(handle), Some(info)) => (handle, info),
+            _ => {
+                imp.inspector_group.set_visible(false);
+                imp.log_group.set_visible(false);
+                return;
+            }
+        };
+        
+        imp.inspector_group.set_title(&info.title);
+        imp.read_button.set_sensitive(info.can_read);
+        imp.write_row.set_sensitive(info.can_write);
+        imp.notify_row.set_visible(info.can_notify);
+        
+        imp.updating.set(true);
+        imp.notify_switch.set_active(imp.subscribed.borrow().contains(&handle));
+        imp.updating.set(false);
+        
+        imp.inspector_group.set_visible(true);
+        imp.log_group.set_visible(info.can_notify);
+        self.render_value();
+        self.render_log();
+    }
+    
+    fn format(&self) -> ValueFormat {
+        ValueFormat::ALL.get(self.imp().format_dropdown.selected() as usize).copied().unwrap_or(ValueFormat::Hex)
+    }
+    
+    fn render_value(&self) {
+        let imp = self.imp();
+        let text = imp.selected.borrow()
+            .and_then(|handle| imp.values.borrow().get(&handle).map(|value| self.format().format(value)))
+            .unwrap_or_else(|| "Not read yet".to_string());
+        imp.value_label.set_text(&text);
+    }
+    
+    fn render_log(&self) {
+        let imp = self.imp();
+        while let Some(child) = imp.log_list.first_child() {
+            imp.log_list.remove(&child);
+        }
+        
+        let handle = match *imp.selected.borrow() {
+            Some(handle) => handle,
+            None => return,
+        };
+        
+        let format = self.format();
+        if let Some(log) = imp.logs.borrow().get(&handle) {
+            // Newest first
+            for (time, value) in log.iter().rev() {
+                let label = Label::new(Some(&format!("{}  {}", time, format.format(value))));
+                label.set_halign(gtk::Align::Start);
+                label.set_selectable(true);
+                label.add_css_class("monospace");
+                label.set_margin_top(4);
+                label.set_margin_bottom(4);
+                label.set_margin_start(12);
+                label.set_margin_end(12);
+                imp.log_list.append(&label);
+            }
+        }
+    }
+    
+    fn on_refresh(&self) {
+        let callback = self.imp().refresh_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb();
+        }
+    }
+    
+    fn on_read(&self) {
+        let imp = self.imp();
+        let handle = match *imp.selected.borrow() {
+            Some(handle) => handle,
+            None => return,
+        };
+        
+        let callback = imp.read_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(handle);
+        }
+    }
+    
+    fn on_write(&self) {
+        let imp = self.imp();
+        let handle = match *imp.selected.borrow() {
+            Some(handle) => handle,
+            None => return,
+        };
+        
+        let value = match self.format().parse(&imp.write_entry.text()) {
+            Ok(value) => value,
+            Err(e) => {
+                log::warn!("Invalid value to write: {}", e);
+                imp.write_entry.add_css_class("error");
+                imp.write_entry.set_tooltip_text(Some(&e.to_string()));
+                return;
+            }
+        };
+        imp.write_entry.remove_css_class("error");
+        imp.write_entry.set_tooltip_text(None);
+        
+        let callback = imp.write_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(handle, value);
+        }
+    }
+    
+    fn on_notify_toggled(&self) {
+        let imp = self.imp();
+        if imp.updating.get() {
+            return;
+        }
+        
+        let handle = match *imp.selected.borrow() {
+            Some(handle) => handle,
+            None => return,
+        };
+        let subscribe = imp.notify_switch.is_active();
+        if subscribe {
+            imp.subscribed.borrow_mut().insert(handle);
+        } else {
+            imp.subscribed.borrow_mut().remove(&handle);
+        }
+        
+        let callback = imp.notify_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(handle, subscribe);
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    type RefreshCallbackFn = Box<dyn Fn()>;
+    type ReadCallbackFn = Box<dyn Fn(GattHandle)>;
+    type WriteCallbackFn = Box<dyn Fn(GattHandle, Vec<u8>)>;
+    type NotifyCallbackFn = Box<dyn Fn(GattHandle, bool)>;
+    /// Time received and value of each notification
+    type NotificationLog = Vec<(String, Vec<u8>)>;
+    
+    pub struct GattView {
+        pub services_group: adw::PreferencesGroup,
+        pub status_row: adw::ActionRow,
+        pub service_rows: RefCell<Vec<adw::ExpanderRow>>,
+        pub inspector_group: adw::PreferencesGroup,
+        pub value_label: Label,
+        pub read_button: Button,
+        pub format_dropdown: DropDown,
+        pub write_row: adw::ActionRow,
+        pub write_entry: Entry,
+        pub notify_row: adw::ActionRow,
+        pub notify_switch: Switch,
+        pub log_group: adw::PreferencesGroup,
+        pub log_list: ListBox,
+        pub attributes: RefCell<HashMap<GattHandle, AttributeInfo>>,
+        pub values: RefCell<HashMap<GattHandle, Vec<u8>>>,
+        pub logs: RefCell<HashMap<GattHandle, NotificationLog>>,
+        pub subscribed: RefCell<HashSet<GattHandle>>,
+        pub selected: RefCell<Option<GattHandle>>,
+        pub updating: Cell<bool>,
+        pub refresh_callback: RefCell<Option<RefreshCallbackFn>>,
+        pub read_callback: RefCell<Option<ReadCallbackFn>>,
+        pub write_callback: RefCell<Option<WriteCallbackFn>>,
+        pub notify_callback: RefCell<Option<NotifyCallbackFn>>,
+    }
+    
+    impl Default for GattView {
+        fn default() -> Self {
+            let formats: Vec<String> = ValueFormat::ALL.iter().map(|format| format.to_string()).collect();
+            let formats: Vec<&str> = formats.iter().map(String::as_str).collect();
+            
+            Self {
+                services_group: adw::PreferencesGroup::new(),
+                status_row: adw::ActionRow::new(),
+                service_rows: RefCell::new(Vec::new()),
+                inspector_group: adw::PreferencesGroup::new(),
+                value_label: Label::new(None),
+                read_button: Button::with_label("Read"),
+                format_dropdown: DropDown::from_strings(&formats),
+                write_row: adw::ActionRow::builder().title("Write").build(),
+                write_entry: Entry::new(),
+                notify_row: adw::ActionRow::builder()
+                    .title("Notifications")
+                    .subtitle("Log every value the device sends")
+                    .build(),
+                notify_switch: Switch::new(),
+                log_group: adw::PreferencesGroup::new(),
+                log_list: ListBox::new(),
+                attributes: RefCell::new(HashMap::new()),
+                values: RefCell::new(HashMap::new()),
+                logs: RefCell::new(HashMap::new()),
+                subscribed: RefCell::new(HashSet::new()),
+                selected: RefCell::new(None),
+                updating: Cell::new(false),
+                refresh_callback: RefCell::new(None),
+                read_callback: RefCell::new(None),
+                write_callback: RefCell::new(None),
+                notify_callback: RefCell::new(None),
+            }
+        }
+    }
+    
+    #[glib::object_subclass]
+    impl ObjectSubclass for GattView {
+        const NAME: &'static str = "GattView";
+        type Type = super::GattView;
+        type ParentType = GtkBox;
+    }
+    
+    impl ObjectImpl for GattView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            
+            let obj = self.obj();
+            obj.set_orientation(Orientation::Vertical);
+            obj.set_spacing(24);
+            obj.set_margin_bottom(16);
+            obj.set_margin_start(16);
+            obj.set_margin_end(16);
+            
+            // Services with their characteristics and descriptors
+            self.services_group.set_title("GATT Services");
+            let refresh_button = Button::from_icon_name("view-refresh-symbolic");
+            refresh_button.set_tooltip_text(Some("Reload services"));
+            refresh_button.add_css_class("flat");
+            self.services_group.set_header_suffix(Some(&refresh_button));
+            self.status_row.set_title("Connect the device to browse its services");
+            self.services_group.add(&self.status_row);
+            obj.append(&self.services_group);
+            
+            // Inspector for the selected characteristic or descriptor
+            self.inspector_group.set_visible(false);
+            
+            let value_row = adw::ActionRow::builder()
+                .title("Value")
+                .build();
+            self.value_label.set_selectable(true);
+            self.value_label.set_wrap(true);
+            self.value_label.set_xalign(1.0);
+            self.value_label.add_css_class("monospace");
+            self.read_button.set_valign(gtk::Align::Center);
+            value_row.add_suffix(&self.value_label);
+            value_row.add_suffix(&self.read_button);
+            self.inspector_group.add(&value_row);
+            
+            let format_row = adw::ActionRow::builder()
+                .title("Format")
+                .build();
+            self.format_dropdown.set_valign(gtk::Align::Center);
+            format_row.add_suffix(&self.format_dropdown);
+            self.inspector_group.add(&format_row);
+            
+            let write_button = Button::with_label("Write");
+            write_button.set_valign(gtk::Align::Center);
+            self.write_entry.set_valign(gtk::Align::Center);
+            self.write_entry.set_placeholder_text(Some("Value to write"));
+            self.write_row.add_suffix(&self.write_entry);
+            self.write_row.add_suffix(&write_button);
+            self.inspector_group.add(&self.write_row);
+            
+            self.notify_switch.set_valign(gtk::Align::Center);
+            self.notify_row.add_suffix(&self.notify_switch);
+            self.notify_row.set_activatable_widget(Some(&self.notify_switch));
+            self.inspector_group.add(&self.notify_row);
+            obj.append(&self.inspector_group);
+            
+            // Live log of notified values
+            self.log_group.set_title("Notification Log");
+            self.log_group.set_visible(false);
+            self.log_list.set_selection_mode(SelectionMode::None);
+            self.log_list.add_css_class("boxed-list");
+            self.log_group.add(&self.log_list);
+            obj.append(&self.log_group);
+            
+            refresh_button.connect_clicked(glib::clone!(@weak obj => move |_| {
+                obj.on_refresh();
+            }));
+            
+            self.read_button.connect_clicked(glib::clone!(@weak obj => move |_| {
+                obj.on_read();
+            }));
+            
+            write_button.connect_clicked(glib::clone!(@weak obj => move |_| {
+                obj.on_write();
+            }));
+            
+            self.write_entry.connect_activate(glib::clone!(@weak obj => move |_| {
+                obj.on_write();
+            }));
+            
+            self.notify_switch.connect_active_notify(glib::clone!(@weak obj => move |_| {
+                obj.on_notify_toggled();
+            }));
+            
+            self.format_dropdown.connect_selected_notify(glib::clone!(@weak obj => move |_| {
+                obj.render_value();
+                obj.render_log();
+            }));
+        }
+    }
+    
+    impl WidgetImpl for GattView {}
+    impl BoxImpl for GattView {}
+}
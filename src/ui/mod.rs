@@ -1,2 +1,8 @@
 pub mod window;
 pub mod device_list;
+pub mod device_details;
+pub mod advertisement_view;
+pub mod signal_chart;
+pub mod gatt_view;
+pub mod preferences;
+pub mod transfer_view;
\ No newline at end of file
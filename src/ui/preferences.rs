@@ -0,0 +1,337 @@
+use adw::prelude::*;
+use gtk::{
+    glib, subclass::prelude::*, Box as GtkBox, Button, DropDown, FileChooserAction,
+    FileChooserNative, Label, Orientation, ResponseType, SpinButton, Switch, Widget,
+};
+
+use crate::bluetooth::power::PowerPolicy;
+use crate::config::Preferences;
+
+glib::wrapper! {
+    pub struct PreferencesView(ObjectSubclass<imp::PreferencesView>)
+        @extends GtkBox, Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl PreferencesView {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+    
+    /// Called with the updated preferences whenever the user changes one.
+    pub fn set_preferences_callback<F>(&self, callback: F)
+    where
+        F: Fn(Preferences) + 'static,
+    {
+        let imp = self.imp();
+        *imp.preferences_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    pub fn set_preferences(&self, preferences: &Preferences) {
+        let imp = self.imp();
+        
+        imp.updating.set(true);
+        let policy_position = PowerPolicy::ALL.iter().position(|policy| *policy == preferences.power_policy).unwrap_or(0);
+        imp.power_policy_dropdown.set_selected(policy_position as u32);
+        imp.download_dir_row.set_subtitle(&preferences.download_dir().display().to_string());
+        imp.auto_accept_switch.set_active(preferences.auto_accept_trusted);
+        imp.notify_switch.set_active(preferences.notifications.enabled);
+        imp.notify_connected_switch.set_active(preferences.notifications.connected);
+        imp.notify_disconnected_switch.set_active(preferences.notifications.disconnected);
+        imp.notify_paired_switch.set_active(preferences.notifications.paired);
+        imp.notify_failed_switch.set_active(preferences.notifications.failed);
+        imp.rssi_threshold_spin.set_value(preferences.proximity.rssi_threshold as f64);
+        imp.away_delay_spin.set_value(preferences.proximity.away_seconds as f64);
+        imp.notify_on_return_switch.set_active(preferences.proximity.notify_on_return);
+        imp.updating.set(false);
+        
+        imp.preferences.replace(preferences.clone());
+    }
+    
+    fn choose_download_dir(&self) {
+        let parent = self.root().and_downcast::<gtk::Window>();
+        let file_chooser = FileChooserNative::new(
+            Some("Download Folder"),
+            parent.as_ref(),
+            FileChooserAction::SelectFolder,
+            Some("Select"),
+            Some("Cancel"),
+        );
+        
+        file_chooser.connect_response(glib::clone!(@weak self as preferences_view => move |file_chooser, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = file_chooser.file().and_then(|file| file.path()) {
+                    let imp = preferences_view.imp();
+                    imp.download_dir_row.set_subtitle(&path.display().to_string());
+                    imp.preferences.borrow_mut().download_dir = Some(path);
+                    preferences_view.on_preferences_changed();
+                }
+            }
+            preferences_view.imp().file_chooser.replace(None);
+        }));
+        
+        file_chooser.show();
+        // Native dialogs are not kept alive by GTK while they are shown
+        self.imp().file_chooser.replace(Some(file_chooser));
+    }
+    
+    fn on_preferences_changed(&self) {
+        let imp = self.imp();
+        if imp.updating.get() {
+            return;
+        }
+        
+        let preferences = {
+            let mut preferences = imp.preferences.borrow_mut();
+            preferences.power_policy = PowerPolicy::ALL.get(imp.power_policy_dropdown.selected() as usize).copied().unwrap_or_default();
+            preferences.auto_accept_trusted = imp.auto_accept_switch.is_active();
+            preferences.notifications.enabled = imp.notify_switch.is_active();
+            preferences.notifications.connected = imp.notify_connected_switch.is_active();
+            preferences.notifications.disconnected = imp.notify_disconnected_switch.is_active();
+            preferences.notifications.paired = imp.notify_paired_switch.is_active();
+            preferences.notifications.failed = imp.notify_failed_switch.is_active();
+            preferences.proximity.rssi_threshold = imp.rssi_threshold_spin.value_as_int() as i16;
+            preferences.proximity.away_seconds = imp.away_delay_spin.value_as_int() as u32;
+            preferences.proximity.notify_on_return = imp.notify_on_return_switch.is_active();
+            preferences.clone()
+        };
+        
+        let callback = imp.preferences_callback.borrow();
+        if let Some(ref cb) = *callback {
+            cb(preferences);
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    type PreferencesCallbackFn = Box<dyn Fn(Preferences)>;
+    
+    pub struct PreferencesView {
+        pub power_policy_dropdown: DropDown,
+        pub download_dir_row: adw::ActionRow,
+        pub auto_accept_switch: Switch,
+        pub notify_switch: Switch,
+        pub notify_connected_switch: Switch,
+        pub notify_disconnected_switch: Switch,
+        pub notify_paired_switch: Switch,
+        pub notify_failed_switch: Switch,
+        pub rssi_threshold_spin: SpinButton,
+        pub away_delay_spin: SpinButton,
+        pub notify_on_return_switch: Switch,
+        pub file_chooser: RefCell<Option<FileChooserNative>>,
+        pub preferences: RefCell<Preferences>,
+        pub updating: Cell<bool>,
+        pub preferences_callback: RefCell<Option<PreferencesCallbackFn>>,
+    }
+    
+    impl Default for PreferencesView {
+        fn default() -> Self {
+            Self {
+                power_policy_dropdown: DropDown::from_strings(&[]),
+                download_dir_row: adw::ActionRow::builder()
+                    .title("Download Folder")
+                    .build(),
+                auto_accept_switch: Switch::new(),
+                notify_switch: Switch::new(),
+                notify_connected_switch: Switch::new(),
+                notify_disconnected_switch: Switch::new(),
+                notify_paired_switch: Switch::new(),
+                notify_failed_switch: Switch::new(),
+                rssi_threshold_spin: SpinButton::with_range(-100.0, -30.0, 1.0),
+                away_delay_spin: SpinButton::with_range(5.0, 600.0, 5.0),
+                notify_on_return_switch: Switch::new(),
+                file_chooser: RefCell::new(None),
+                preferences: RefCell::new(Preferences::default()),
+                updating: Cell::new(false),
+                preferences_callback: RefCell::new(None),
+            }
+        }
+    }
+    
+    #[glib::object_subclass]
+    impl ObjectSubclass for PreferencesView {
+        const NAME: &'static str = "PreferencesView";
+        type Type = super::PreferencesView;
+        type ParentType = GtkBox;
+    }
+    
+    impl ObjectImpl for PreferencesView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            
+            let obj = self.obj();
+            obj.set_orientation(Orientation::Vertical);
+            obj.set_spacing(24);
+            obj.set_margin_top(16);
+            obj.set_margin_bottom(16);
+            obj.set_margin_start(16);
+            obj.set_margin_end(16);
+            
+            let title_label = Label::new(None);
+            title_label.set_markup("<big><b>Preferences</b></big>");
+            title_label.set_halign(gtk::Align::Start);
+            obj.append(&title_label);
+            
+            let adapter_group = adw::PreferencesGroup::new();
+            adapter_group.set_title("Adapter");
+            
+            let policies: Vec<String> = PowerPolicy::ALL.iter().map(|policy| policy.to_string()).collect();
+            let policies: Vec<&str> = policies.iter().map(String::as_str).collect();
+            self.power_policy_dropdown.set_model(Some(&gtk::StringList::new(&policies)));
+            self.power_policy_dropdown.set_valign(gtk::Align::Center);
+            let power_policy_row = adw::ActionRow::builder()
+                .title("Power on Startup")
+                .subtitle("Also applies when an adapter is plugged in or unblocked")
+                .build();
+            power_policy_row.add_suffix(&self.power_policy_dropdown);
+            adapter_group.add(&power_policy_row);
+            obj.append(&adapter_group);
+            
+            self.power_policy_dropdown.connect_selected_notify(glib::clone!(@weak obj => move |_| {
+                obj.on_preferences_changed();
+            }));
+            
+            // Files pushed to this computer over OBEX
+            let receive_group = adw::PreferencesGroup::new();
+            receive_group.set_title("Receiving Files");
+            
+            let choose_button = Button::from_icon_name("folder-open-symbolic");
+            choose_button.set_tooltip_text(Some("Choose folder…"));
+            choose_button.set_valign(gtk::Align::Center);
+            choose_button.add_css_class("flat");
+            self.download_dir_row.add_suffix(&choose_button);
+            self.download_dir_row.set_activatable_widget(Some(&choose_button));
+            receive_group.add(&self.download_dir_row);
+            
+            let auto_accept_row = adw::ActionRow::builder()
+                .title("Accept From Trusted Devices")
+                .subtitle("Receive files from trusted devices without asking")
+                .build();
+            self.auto_accept_switch.set_valign(gtk::Align::Center);
+            auto_accept_row.add_suffix(&self.auto_accept_switch);
+            auto_accept_row.set_activatable_widget(Some(&self.auto_accept_switch));
+            receive_group.add(&auto_accept_row);
+            obj.append(&receive_group);
+            
+            choose_button.connect_clicked(glib::clone!(@weak obj => move |_| {
+                obj.choose_download_dir();
+            }));
+            
+            // Desktop notifications for device events
+            let notify_group = adw::PreferencesGroup::new();
+            notify_group.set_title("Notifications");
+            
+            let notify_row = adw::ActionRow::builder()
+                .title("Show Notifications")
+                .subtitle("Notify about device events while RustBlue is running")
+                .build();
+            self.notify_switch.set_valign(gtk::Align::Center);
+            notify_row.add_suffix(&self.notify_switch);
+            notify_row.set_activatable_widget(Some(&self.notify_switch));
+            notify_group.add(&notify_row);
+            obj.append(&notify_group);
+            
+            let events_group = adw::PreferencesGroup::new();
+            events_group.set_title("Notify When");
+            let events = [
+                ("A Device Connects", &self.notify_connected_switch),
+                ("A Device Disconnects", &self.notify_disconnected_switch),
+                ("A Device Is Paired", &self.notify_paired_switch),
+                ("Connecting, Pairing or Reconnecting Fails", &self.notify_failed_switch),
+            ];
+            for (title, switch) in events {
+                let row = adw::ActionRow::builder()
+                    .title(title)
+                    .build();
+                switch.set_valign(gtk::Align::Center);
+                row.add_suffix(switch);
+                row.set_activatable_widget(Some(switch));
+                events_group.add(&row);
+            }
+            self.notify_switch.bind_property("active", &events_group, "sensitive")
+                .sync_create()
+                .build();
+            obj.append(&events_group);
+            
+            // Thresholds of the devices marked "Lock When Away" on their details page
+            let proximity_group = adw::PreferencesGroup::new();
+            proximity_group.set_title("Proximity Lock");
+            proximity_group.set_description(Some("Lock the session when the devices marked \"Lock When Away\" leave"));
+            
+            let rssi_threshold_row = adw::ActionRow::builder()
+                .title("Signal Threshold")
+                .subtitle("Weakest signal in dBm at which a device counts as near")
+                .build();
+            self.rssi_threshold_spin.set_valign(gtk::Align::Center);
+            rssi_threshold_row.add_suffix(&self.rssi_threshold_spin);
+            proximity_group.add(&rssi_threshold_row);
+            
+            let away_delay_row = adw::ActionRow::builder()
+                .title("Delay")
+                .subtitle("Seconds the devices must be away before locking")
+                .build();
+            self.away_delay_spin.set_valign(gtk::Align::Center);
+            away_delay_row.add_suffix(&self.away_delay_spin);
+            proximity_group.add(&away_delay_row);
+            
+            let notify_on_return_row = adw::ActionRow::builder()
+                .title("Notify on Return")
+                .subtitle("Show a notification when a device comes back, the session stays locked")
+                .build();
+            self.notify_on_return_switch.set_valign(gtk::Align::Center);
+            notify_on_return_row.add_suffix(&self.notify_on_return_switch);
+            notify_on_return_row.set_activatable_widget(Some(&self.notify_on_return_switch));
+            proximity_group.add(&notify_on_return_row);
+            obj.append(&proximity_group);
+            
+            // Handled by the window, see RustBlueWindow::install_app_actions
+            let backup_group = adw::PreferencesGroup::new();
+            backup_group.set_title("Backup");
+            backup_group.set_description(Some("Aliases, trust and the settings of each device, to carry them over to a new installation"));
+            let backups = [
+                ("Export Device Settings", "document-save-symbolic", "app.export-settings"),
+                ("Import Device Settings", "document-open-symbolic", "app.import-settings"),
+                ("Import Pairing Keys From Windows", "document-open-symbolic", "app.import-windows-keys"),
+            ];
+            for (title, icon_name, action_name) in backups {
+                let button = Button::from_icon_name(icon_name);
+                button.set_valign(gtk::Align::Center);
+                button.add_css_class("flat");
+                button.set_action_name(Some(action_name));
+                let row = adw::ActionRow::builder()
+                    .title(title)
+                    .build();
+                row.add_suffix(&button);
+                row.set_activatable_widget(Some(&button));
+                backup_group.add(&row);
+            }
+            obj.append(&backup_group);
+            
+            for spin_button in [&self.rssi_threshold_spin, &self.away_delay_spin] {
+                spin_button.connect_value_changed(glib::clone!(@weak obj => move |_| {
+                    obj.on_preferences_changed();
+                }));
+            }
+            
+            for switch in [
+                &self.auto_accept_switch,
+                &self.notify_switch,
+                &self.notify_connected_switch,
+                &self.notify_disconnected_switch,
+                &self.notify_paired_switch,
+                &self.notify_failed_switch,
+                &self.notify_on_return_switch,
+            ] {
+                switch.connect_active_notify(glib::clone!(@weak obj => move |_| {
+                    obj.on_preferences_changed();
+                }));
+            }
+        }
+    }
+    
+    impl WidgetImpl for PreferencesView {}
+    impl BoxImpl for PreferencesView {}
+}
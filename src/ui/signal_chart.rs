@@ -0,0 +1,185 @@
+use std::time::SystemTime;
+
+use adw::prelude::*;
+use gtk::{cairo, glib, subclass::prelude::*, Box as GtkBox, DrawingArea, Label, Orientation, Widget};
+
+use crate::bluetooth::history::{SignalHistory, HISTORY_WINDOW};
+
+/// Range of the RSSI axis in dBm
+const RSSI_MIN: f64 = -100.0;
+const RSSI_MAX: f64 = -30.0;
+const RSSI_GRID: [f64; 3] = [-40.0, -60.0, -80.0];
+/// Adwaita blue and green
+const RSSI_COLOR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+const BATTERY_COLOR: (f64, f64, f64) = (0.2, 0.82, 0.48);
+
+glib::wrapper! {
+    pub struct SignalChart(ObjectSubclass<imp::SignalChart>)
+        @extends GtkBox, Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+/// Horizontal position of a sample, the right edge being now.
+fn x_position(time: SystemTime, now: SystemTime, width: f64) -> f64 {
+    let age = now.duration_since(time).unwrap_or_default().as_secs_f64();
+    width * (1.0 - age / HISTORY_WINDOW.as_secs_f64()).clamp(0.0, 1.0)
+}
+
+fn rssi_position(rssi: f64, height: f64) -> f64 {
+    height * (1.0 - (rssi.clamp(RSSI_MIN, RSSI_MAX) - RSSI_MIN) / (RSSI_MAX - RSSI_MIN))
+}
+
+fn draw_series(cr: &cairo::Context, points: &[(f64, f64)], color: (f64, f64, f64)) {
+    let Some(((x, y), rest)) = points.split_first() else {
+        return;
+    };
+    
+    cr.set_source_rgb(color.0, color.1, color.2);
+    cr.set_line_width(2.0);
+    cr.move_to(*x, *y);
+    for (x, y) in rest {
+        cr.line_to(*x, *y);
+    }
+    if rest.is_empty() {
+        // A single reading, draw it as a dot
+        cr.arc(*x, *y, 2.0, 0.0, std::f64::consts::TAU);
+    }
+    let _ = cr.stroke();
+}
+
+impl SignalChart {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+    
+    /// Show the readings of a device, `None` hides the chart.
+    pub fn set_history(&self, history: Option<&SignalHistory>) {
+        let imp = self.imp();
+        
+        let history = match history {
+            Some(history) => history,
+            None => {
+                self.set_visible(false);
+                return;
+            }
+        };
+        self.set_visible(true);
+        
+        let mut legend = Vec::new();
+        if let Some(rssi) = history.rssi.back() {
+            legend.push(format!("<span foreground=\"#3584e4\">●</span> RSSI {} dBm", rssi.value));
+        }
+        if let Some(battery) = history.battery.back() {
+            legend.push(format!("<span foreground=\"#33d17a\">●</span> Battery {}%", battery.value));
+        }
+        imp.legend_label.set_markup(&legend.join("    "));
+        
+        imp.history.replace(history.clone());
+        imp.drawing_area.queue_draw();
+    }
+    
+    fn draw(&self, cr: &cairo::Context, width: i32, height: i32) {
+        let history = self.imp().history.borrow();
+        let (width, height) = (width as f64, height as f64);
+        let now = SystemTime::now();
+        
+        // Grid lines with their RSSI labels, in the foreground color
+        #[allow(deprecated)]
+        let foreground = self.style_context().color();
+        cr.set_source_rgba(
+            foreground.red() as f64,
+            foreground.green() as f64,
+            foreground.blue() as f64,
+            0.3,
+        );
+        cr.set_line_width(1.0);
+        cr.set_font_size(10.0);
+        for rssi in RSSI_GRID {
+            let y = rssi_position(rssi, height);
+            cr.move_to(0.0, y);
+            cr.line_to(width, y);
+            let _ = cr.stroke();
+            cr.move_to(4.0, y - 3.0);
+            let _ = cr.show_text(&format!("{} dBm", rssi));
+        }
+        
+        let rssi: Vec<(f64, f64)> = history.rssi.iter()
+            .map(|sample| (x_position(sample.time, now, width), rssi_position(sample.value as f64, height)))
+            .collect();
+        draw_series(cr, &rssi, RSSI_COLOR);
+        
+        let battery: Vec<(f64, f64)> = history.battery.iter()
+            .map(|sample| (x_position(sample.time, now, width), height * (1.0 - sample.value as f64 / 100.0)))
+            .collect();
+        draw_series(cr, &battery, BATTERY_COLOR);
+    }
+}
+
+mod imp {
+    use super::*;
+    use std::cell::RefCell;
+
+    pub struct SignalChart {
+        pub drawing_area: DrawingArea,
+        pub legend_label: Label,
+        pub history: RefCell<SignalHistory>,
+    }
+    
+    impl Default for SignalChart {
+        fn default() -> Self {
+            Self {
+                drawing_area: DrawingArea::new(),
+                legend_label: Label::new(None),
+                history: RefCell::new(SignalHistory::default()),
+            }
+        }
+    }
+    
+    #[glib::object_subclass]
+    impl ObjectSubclass for SignalChart {
+        const NAME: &'static str = "SignalChart";
+        type Type = super::SignalChart;
+        type ParentType = GtkBox;
+    }
+    
+    impl ObjectImpl for SignalChart {
+        fn constructed(&self) {
+            self.parent_constructed();
+            
+            let obj = self.obj();
+            obj.set_orientation(Orientation::Vertical);
+            obj.set_margin_bottom(16);
+            obj.set_margin_start(16);
+            obj.set_margin_end(16);
+            // Only shown for devices with readings
+            obj.set_visible(false);
+            
+            let group = adw::PreferencesGroup::new();
+            group.set_title("Signal History");
+            group.set_description(Some("RSSI and battery level over the last 30 minutes"));
+            
+            let chart_box = GtkBox::new(Orientation::Vertical, 8);
+            chart_box.add_css_class("card");
+            self.drawing_area.set_content_height(160);
+            self.drawing_area.set_margin_top(12);
+            self.drawing_area.set_margin_start(12);
+            self.drawing_area.set_margin_end(12);
+            self.drawing_area.set_draw_func(glib::clone!(@weak obj => move |_, cr, width, height| {
+                obj.draw(cr, width, height);
+            }));
+            chart_box.append(&self.drawing_area);
+            
+            self.legend_label.set_halign(gtk::Align::Start);
+            self.legend_label.set_margin_start(12);
+            self.legend_label.set_margin_bottom(12);
+            self.legend_label.add_css_class("caption");
+            chart_box.append(&self.legend_label);
+            
+            group.add(&chart_box);
+            obj.append(&group);
+        }
+    }
+    
+    impl WidgetImpl for SignalChart {}
+    impl BoxImpl for SignalChart {}
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use gtk::{
+    glib, prelude::*, subclass::prelude::*, Box as GtkBox, Button, Label, ListBox, Orientation,
+    ProgressBar, SelectionMode, Widget,
+};
+
+use crate::obex::transfer::{TransferProgress, TransferStatus};
+
+glib::wrapper! {
+    pub struct TransferView(ObjectSubclass<imp::TransferView>)
+        @extends GtkBox, Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+pub struct TransferRow {
+    row: GtkBox,
+    progress_bar: ProgressBar,
+    detail_label: Label,
+    button: Button,
+    incoming: bool,
+    finished: bool,
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value as u64, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn waiting_text(incoming: bool) -> &'static str {
+    if incoming {
+        "Starting…"
+    } else {
+        "Waiting for the device to accept…"
+    }
+}
+
+impl TransferView {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+    
+    /// Called with the transfer id when the user cancels a running transfer.
+    pub fn set_cancel_callback<F>(&self, callback: F)
+    where
+        F: Fn(String) + 'static,
+    {
+        let imp = self.imp();
+        *imp.cancel_callback.borrow_mut() = Some(Box::new(callback));
+    }
+    
+    /// Show a transfer, `incoming` for files received from the device rather than sent to it.
+    pub fn add_transfer(&self, id: &str, file_name: &str, device_name: &str, incoming: bool) {
+        log::info!("Adding transfer {} ({} {} {})", id, file_name, if incoming { "from" } else { "to" }, device_name);
+        let imp = self.imp();
+        
+        let row = GtkBox::new(Orientation::Vertical, 4);
+        row.set_margin_top(6);
+        row.set_margin_bottom(6);
+        row.set_margin_start(12);
+        row.set_margin_end(12);
+        
+        let header = GtkBox::new(Orientation::Horizontal, 8);
+        let title_label = Label::new(None);
+        title_label.set_markup(&format!("<b>{}</b> {} {}",
+            glib::markup_escape_text(file_name), if incoming { "←" } else { "→" }, glib::markup_escape_text(device_name)));
+        title_label.set_halign(gtk::Align::Start);
+        title_label.set_hexpand(true);
+        title_label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+        
+        let button = Button::from_icon_name("process-stop-symbolic");
+        button.set_tooltip_text(Some("Cancel transfer"));
+        button.add_css_class("flat");
+        
+        let transfer_id = id.to_string();
+        button.connect_clicked(glib::clone!(@weak self as transfer_view => move |_| {
+            transfer_view.on_button_clicked(&transfer_id);
+        }));
+        
+        header.append(&title_label);
+        header.append(&button);
+        
+        let progress_bar = ProgressBar::new();
+        
+        let detail_label = Label::new(Some(waiting_text(incoming)));
+        detail_label.set_halign(gtk::Align::Start);
+        detail_label.add_css_class("dim-label");
+        detail_label.add_css_class("caption");
+        
+        row.append(&header);
+        row.append(&progress_bar);
+        row.append(&detail_label);
+        
+        imp.list_box.append(&row);
+        imp.rows.borrow_mut().insert(id.to_string(), TransferRow {
+            row,
+            progress_bar,
+            detail_label,
+            button,
+            incoming,
+            finished: false,
+        });
+        self.set_visible(true);
+    }
+    
+    pub fn update_transfer(&self, id: &str, progress: &TransferProgress) {
+        let imp = self.imp();
+        let mut rows = imp.rows.borrow_mut();
+        let transfer_row = match rows.get_mut(id) {
+            Some(transfer_row) => transfer_row,
+            None => return,
+        };
+        
+        let size_text = match progress.size {
+            Some(size) if size > 0 => {
+                transfer_row.progress_bar.set_fraction((progress.transferred as f64 / size as f64).min(1.0));
+                format!("{} of {}", format_bytes(progress.transferred as f64), format_bytes(size as f64))
+            }
+            _ => {
+                transfer_row.progress_bar.pulse();
+                format_bytes(progress.transferred as f64)
+            }
+        };
+        
+        let detail = match progress.status {
+            TransferStatus::Queued => waiting_text(transfer_row.incoming).to_string(),
+            TransferStatus::Active => match progress.rate {
+                Some(rate) => format!("{} ({}/s)", size_text, format_bytes(rate)),
+                None => size_text,
+            },
+            TransferStatus::Suspended => format!("{} (paused)", size_text),
+            TransferStatus::Complete => {
+                transfer_row.progress_bar.set_fraction(1.0);
+                format!("{} {}", if transfer_row.incoming { "Received" } else { "Sent" }, size_text)
+            }
+            TransferStatus::Error if transfer_row.incoming => "Failed or cancelled by the device".to_string(),
+            TransferStatus::Error => "Failed or rejected by the device".to_string(),
+        };
+        transfer_row.detail_label.set_text(&detail);
+        
+        if progress.status.is_finished() && !transfer_row.finished {
+            transfer_row.finished = true;
+            transfer_row.button.set_icon_name("window-close-symbolic");
+            transfer_row.button.set_tooltip_text(Some("Dismiss"));
+            if progress.status == TransferStatus::Error {
+                transfer_row.detail_label.add_css_class("error");
+            }
+        }
+    }
+    
+    fn on_button_clicked(&self, id: &str) {
+        let imp = self.imp();
+        
+        let finished = match imp.rows.borrow().get(id) {
+            Some(transfer_row) => transfer_row.finished,
+            None => return,
+        };
+        
+        if finished {
+            // Dismiss the finished transfer
+            if let Some(transfer_row) = imp.rows.borrow_mut().remove(id) {
+                imp.list_box.remove(&transfer_row.row);
+            }
+            if imp.rows.borrow().is_empty() {
+                self.set_visible(false);
+            }
+        } else {
+            let callback = imp.cancel_callback.borrow();
+            if let Some(ref cb) = *callback {
+                cb(id.to_string());
+            }
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+    use std::cell::RefCell;
+
+    type CallbackFn = Box<dyn Fn(String)>;
+    
+    pub struct TransferView {
+        pub list_box: ListBox,
+        pub rows: RefCell<HashMap<String, TransferRow>>,
+        pub cancel_callback: RefCell<Option<CallbackFn>>,
+    }
+    
+    impl Default for TransferView {
+        fn default() -> Self {
+            Self {
+                list_box: ListBox::new(),
+                rows: RefCell::new(HashMap::new()),
+                cancel_callback: RefCell::new(None),
+            }
+        }
+    }
+    
+    #[glib::object_subclass]
+    impl ObjectSubclass for TransferView {
+        const NAME: &'static str = "TransferView";
+        type Type = super::TransferView;
+        type ParentType = GtkBox;
+    }
+    
+    impl ObjectImpl for TransferView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            
+            let obj = self.obj();
+            obj.set_orientation(Orientation::Vertical);
+            obj.set_spacing(8);
+            obj.set_margin_start(12);
+            obj.set_margin_end(12);
+            obj.set_margin_bottom(12);
+            // Only shown while there are transfers
+            obj.set_visible(false);
+            
+            let header = Label::new(None);
+            header.set_markup("<b>File Transfers</b>");
+            header.set_halign(gtk::Align::Start);
+            header.set_margin_start(16);
+            obj.append(&header);
+            
+            self.list_box.set_selection_mode(SelectionMode::None);
+            self.list_box.add_css_class("boxed-list");
+            self.list_box.set_margin_start(16);
+            self.list_box.set_margin_end(16);
+            obj.append(&self.list_box);
+        }
+    }
+    
+    impl WidgetImpl for TransferView {}
+    impl BoxImpl for TransferView {}
+}
@@ -1,10 +1,40 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use adw::prelude::*;
 use gtk::{
-    glib, prelude::*, subclass::prelude::*, Application, ApplicationWindow, Box as GtkBox,
-    Button, HeaderBar, Label, Orientation, Switch,
+    glib, subclass::prelude::*, Application, ApplicationWindow, Box as GtkBox,
+    Button, CheckButton, FileChooserAction, FileChooserNative, HeaderBar, Label, Orientation, ResponseType,
+    ScrolledWindow, Stack, Switch,
 };
 
+use dbus::arg::PropMap;
+use futures::StreamExt;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::api;
+use crate::backup::{self, Backup, Preview};
+use crate::audio::defaults::DefaultAudioSwitcher;
+use crate::audio::pulse::PulseAudio;
+use crate::bluetooth::adapter::DiscoveryOwner;
+use crate::bluetooth::events::{BluetoothEvent, ReconnectOutcome};
+use crate::bluetooth::gatt::{GattClient, GattHandle};
 use crate::bluetooth::manager::BluetoothManager;
+use crate::config::{DeviceListSettings, DeviceSettings, Preferences, Settings};
+use crate::dualboot::{self, keys::DeviceKeys};
+use crate::hooks::{self, Hooks};
+use crate::logind::Logind;
+use crate::obex::agent::{self, AgentRequest, PushRequest};
+use crate::obex::client::ObexClient;
+use crate::obex::transfer::{Transfer, TransferStatus};
+use crate::proximity::{self, ProximityLock};
+use crate::ui::advertisement_view::AdvertisementView;
+use crate::ui::signal_chart::SignalChart;
+use crate::ui::device_details::DeviceDetailsView;
 use crate::ui::device_list::DeviceListView;
+use crate::ui::gatt_view::GattView;
+use crate::ui::preferences::PreferencesView;
+use crate::ui::transfer_view::TransferView;
 
 glib::wrapper! {
     pub struct RustBlueWindow(ObjectSubclass<imp::RustBlueWindow>)
@@ -15,33 +45,87 @@ glib::wrapper! {
 
 impl RustBlueWindow {
     pub fn new(app: &Application) -> Self {
-        glib::Object::builder()
+        let window: Self = glib::Object::builder()
             .property("application", app)
-            .build()
+            .build();
+        window.install_app_actions(app);
+        window
     }
 }
 
 mod imp {
     use super::*;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
     #[derive(Debug)]
     pub struct RustBlueWindow {
         pub device_list: RefCell<Option<DeviceListView>>,
+        pub device_details: RefCell<Option<DeviceDetailsView>>,
+        pub advertisement_view: RefCell<Option<AdvertisementView>>,
+        pub signal_chart: RefCell<Option<SignalChart>>,
+        pub gatt_view: RefCell<Option<GattView>>,
+        pub gatt_client: RefCell<Option<GattClient>>,
+        pub gatt_subscriptions: RefCell<HashMap<GattHandle, glib::JoinHandle<()>>>,
+        pub stack: RefCell<Option<Stack>>,
+        pub toast_overlay: RefCell<Option<adw::ToastOverlay>>,
+        pub back_button: RefCell<Option<Button>>,
+        pub preferences_view: RefCell<Option<PreferencesView>>,
+        pub preferences_button: RefCell<Option<Button>>,
+        pub audio: PulseAudio,
+        pub audio_defaults: DefaultAudioSwitcher,
+        pub settings: RefCell<Settings>,
+        pub hooks: Hooks,
+        pub proximity: ProximityLock,
+        pub transfer_view: RefCell<Option<TransferView>>,
+        pub obex_client: RefCell<Option<ObexClient>>,
+        pub transfers: RefCell<HashMap<String, Transfer>>,
+        pub incoming_dialog: RefCell<Option<adw::MessageDialog>>,
+        pub backup_chooser: RefCell<Option<FileChooserNative>>,
+        pub hive_chooser: RefCell<Option<FileChooserNative>>,
         pub bluetooth_manager: RefCell<Option<BluetoothManager>>,
         pub scan_button: RefCell<Option<Button>>,
         pub bluetooth_toggle: RefCell<Option<Switch>>,
         pub auto_scan_source_id: RefCell<Option<glib::SourceId>>,
+        /// Set once `initialize_bluetooth` is done, successfully or not
+        pub initialized: Cell<bool>,
+        /// Between logind announcing a suspend and the resume, scanning stays off meanwhile
+        pub sleeping: Cell<bool>,
+        pub pending_actions: RefCell<Vec<(String, Option<glib::Variant>)>>,
     }
 
     impl Default for RustBlueWindow {
         fn default() -> Self {
             Self {
                 device_list: RefCell::new(None),
+                device_details: RefCell::new(None),
+                advertisement_view: RefCell::new(None),
+                signal_chart: RefCell::new(None),
+                gatt_view: RefCell::new(None),
+                gatt_client: RefCell::new(None),
+                gatt_subscriptions: RefCell::new(HashMap::new()),
+                stack: RefCell::new(None),
+                toast_overlay: RefCell::new(None),
+                back_button: RefCell::new(None),
+                preferences_view: RefCell::new(None),
+                preferences_button: RefCell::new(None),
+                audio: PulseAudio::new(),
+                audio_defaults: DefaultAudioSwitcher::new(PulseAudio::new()),
+                settings: RefCell::new(Settings::load()),
+                hooks: Hooks::new(),
+                proximity: ProximityLock::new(),
+                transfer_view: RefCell::new(None),
+                obex_client: RefCell::new(None),
+                transfers: RefCell::new(HashMap::new()),
+                incoming_dialog: RefCell::new(None),
+                backup_chooser: RefCell::new(None),
+                hive_chooser: RefCell::new(None),
                 bluetooth_manager: RefCell::new(None),
                 scan_button: RefCell::new(None),
                 bluetooth_toggle: RefCell::new(None),
                 auto_scan_source_id: RefCell::new(None),
+                initialized: Cell::new(false),
+                sleeping: Cell::new(false),
+                pending_actions: RefCell::new(Vec::new()),
             }
         }
     }
@@ -90,6 +174,15 @@ impl RustBlueWindow {
         bluetooth_toggle.set_active(true); // Assume Bluetooth is on by default
         header_bar.pack_end(&bluetooth_toggle);
         
+        let preferences_button = Button::from_icon_name("emblem-system-symbolic");
+        preferences_button.set_tooltip_text(Some("Preferences"));
+        header_bar.pack_end(&preferences_button);
+        
+        let back_button = Button::from_icon_name("go-previous-symbolic");
+        back_button.set_tooltip_text(Some("Back to devices"));
+        back_button.set_visible(false);
+        header_bar.pack_start(&back_button);
+        
         let scan_button = Button::with_label("Scan");
         scan_button.set_tooltip_text(Some("Scan for devices"));
         header_bar.pack_start(&scan_button);
@@ -130,14 +223,50 @@ impl RustBlueWindow {
         let device_list = DeviceListView::new();
         device_list.set_hexpand(true);
         device_list.set_vexpand(true);
+        device_list.set_list_settings(&self.imp().settings.borrow().device_list);
         
         // Add device list to the content box
         content_box.append(&device_list);
         
         main_box.append(&content_box);
         
-        // Set main content
-        self.set_child(Some(&main_box));
+        // OBEX file transfers, hidden while there are none
+        let transfer_view = TransferView::new();
+        main_box.append(&transfer_view);
+        
+        // Details page for a single device, with its advertisements and the GATT explorer below
+        let device_details = DeviceDetailsView::new();
+        let signal_chart = SignalChart::new();
+        let advertisement_view = AdvertisementView::new();
+        let gatt_view = GattView::new();
+        let details_box = GtkBox::new(Orientation::Vertical, 0);
+        details_box.append(&device_details);
+        details_box.append(&signal_chart);
+        details_box.append(&advertisement_view);
+        details_box.append(&gatt_view);
+        let details_scroll = ScrolledWindow::new();
+        details_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        details_scroll.set_child(Some(&details_box));
+        
+        // Application preferences
+        let preferences_view = PreferencesView::new();
+        preferences_view.set_preferences(&self.imp().settings.borrow().preferences);
+        let preferences_scroll = ScrolledWindow::new();
+        preferences_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        preferences_scroll.set_child(Some(&preferences_view));
+        
+        // Stack switching between the device list, the details page and the preferences
+        let stack = Stack::new();
+        stack.set_transition_type(gtk::StackTransitionType::SlideLeftRight);
+        stack.add_named(&main_box, Some("devices"));
+        stack.add_named(&details_scroll, Some("details"));
+        stack.add_named(&preferences_scroll, Some("preferences"));
+        stack.set_visible_child_name("devices");
+        
+        // Set main content, with outcomes of what the user did shown on top
+        let toast_overlay = adw::ToastOverlay::new();
+        toast_overlay.set_child(Some(&stack));
+        self.set_child(Some(&toast_overlay));
         
         // Additional window properties for better Hyprland handling
         // Set minimum and maximum size constraints
@@ -149,6 +278,16 @@ impl RustBlueWindow {
         // Store references
         let imp = self.imp();
         imp.device_list.replace(Some(device_list));
+        imp.device_details.replace(Some(device_details));
+        imp.advertisement_view.replace(Some(advertisement_view));
+        imp.signal_chart.replace(Some(signal_chart));
+        imp.gatt_view.replace(Some(gatt_view));
+        imp.transfer_view.replace(Some(transfer_view));
+        imp.stack.replace(Some(stack));
+        imp.toast_overlay.replace(Some(toast_overlay));
+        imp.back_button.replace(Some(back_button));
+        imp.preferences_view.replace(Some(preferences_view));
+        imp.preferences_button.replace(Some(preferences_button));
         imp.scan_button.replace(Some(scan_button.clone()));
         imp.bluetooth_toggle.replace(Some(bluetooth_toggle.clone()));
         
@@ -159,6 +298,21 @@ impl RustBlueWindow {
     fn connect_signals(&self) {
         let imp = self.imp();
         
+        // Scanning is only for the device list, pause it while hidden in the background
+        self.connect_visible_notify(|window| {
+            if !window.is_visible() {
+                let window = window.clone();
+                glib::spawn_future_local(async move {
+                    window.stop_device_scan().await;
+                });
+            } else if window.imp().initialized.get() && !window.imp().sleeping.get() && window.is_powered_on() {
+                let window = window.clone();
+                glib::spawn_future_local(async move {
+                    window.start_device_scan().await;
+                });
+            }
+        });
+        
         // Set up device list callbacks
         if let Some(device_list) = imp.device_list.borrow().as_ref() {
             let window_weak = self.downgrade();
@@ -187,6 +341,130 @@ impl RustBlueWindow {
                     });
                 }
             });
+            
+            let window_weak = self.downgrade();
+            device_list.set_details_callback(move |address| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.show_device_details(address).await;
+                    });
+                }
+            });
+            
+            let window_weak = self.downgrade();
+            device_list.set_send_files_callback(move |address, files| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.send_files(address, files).await;
+                    });
+                }
+            });
+            
+            let window_weak = self.downgrade();
+            device_list.set_list_settings_callback(move |list_settings| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.update_list_settings(list_settings);
+                }
+            });
+        }
+        
+        // Cancel running file transfers
+        if let Some(transfer_view) = imp.transfer_view.borrow().as_ref() {
+            let window_weak = self.downgrade();
+            transfer_view.set_cancel_callback(move |id| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.cancel_transfer(id).await;
+                    });
+                }
+            });
+        }
+        
+        // Switch audio profiles from the details page
+        if let Some(device_details) = imp.device_details.borrow().as_ref() {
+            let window_weak = self.downgrade();
+            device_details.set_profile_callback(move |card, profile| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.set_audio_profile(card, profile).await;
+                    });
+                }
+            });
+            
+            let window_weak = self.downgrade();
+            device_details.set_settings_callback(move |address, settings| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.update_device_settings(&address, settings);
+                }
+            });
+        }
+        
+        // Browse and use the GATT services of the device on the details page
+        if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+            let window_weak = self.downgrade();
+            gatt_view.set_refresh_callback(move || {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.refresh_gatt().await;
+                    });
+                }
+            });
+            
+            let window_weak = self.downgrade();
+            gatt_view.set_read_callback(move |handle| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.read_gatt(handle).await;
+                    });
+                }
+            });
+            
+            let window_weak = self.downgrade();
+            gatt_view.set_write_callback(move |handle, value| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.write_gatt(handle, value).await;
+                    });
+                }
+            });
+            
+            let window_weak = self.downgrade();
+            gatt_view.set_notify_callback(move |handle, subscribe| {
+                if let Some(window) = window_weak.upgrade() {
+                    glib::spawn_future_local(async move {
+                        window.set_gatt_notifications(handle, subscribe).await;
+                    });
+                }
+            });
+        }
+        
+        // Application preferences
+        if let Some(preferences_view) = imp.preferences_view.borrow().as_ref() {
+            let window_weak = self.downgrade();
+            preferences_view.set_preferences_callback(move |preferences| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.update_preferences(preferences);
+                }
+            });
+        }
+        
+        if let Some(preferences_button) = imp.preferences_button.borrow().as_ref() {
+            let window_weak = self.downgrade();
+            preferences_button.connect_clicked(move |_| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.show_preferences();
+                }
+            });
+        }
+        
+        // Go back from the details page to the device list
+        if let Some(back_button) = imp.back_button.borrow().as_ref() {
+            let window_weak = self.downgrade();
+            back_button.connect_clicked(move |_| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.show_device_list();
+                }
+            });
         }
         
         // Connect scan button to scan action
@@ -216,12 +494,35 @@ impl RustBlueWindow {
     }
     
     async fn initialize_bluetooth(&self) {
-        match BluetoothManager::new().await {
+        let power_policy = self.imp().settings.borrow().preferences.power_policy;
+        match BluetoothManager::new(power_policy).await {
             Ok(manager) => {
                 let imp = self.imp();
+                // Sightings since the last periodic save would be lost otherwise
+                if let Some(application) = self.application() {
+                    let manager = manager.clone();
+                    application.connect_shutdown(move |_| manager.save_device_records());
+                }
                 imp.bluetooth_manager.replace(Some(manager));
                 log::info!("Bluetooth manager initialized successfully");
                 
+                self.listen_for_events();
+                self.start_obex_agent();
+                self.start_api();
+                self.watch_sleep();
+                
+                if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+                    {
+                        let settings = imp.settings.borrow();
+                        imp.hooks.set(settings.hooks.clone(), settings.device_hooks());
+                        imp.proximity.set(settings.lock_when_away_devices(), settings.preferences.proximity.clone());
+                        manager.set_auto_connect_devices(settings.auto_connect_devices());
+                    }
+                    hooks::watch(manager, imp.hooks.clone());
+                    self.notify_proximity_returns(proximity::watch(manager, imp.proximity.clone()));
+                    manager.reconnect_favourites().await;
+                }
+                
                 // Check current Bluetooth adapter state and update toggle
                 if let Some(bluetooth_manager) = imp.bluetooth_manager.borrow().as_ref() {
                     if let Some(adapter) = bluetooth_manager.get_default_adapter().await {
@@ -232,15 +533,17 @@ impl RustBlueWindow {
                                     log::info!("Set Bluetooth toggle to: {}", powered);
                                 }
                                 
-                                // Only scan for devices if Bluetooth is powered on
-                                if powered {
+                                // Only scan for devices if Bluetooth is powered on and someone sees them
+                                if powered && self.is_visible() {
                                     self.start_device_scan().await;
                                 }
                             }
                             Err(e) => {
                                 log::warn!("Failed to check adapter power state: {}", e);
                                 // Default to scanning anyway
-                                self.start_device_scan().await;
+                                if self.is_visible() {
+                                    self.start_device_scan().await;
+                                }
                             }
                         }
                     } else {
@@ -262,6 +565,15 @@ impl RustBlueWindow {
                 }
             }
         }
+        
+        let imp = self.imp();
+        imp.initialized.set(true);
+        let pending_actions = imp.pending_actions.take();
+        if let Some(application) = self.application() {
+            for (name, target) in pending_actions {
+                application.activate_action(&name, target.as_ref());
+            }
+        }
     }
     
     async fn start_device_scan(&self) {
@@ -269,13 +581,16 @@ impl RustBlueWindow {
         
         if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
             // Start discovery
-            if let Err(e) = manager.start_discovery().await {
+            if let Err(e) = manager.start_discovery(DiscoveryOwner::Window).await {
                 log::error!("Failed to start device discovery: {}", e);
                 self.show_error_message(&format!("Failed to start scanning: {}", e));
                 return;
             }
             
             log::info!("Started device discovery, scanning for devices...");
+            if let Some(device_list) = imp.device_list.borrow().as_ref() {
+                device_list.set_discovering(true);
+            }
             
             // Get initial devices immediately
             match manager.get_devices().await {
@@ -314,7 +629,9 @@ impl RustBlueWindow {
             });
             
             // Store the source ID to be able to stop auto-scan later if needed
-            imp.auto_scan_source_id.replace(Some(source_id));
+            if let Some(previous) = imp.auto_scan_source_id.replace(Some(source_id)) {
+                previous.remove();
+            }
             
             log::info!("Device discovery initiated with continuous auto-scan every 3 seconds");
         } else {
@@ -327,6 +644,15 @@ impl RustBlueWindow {
         log::debug!("Updating device list with {} devices", devices.len());
         let imp = self.imp();
         
+        // Keep the details page in sync with the latest device state
+        if let Some(device_details) = imp.device_details.borrow().as_ref() {
+            if let Some(address) = device_details.address() {
+                if let Some(device) = devices.iter().find(|device| device.address == address) {
+                    device_details.set_device(device);
+                }
+            }
+        }
+        
         if let Some(device_list) = imp.device_list.borrow().as_ref() {
             log::debug!("Device list widget found, updating efficiently");
             device_list.update_devices_efficiently(devices);
@@ -336,139 +662,1444 @@ impl RustBlueWindow {
         }
     }
     
-    fn show_error_message(&self, message: &str) {
-        // For now, just log the error. In a full implementation, 
-        // you'd show a proper error dialog
-        log::error!("Error: {}", message);
-    }
-    
-    fn show_info_message(&self, message: &str) {
-        // For now, just log the info. In a full implementation, 
-        // you'd show a proper info notification
-        log::info!("Info: {}", message);
-    }
-    
-    async fn connect_device(&self, address: String) {
-        log::info!("Connecting to device: {}", address);
+    async fn show_device_details(&self, address: String) {
+        log::info!("Showing details for device: {}", address);
         let imp = self.imp();
         
-        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
-            match manager.connect_device(&address).await {
-                Ok(()) => {
-                    log::info!("Successfully connected to device: {}", address);
-                    self.show_info_message(&format!("Connected to {}", address));
-                    // Refresh the device list to update connection status
-                    self.refresh_device_list().await;
-                }
+        let device = if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            match manager.get_devices().await {
+                Ok(devices) => devices.into_iter().find(|device| device.address == address),
                 Err(e) => {
-                    log::error!("Failed to connect to device {}: {}", address, e);
-                    self.show_error_message(&format!("Failed to connect: {}", e));
+                    log::error!("Failed to get devices: {}", e);
+                    None
                 }
             }
         } else {
-            log::warn!("Bluetooth manager not initialized");
-            self.show_error_message("Bluetooth manager not initialized");
+            None
+        };
+        
+        let device = match device {
+            Some(device) => device,
+            None => {
+                self.show_error_message(&format!("Device {} not found", address));
+                return;
+            }
+        };
+        
+        let previous_address = imp.device_details.borrow().as_ref().and_then(|details| details.address());
+        if previous_address.as_deref() != Some(address.as_str()) {
+            self.stop_gatt();
+            if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+                gatt_view.clear();
+            }
+        }
+        
+        if let Some(device_details) = imp.device_details.borrow().as_ref() {
+            device_details.set_device(&device);
+            device_details.set_settings(&imp.settings.borrow().device(&address));
+        }
+        self.refresh_advertisement(&address);
+        self.refresh_signal_history(&address);
+        if let Some(stack) = imp.stack.borrow().as_ref() {
+            stack.set_visible_child_name("details");
+        }
+        if let Some(back_button) = imp.back_button.borrow().as_ref() {
+            back_button.set_visible(true);
+        }
+        
+        self.refresh_audio_card(&address).await;
+        if device.connected && imp.gatt_client.borrow().is_none() {
+            self.refresh_gatt().await;
         }
     }
     
-    async fn disconnect_device(&self, address: String) {
-        log::info!("Disconnecting from device: {}", address);
+    fn show_device_list(&self) {
+        let imp = self.imp();
+        if let Some(stack) = imp.stack.borrow().as_ref() {
+            stack.set_visible_child_name("devices");
+        }
+        if let Some(back_button) = imp.back_button.borrow().as_ref() {
+            back_button.set_visible(false);
+        }
+    }
+    
+    fn refresh_advertisement(&self, address: &str) {
         let imp = self.imp();
         
-        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
-            match manager.disconnect_device(&address).await {
-                Ok(()) => {
-                    log::info!("Successfully disconnected from device: {}", address);
-                    self.show_info_message(&format!("Disconnected from {}", address));
-                    // Refresh the device list to update connection status
-                    self.refresh_device_list().await;
+        let advertisement = imp.bluetooth_manager.borrow().as_ref()
+            .and_then(|manager| manager.advertisement(address));
+        if let Some(advertisement_view) = imp.advertisement_view.borrow().as_ref() {
+            advertisement_view.set_advertisement(advertisement.as_ref());
+        }
+    }
+    
+    fn refresh_signal_history(&self, address: &str) {
+        let imp = self.imp();
+        
+        let history = imp.bluetooth_manager.borrow().as_ref()
+            .and_then(|manager| manager.signal_history(address));
+        if let Some(signal_chart) = imp.signal_chart.borrow().as_ref() {
+            signal_chart.set_history(history.as_ref());
+        }
+    }
+    
+    async fn refresh_gatt(&self) {
+        let imp = self.imp();
+        
+        let address = match imp.device_details.borrow().as_ref().and_then(|details| details.address()) {
+            Some(address) => address,
+            None => return,
+        };
+        
+        let client = match imp.bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.gatt_client(&address).await,
+            None => return,
+        };
+        let client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Failed to get GATT client for {}: {}", address, e);
+                return;
+            }
+        };
+        
+        if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+            gatt_view.set_status(Some("Loading services…"));
+        }
+        
+        let services = client.services().await;
+        
+        // The user may have moved on to another device in the meantime
+        if imp.device_details.borrow().as_ref().and_then(|details| details.address()).as_deref() != Some(address.as_str()) {
+            return;
+        }
+        
+        if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+            match services {
+                Ok(services) => {
+                    gatt_view.set_services(&services);
+                    imp.gatt_client.replace(Some(client));
                 }
                 Err(e) => {
-                    log::error!("Failed to disconnect from device {}: {}", address, e);
-                    self.show_error_message(&format!("Failed to disconnect: {}", e));
+                    log::warn!("Failed to enumerate GATT services of {}: {}", address, e);
+                    gatt_view.set_status(Some(&e.to_string()));
                 }
             }
-        } else {
-            log::warn!("Bluetooth manager not initialized");
-            self.show_error_message("Bluetooth manager not initialized");
         }
     }
     
-    async fn forget_device(&self, address: String) {
-        log::info!("Forgetting device: {}", address);
+    async fn read_gatt(&self, handle: GattHandle) {
         let imp = self.imp();
+        let client = match imp.gatt_client.borrow().clone() {
+            Some(client) => client,
+            None => return,
+        };
         
-        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
-            match manager.remove_device(&address).await {
-                Ok(()) => {
-                    log::info!("Successfully removed device: {}", address);
-                    self.show_info_message(&format!("Forgotten {}", address));
-                    // Refresh the device list to remove the device
-                    self.refresh_device_list().await;
-                }
-                Err(e) => {
-                    log::error!("Failed to remove device {}: {}", address, e);
-                    self.show_error_message(&format!("Failed to forget device: {}", e));
+        match client.read(handle).await {
+            Ok(value) => {
+                if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+                    gatt_view.set_value(handle, &value);
                 }
             }
-        } else {
-            log::warn!("Bluetooth manager not initialized");
-            self.show_error_message("Bluetooth manager not initialized");
+            Err(e) => {
+                log::error!("Failed to read {:?}: {}", handle, e);
+                self.show_error_message(&format!("Failed to read value: {}", e));
+            }
         }
     }
     
-    async fn refresh_device_list(&self) {
-        log::info!("Refreshing device list");
+    async fn write_gatt(&self, handle: GattHandle, value: Vec<u8>) {
         let imp = self.imp();
+        let client = match imp.gatt_client.borrow().clone() {
+            Some(client) => client,
+            None => return,
+        };
         
-        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
-            match manager.get_devices().await {
-                Ok(devices) => {
-                    log::info!("Refreshed device list with {} devices", devices.len());
-                    self.update_device_list(devices);
-                }
-                Err(e) => {
-                    log::error!("Failed to refresh device list: {}", e);
+        match client.write(handle, &value).await {
+            Ok(()) => {
+                self.show_info_message(&format!("Wrote {} bytes", value.len()));
+                if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+                    gatt_view.set_value(handle, &value);
                 }
             }
+            Err(e) => {
+                log::error!("Failed to write {:?}: {}", handle, e);
+                self.show_error_message(&format!("Failed to write value: {}", e));
+            }
         }
     }
     
-    async fn toggle_bluetooth(&self, enabled: bool) {
-        log::info!("Toggling Bluetooth: {}", if enabled { "ON" } else { "OFF" });
+    async fn set_gatt_notifications(&self, handle: GattHandle, subscribe: bool) {
         let imp = self.imp();
         
-        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
-            match manager.set_adapter_powered(enabled).await {
-                Ok(()) => {
-                    log::info!("Successfully {} Bluetooth", if enabled { "enabled" } else { "disabled" });
-                    if enabled {
-                        self.show_info_message("Bluetooth enabled");
-                        // Automatically scan for devices when Bluetooth is turned on
-                        self.start_device_scan().await;
-                    } else {
-                        self.show_info_message("Bluetooth disabled");
-                        // Clear device list when Bluetooth is turned off
-                        self.update_device_list(vec![]);
-                    }
+        if !subscribe {
+            // Dropping the stream makes BlueZ stop notifying
+            if let Some(subscription) = imp.gatt_subscriptions.borrow_mut().remove(&handle) {
+                subscription.abort();
+            }
+            return;
+        }
+        
+        let client = match imp.gatt_client.borrow().clone() {
+            Some(client) => client,
+            None => return,
+        };
+        
+        let mut values = match client.notify(handle).await {
+            Ok(values) => values,
+            Err(e) => {
+                log::error!("Failed to subscribe to {:?}: {}", handle, e);
+                self.show_error_message(&format!("Failed to subscribe to notifications: {}", e));
+                if let Some(gatt_view) = imp.gatt_view.borrow().as_ref() {
+                    gatt_view.set_subscribed(handle, false);
                 }
-                Err(e) => {
-                    log::error!("Failed to {} Bluetooth: {}", if enabled { "enable" } else { "disable" }, e);
-                    self.show_error_message(&format!("Failed to {} Bluetooth: {}", if enabled { "enable" } else { "disable" }, e));
-                    
-                    // Revert the toggle state on error
-                    if let Some(toggle) = imp.bluetooth_toggle.borrow().as_ref() {
-                        toggle.set_active(!enabled);
+                return;
+            }
+        };
+        
+        let window_weak = self.downgrade();
+        let subscription = glib::spawn_future_local(async move {
+            while let Some(value) = values.next().await {
+                match window_weak.upgrade() {
+                    Some(window) => {
+                        if let Some(gatt_view) = window.imp().gatt_view.borrow().as_ref() {
+                            gatt_view.add_notification(handle, &value);
+                        }
                     }
+                    None => return,
                 }
             }
-        } else {
-            log::warn!("Bluetooth manager not initialized");
-            self.show_error_message("Bluetooth manager not initialized");
             
-            // Revert the toggle state on error
-            if let Some(toggle) = imp.bluetooth_toggle.borrow().as_ref() {
-                toggle.set_active(!enabled);
+            // The device disconnected or stopped notifying
+            if let Some(window) = window_weak.upgrade() {
+                window.imp().gatt_subscriptions.borrow_mut().remove(&handle);
+                if let Some(gatt_view) = window.imp().gatt_view.borrow().as_ref() {
+                    gatt_view.set_subscribed(handle, false);
+                }
+            }
+        });
+        
+        if let Some(previous) = imp.gatt_subscriptions.borrow_mut().insert(handle, subscription) {
+            previous.abort();
+        }
+    }
+    
+    fn stop_gatt(&self) {
+        let imp = self.imp();
+        for (_, subscription) in imp.gatt_subscriptions.borrow_mut().drain() {
+            subscription.abort();
+        }
+        imp.gatt_client.replace(None);
+    }
+    
+    fn show_preferences(&self) {
+        let imp = self.imp();
+        if let Some(stack) = imp.stack.borrow().as_ref() {
+            stack.set_visible_child_name("preferences");
+        }
+        if let Some(back_button) = imp.back_button.borrow().as_ref() {
+            back_button.set_visible(true);
+        }
+    }
+    
+    async fn refresh_audio_card(&self, address: &str) {
+        let imp = self.imp();
+        
+        let card = match imp.audio.find_card(address).await {
+            Ok(card) => card,
+            Err(e) => {
+                log::warn!("Failed to query audio card for {}: {}", address, e);
+                None
+            }
+        };
+        
+        if let Some(device_details) = imp.device_details.borrow().as_ref() {
+            // The user may have moved on to another device in the meantime
+            if device_details.address().as_deref() == Some(address) {
+                device_details.set_audio_card(card.as_ref());
+            }
+        }
+    }
+    
+    fn update_device_settings(&self, address: &str, device_settings: DeviceSettings) {
+        log::info!("Updating settings of {}: {:?}", address, device_settings);
+        let imp = self.imp();
+        
+        let mut settings = imp.settings.borrow_mut();
+        settings.set_device(address, device_settings);
+        if let Err(e) = settings.save() {
+            log::error!("Failed to save settings: {}", e);
+            self.show_error_message(&format!("Failed to save settings: {}", e));
+        }
+        
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            manager.set_auto_connect_devices(settings.auto_connect_devices());
+        }
+        imp.hooks.set(settings.hooks.clone(), settings.device_hooks());
+        imp.proximity.set(settings.lock_when_away_devices(), settings.preferences.proximity.clone());
+    }
+    
+    fn update_preferences(&self, preferences: Preferences) {
+        log::info!("Updating preferences: {:?}", preferences);
+        let imp = self.imp();
+        
+        let mut settings = imp.settings.borrow_mut();
+        settings.preferences = preferences;
+        if let Err(e) = settings.save() {
+            log::error!("Failed to save settings: {}", e);
+            self.show_error_message(&format!("Failed to save settings: {}", e));
+        }
+        imp.proximity.set(settings.lock_when_away_devices(), settings.preferences.proximity.clone());
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            manager.set_power_policy(settings.preferences.power_policy);
+        }
+    }
+    
+    fn update_list_settings(&self, list_settings: DeviceListSettings) {
+        log::debug!("Updating device list settings: {:?}", list_settings);
+        let imp = self.imp();
+        
+        let mut settings = imp.settings.borrow_mut();
+        settings.device_list = list_settings;
+        if let Err(e) = settings.save() {
+            log::error!("Failed to save settings: {}", e);
+            self.show_error_message(&format!("Failed to save settings: {}", e));
+        }
+    }
+    
+    fn listen_for_events(&self) {
+        let imp = self.imp();
+        
+        let mut receiver = match imp.bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.subscribe(),
+            None => return,
+        };
+        
+        let window_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log::warn!("Missed {} Bluetooth events", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                
+                match window_weak.upgrade() {
+                    Some(window) => window.handle_bluetooth_event(event).await,
+                    None => break,
+                }
+            }
+        });
+    }
+    
+    async fn handle_bluetooth_event(&self, event: BluetoothEvent) {
+        log::debug!("Handling Bluetooth event: {:?}", event);
+        self.notify_device_event(&event);
+        
+        match event {
+            BluetoothEvent::DeviceConnected { address, .. } => {
+                // Waits up to seconds for the sound server's nodes, so it must not hold up
+                // the other events
+                let window = self.clone();
+                glib::spawn_future_local(async move {
+                    window.apply_default_audio(&address).await;
+                });
+                self.refresh_device_list().await;
+            }
+            BluetoothEvent::DeviceDisconnected { address, .. } => {
+                self.refresh_device_list().await;
+                self.restore_default_audio(&address).await;
+            }
+            BluetoothEvent::DeviceAdded { .. } | BluetoothEvent::DeviceRemoved { .. } | BluetoothEvent::DevicePaired { .. } => {
+                self.refresh_device_list().await;
+            }
+            BluetoothEvent::ConnectFailed { .. } | BluetoothEvent::PairingFailed { .. } => {
+                // Already reported by whoever started the attempt
+            }
+            BluetoothEvent::ReconnectStarted { .. } | BluetoothEvent::ReconnectStopped { .. } => {
+                // Shows or clears the "Reconnecting…" state
+                self.refresh_device_list().await;
+            }
+            BluetoothEvent::AdvertisementReceived { address, .. } => {
+                let imp = self.imp();
+                let rssi = imp.bluetooth_manager.borrow().as_ref()
+                    .and_then(|manager| manager.advertisement(&address))
+                    .and_then(|advertisement| advertisement.rssi);
+                if let Some(device_list) = imp.device_list.borrow().as_ref() {
+                    device_list.set_rssi(&address, rssi);
+                }
+                
+                // Only the details page shows advertisements
+                let shown = imp.device_details.borrow().as_ref().and_then(|details| details.address());
+                if shown.as_deref() == Some(address.as_str()) {
+                    self.refresh_advertisement(&address);
+                    self.refresh_signal_history(&address);
+                }
+            }
+            BluetoothEvent::BatteryChanged { address, .. } => {
+                let shown = self.imp().device_details.borrow().as_ref().and_then(|details| details.address());
+                if shown.as_deref() == Some(address.as_str()) {
+                    self.refresh_signal_history(&address);
+                }
+            }
+            BluetoothEvent::PresenceChanged { address, present, .. } => {
+                // Seen by an advertisement monitor, outside of discovery
+                if present {
+                    self.refresh_device_list().await;
+                } else if let Some(device_list) = self.imp().device_list.borrow().as_ref() {
+                    device_list.set_rssi(&address, None);
+                }
+            }
+            BluetoothEvent::AdapterAdded { .. } | BluetoothEvent::AdapterRemoved { .. } => {
+                let manager = self.imp().bluetooth_manager.borrow().clone();
+                let powered = match manager {
+                    Some(manager) => manager.is_adapter_powered().await.unwrap_or(false),
+                    None => false,
+                };
+                if let Some(toggle) = self.imp().bluetooth_toggle.borrow().as_ref() {
+                    toggle.set_active(powered);
+                }
+            }
+            BluetoothEvent::AdapterPowered { powered, .. } => {
+                if let Some(toggle) = self.imp().bluetooth_toggle.borrow().as_ref() {
+                    toggle.set_active(powered);
+                }
+            }
+            BluetoothEvent::AdapterDiscovering { discovering, .. } => {
+                if let Some(device_list) = self.imp().device_list.borrow().as_ref() {
+                    device_list.set_discovering(discovering);
+                }
+            }
+        }
+    }
+    
+    /// Application actions, used by the notification buttons and forwarded from the command
+    /// line of a second `rustblue` (see `main.rs`). Device actions take the address.
+    fn install_app_actions(&self, app: &Application) {
+        let connect_action = gio::SimpleAction::new("connect", Some(glib::VariantTy::STRING));
+        let window_weak = self.downgrade();
+        connect_action.connect_activate(move |_, parameter| {
+            let address = parameter.and_then(|parameter| parameter.get::<String>());
+            if let (Some(window), Some(address)) = (window_weak.upgrade(), address) {
+                glib::spawn_future_local(async move {
+                    window.connect_device(address).await;
+                });
+            }
+        });
+        app.add_action(&connect_action);
+        
+        let disconnect_action = gio::SimpleAction::new("disconnect", Some(glib::VariantTy::STRING));
+        let window_weak = self.downgrade();
+        disconnect_action.connect_activate(move |_, parameter| {
+            let address = parameter.and_then(|parameter| parameter.get::<String>());
+            if let (Some(window), Some(address)) = (window_weak.upgrade(), address) {
+                glib::spawn_future_local(async move {
+                    window.disconnect_device(address).await;
+                });
+            }
+        });
+        app.add_action(&disconnect_action);
+        
+        let toggle_power_action = gio::SimpleAction::new("toggle-power", None);
+        let window_weak = self.downgrade();
+        toggle_power_action.connect_activate(move |_, _| {
+            if let Some(window) = window_weak.upgrade() {
+                // Goes through the switch so it stays in sync, see connect_signals
+                if let Some(toggle) = window.imp().bluetooth_toggle.borrow().as_ref() {
+                    toggle.set_active(!toggle.is_active());
+                }
+            }
+        });
+        app.add_action(&toggle_power_action);
+        
+        let scan_action = gio::SimpleAction::new("scan", None);
+        let window_weak = self.downgrade();
+        scan_action.connect_activate(move |_, _| {
+            if let Some(window) = window_weak.upgrade() {
+                glib::spawn_future_local(async move {
+                    window.start_device_scan().await;
+                });
+            }
+        });
+        app.add_action(&scan_action);
+        
+        let show_action = gio::SimpleAction::new("show", None);
+        let window_weak = self.downgrade();
+        show_action.connect_activate(move |_, _| {
+            if let Some(window) = window_weak.upgrade() {
+                window.present();
+            }
+        });
+        app.add_action(&show_action);
+        
+        let show_device_action = gio::SimpleAction::new("show-device", Some(glib::VariantTy::STRING));
+        let window_weak = self.downgrade();
+        show_device_action.connect_activate(move |_, parameter| {
+            let address = parameter.and_then(|parameter| parameter.get::<String>());
+            if let (Some(window), Some(address)) = (window_weak.upgrade(), address) {
+                window.present();
+                glib::spawn_future_local(async move {
+                    window.show_device_details(address).await;
+                });
+            }
+        });
+        app.add_action(&show_device_action);
+        
+        let export_action = gio::SimpleAction::new("export-settings", None);
+        let window_weak = self.downgrade();
+        export_action.connect_activate(move |_, _| {
+            if let Some(window) = window_weak.upgrade() {
+                window.choose_backup_file(FileChooserAction::Save);
+            }
+        });
+        app.add_action(&export_action);
+        
+        let import_action = gio::SimpleAction::new("import-settings", None);
+        let window_weak = self.downgrade();
+        import_action.connect_activate(move |_, _| {
+            if let Some(window) = window_weak.upgrade() {
+                window.choose_backup_file(FileChooserAction::Open);
+            }
+        });
+        app.add_action(&import_action);
+        
+        let windows_keys_action = gio::SimpleAction::new("import-windows-keys", None);
+        let window_weak = self.downgrade();
+        windows_keys_action.connect_activate(move |_, _| {
+            if let Some(window) = window_weak.upgrade() {
+                window.choose_windows_hive();
+            }
+        });
+        app.add_action(&windows_keys_action);
+    }
+    
+    /// Activate an application action once Bluetooth is initialized, e.g. one given on the
+    /// command line of the first instance.
+    pub fn activate_when_ready(&self, name: &str, target: Option<glib::Variant>) {
+        let imp = self.imp();
+        
+        if imp.initialized.get() {
+            if let Some(application) = self.application() {
+                application.activate_action(name, target.as_ref());
+            }
+        } else {
+            imp.pending_actions.borrow_mut().push((name.to_string(), target));
+        }
+    }
+    
+    /// Show a desktop notification for the event if the preferences ask for one.
+    fn notify_device_event(&self, event: &BluetoothEvent) {
+        let imp = self.imp();
+        let settings = imp.settings.borrow().preferences.notifications.clone();
+        if !settings.enabled {
+            return;
+        }
+        
+        let (address, title, error) = match event {
+            BluetoothEvent::DeviceConnected { address, .. } if settings.connected => (address, "Device Connected", None),
+            BluetoothEvent::DeviceDisconnected { address, .. } if settings.disconnected => (address, "Device Disconnected", None),
+            BluetoothEvent::DevicePaired { address, .. } if settings.paired => (address, "Device Paired", None),
+            BluetoothEvent::ConnectFailed { address, error } if settings.failed => (address, "Connection Failed", Some(error)),
+            BluetoothEvent::PairingFailed { address, error } if settings.failed => (address, "Pairing Failed", Some(error)),
+            BluetoothEvent::ReconnectStopped { address, outcome: ReconnectOutcome::GaveUp } if settings.failed => {
+                (address, "Reconnecting Failed", None)
+            }
+            _ => return,
+        };
+        
+        let application = match self.application() {
+            Some(application) => application,
+            None => return,
+        };
+        
+        // The list's copy is enough for a name and icon, asking BlueZ would hold up the events
+        let device = imp.device_list.borrow().as_ref().and_then(|device_list| device_list.device(address));
+        let name = device.as_ref().map(|device| device.name.clone()).unwrap_or_else(|| address.clone());
+        let icon_name = device.as_ref().map(|device| device.icon_name()).unwrap_or("bluetooth");
+        
+        let notification = gio::Notification::new(title);
+        notification.set_body(Some(&match error {
+            Some(error) => format!("{}: {}", name, error),
+            None => name,
+        }));
+        notification.set_icon(&gio::ThemedIcon::new(icon_name));
+        
+        let target = address.to_variant();
+        if matches!(event, BluetoothEvent::DeviceConnected { .. }) {
+            notification.add_button_with_target_value("Disconnect", "app.disconnect", Some(&target));
+        }
+        notification.add_button_with_target_value("Open Details", "app.show-device", Some(&target));
+        notification.set_default_action_and_target_value("app.show-device", Some(&target));
+        
+        // One notification per device, a newer event replaces the older one
+        application.send_notification(Some(&format!("device-{}", address)), &notification);
+    }
+    
+    async fn apply_default_audio(&self, address: &str) {
+        let imp = self.imp();
+        
+        if !imp.settings.borrow().device(address).default_audio_on_connect {
+            return;
+        }
+        
+        if let Err(e) = imp.audio_defaults.make_default(address).await {
+            log::error!("Failed to make {} the default audio device: {}", address, e);
+        }
+    }
+    
+    async fn restore_default_audio(&self, address: &str) {
+        if let Err(e) = self.imp().audio_defaults.restore(address).await {
+            log::error!("Failed to restore default audio devices: {}", e);
+        }
+    }
+    
+    async fn set_audio_profile(&self, card: String, profile: String) {
+        let imp = self.imp();
+        
+        match imp.audio.set_card_profile(&card, &profile).await {
+            Ok(()) => {
+                self.show_info_message(&format!("Switched {} to {}", card, profile));
+            }
+            Err(e) => {
+                log::error!("Failed to set profile {} on {}: {}", profile, card, e);
+                self.show_error_message(&format!("Failed to switch audio profile: {}", e));
+            }
+        }
+        
+        // Re-read the card so the codec and selection reflect what the sound server did
+        let address = imp.device_details.borrow().as_ref().and_then(|details| details.address());
+        if let Some(address) = address {
+            self.refresh_audio_card(&address).await;
+        }
+    }
+    
+    fn show_error_message(&self, message: &str) {
+        log::error!("Error: {}", message);
+        // Toast titles are markup, error messages may well contain paths with '&' or '<'
+        let toast = adw::Toast::new(&glib::markup_escape_text(message));
+        // Shown before queued info toasts, and until dismissed
+        toast.set_priority(adw::ToastPriority::High);
+        toast.set_timeout(0);
+        self.show_toast(toast);
+    }
+    
+    fn show_info_message(&self, message: &str) {
+        log::info!("Info: {}", message);
+        self.show_toast(adw::Toast::new(&glib::markup_escape_text(message)));
+    }
+    
+    fn show_toast(&self, toast: adw::Toast) {
+        if let Some(toast_overlay) = self.imp().toast_overlay.borrow().as_ref() {
+            toast_overlay.add_toast(toast);
+        }
+    }
+    
+    async fn connect_device(&self, address: String) {
+        log::info!("Connecting to device: {}", address);
+        let imp = self.imp();
+        
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            match manager.connect_device(&address).await {
+                Ok(()) => {
+                    log::info!("Successfully connected to device: {}", address);
+                    self.show_info_message(&format!("Connected to {}", address));
+                    // Refresh the device list to update connection status
+                    self.refresh_device_list().await;
+                }
+                Err(e) => {
+                    log::error!("Failed to connect to device {}: {}", address, e);
+                    self.show_error_message(&format!("Failed to connect: {}", e));
+                }
+            }
+        } else {
+            log::warn!("Bluetooth manager not initialized");
+            self.show_error_message("Bluetooth manager not initialized");
+        }
+    }
+    
+    async fn disconnect_device(&self, address: String) {
+        log::info!("Disconnecting from device: {}", address);
+        let imp = self.imp();
+        
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            match manager.disconnect_device(&address).await {
+                Ok(()) => {
+                    log::info!("Successfully disconnected from device: {}", address);
+                    self.show_info_message(&format!("Disconnected from {}", address));
+                    self.restore_default_audio(&address).await;
+                    // Refresh the device list to update connection status
+                    self.refresh_device_list().await;
+                }
+                Err(e) => {
+                    log::error!("Failed to disconnect from device {}: {}", address, e);
+                    self.show_error_message(&format!("Failed to disconnect: {}", e));
+                }
+            }
+        } else {
+            log::warn!("Bluetooth manager not initialized");
+            self.show_error_message("Bluetooth manager not initialized");
+        }
+    }
+    
+    async fn forget_device(&self, address: String) {
+        log::info!("Forgetting device: {}", address);
+        let imp = self.imp();
+        
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            match manager.remove_device(&address).await {
+                Ok(()) => {
+                    log::info!("Successfully removed device: {}", address);
+                    self.show_info_message(&format!("Forgotten {}", address));
+                    // Refresh the device list to remove the device
+                    self.refresh_device_list().await;
+                }
+                Err(e) => {
+                    log::error!("Failed to remove device {}: {}", address, e);
+                    self.show_error_message(&format!("Failed to forget device: {}", e));
+                }
+            }
+        } else {
+            log::warn!("Bluetooth manager not initialized");
+            self.show_error_message("Bluetooth manager not initialized");
+        }
+    }
+    
+    fn obex_client(&self) -> anyhow::Result<ObexClient> {
+        let imp = self.imp();
+        
+        if let Some(client) = imp.obex_client.borrow().as_ref() {
+            return Ok(client.clone());
+        }
+        
+        let client = ObexClient::new()?;
+        imp.obex_client.replace(Some(client.clone()));
+        Ok(client)
+    }
+    
+    async fn send_files(&self, address: String, files: Vec<PathBuf>) {
+        log::info!("Sending {} files to device: {}", files.len(), address);
+        let imp = self.imp();
+        
+        let client = match self.obex_client() {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Failed to connect to the session bus: {}", e);
+                self.show_error_message(&format!("Failed to send files: {}", e));
+                return;
+            }
+        };
+        
+        let device_name = match imp.bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.get_devices().await.ok()
+                .and_then(|devices| devices.into_iter().find(|device| device.address == address))
+                .map(|device| device.name),
+            None => None,
+        }.unwrap_or_else(|| address.clone());
+        
+        let session = match client.create_push_session(&address).await {
+            Ok(session) => session,
+            Err(e) => {
+                log::error!("Failed to create OBEX session to {}: {}", address, e);
+                self.show_error_message(&format!("Failed to send files: {}", e));
+                return;
+            }
+        };
+        
+        // obexd sends the files of a session one after another
+        for file in files {
+            let file_name = file.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file.display().to_string());
+            
+            let (transfer, properties) = match session.send_file(&file).await {
+                Ok(transfer) => transfer,
+                Err(e) => {
+                    log::error!("Failed to send {}: {}", file.display(), e);
+                    self.show_error_message(&format!("Failed to send {}: {}", file_name, e));
+                    break;
+                }
+            };
+            
+            let id = transfer.id();
+            if let Some(transfer_view) = imp.transfer_view.borrow().as_ref() {
+                transfer_view.add_transfer(&id, &file_name, &device_name, false);
+            }
+            
+            let mut updates = match transfer.watch(&properties).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    log::error!("Failed to follow transfer {}: {}", id, e);
+                    break;
+                }
+            };
+            imp.transfers.borrow_mut().insert(id.clone(), transfer);
+            
+            let mut completed = false;
+            while let Some(progress) = updates.recv().await {
+                completed = progress.status == TransferStatus::Complete;
+                if let Some(transfer_view) = imp.transfer_view.borrow().as_ref() {
+                    transfer_view.update_transfer(&id, &progress);
+                }
+            }
+            imp.transfers.borrow_mut().remove(&id);
+            
+            // A cancelled or rejected file ends the whole batch
+            if !completed {
+                break;
+            }
+            self.show_info_message(&format!("Sent {} to {}", file_name, device_name));
+        }
+        
+        if let Err(e) = session.close().await {
+            log::warn!("Failed to close OBEX session: {}", e);
+        }
+    }
+    
+    /// Register the OBEX agent so files pushed by devices are offered to the user.
+    fn start_obex_agent(&self) {
+        let client = match self.obex_client() {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Failed to connect to the session bus: {}", e);
+                return;
+            }
+        };
+        
+        let window_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            let mut requests = match client.register_agent().await {
+                Ok(requests) => requests,
+                Err(e) => {
+                    log::warn!("Receiving files is unavailable: {}", e);
+                    return;
+                }
+            };
+            
+            while let Some(request) = requests.recv().await {
+                let window = match window_weak.upgrade() {
+                    Some(window) => window,
+                    None => break,
+                };
+                
+                match request {
+                    AgentRequest::AuthorizePush(request) => {
+                        // Handled separately so a Cancel can still close the prompt
+                        glib::spawn_future_local(async move {
+                            window.receive_file(request).await;
+                        });
+                    }
+                    AgentRequest::Cancel => {
+                        log::info!("Incoming file was cancelled by the sender");
+                        if let Some(dialog) = window.imp().incoming_dialog.take() {
+                            dialog.close();
+                        }
+                    }
+                }
+            }
+        });
+    }
+    
+    /// Export the `org.rustblue.Manager` D-Bus interface for other applications.
+    fn start_api(&self) {
+        let manager = match self.imp().bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.clone(),
+            None => return,
+        };
+        
+        glib::spawn_future_local(async move {
+            if let Err(e) = api::serve(manager).await {
+                log::warn!("D-Bus API is unavailable: {}", e);
+            }
+        });
+    }
+    
+    /// Tell the user when the proximity lock's devices come back, without unlocking anything.
+    fn notify_proximity_returns(&self, mut returned: mpsc::UnboundedReceiver<()>) {
+        let window_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            while returned.recv().await.is_some() {
+                let Some(application) = window_weak.upgrade().and_then(|window| window.application()) else {
+                    break;
+                };
+                let notification = gio::Notification::new("Welcome Back");
+                notification.set_body(Some("Your device is near again, unlock the session to continue"));
+                notification.set_icon(&gio::ThemedIcon::new("phone"));
+                application.send_notification(Some("proximity-returned"), &notification);
+            }
+        });
+    }
+    
+    /// Pause scanning while the system sleeps and bring Bluetooth back up once it has resumed.
+    fn watch_sleep(&self) {
+        let window_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            let logind = match Logind::new() {
+                Ok(logind) => logind,
+                Err(e) => {
+                    log::warn!("Suspend handling is unavailable: {}", e);
+                    return;
+                }
+            };
+            let mut sleep = match logind.watch_sleep().await {
+                Ok(sleep) => sleep,
+                Err(e) => {
+                    log::warn!("Failed to watch for suspend: {}", e);
+                    return;
+                }
+            };
+            
+            const WHY: &str = "Pausing Bluetooth scanning";
+            let mut inhibitor = logind.inhibit_sleep(WHY).await
+                .map_err(|e| log::warn!("Failed to delay suspend: {}", e))
+                .ok();
+            
+            while let Some(sleeping) = sleep.recv().await {
+                let Some(window) = window_weak.upgrade() else {
+                    break;
+                };
+                let imp = window.imp();
+                let manager = match imp.bluetooth_manager.borrow().as_ref() {
+                    Some(manager) => manager.clone(),
+                    None => continue,
+                };
+                
+                if sleeping {
+                    imp.sleeping.set(true);
+                    window.stop_auto_scan();
+                    if let Some(device_list) = imp.device_list.borrow().as_ref() {
+                        device_list.set_discovering(false);
+                    }
+                    manager.prepare_for_sleep().await;
+                    // Done preparing, let the system go to sleep
+                    inhibitor = None;
+                } else {
+                    if let Err(e) = manager.resume().await {
+                        log::error!("Failed to restore Bluetooth after resume: {}", e);
+                    }
+                    imp.sleeping.set(false);
+                    if inhibitor.is_none() {
+                        inhibitor = logind.inhibit_sleep(WHY).await
+                            .map_err(|e| log::warn!("Failed to delay suspend: {}", e))
+                            .ok();
+                    }
+                    
+                    let powered = manager.is_adapter_powered().await.unwrap_or(false);
+                    if powered && window.is_visible() {
+                        window.start_device_scan().await;
+                    }
+                }
+            }
+        });
+    }
+    
+    async fn receive_file(&self, request: PushRequest) {
+        let imp = self.imp();
+        
+        let device = match (imp.bluetooth_manager.borrow().as_ref(), request.address.as_ref()) {
+            (Some(manager), Some(address)) => manager.get_devices().await.ok()
+                .and_then(|devices| devices.into_iter().find(|device| &device.address == address)),
+            _ => None,
+        };
+        let device_name = device.as_ref().map(|device| device.name.clone())
+            .or_else(|| request.address.clone())
+            .unwrap_or_else(|| "Unknown device".to_string());
+        let trusted = device.as_ref().map(|device| device.is_trusted()).unwrap_or(false);
+        
+        let preferences = imp.settings.borrow().preferences.clone();
+        let accepted = if trusted && preferences.auto_accept_trusted {
+            log::info!("Accepting {} from trusted device {}", request.file_name, device_name);
+            true
+        } else {
+            self.ask_to_receive(&device_name, &request.file_name, request.size).await
+        };
+        
+        if !accepted {
+            if let Err(e) = request.reject() {
+                log::error!("Failed to reject incoming file: {}", e);
+            }
+            return;
+        }
+        
+        let download_dir = preferences.download_dir();
+        if let Err(e) = std::fs::create_dir_all(&download_dir) {
+            log::error!("Failed to create {}: {}", download_dir.display(), e);
+            self.show_error_message(&format!("Failed to receive file: {}", e));
+            let _ = request.reject();
+            return;
+        }
+        let destination = agent::unique_destination(&download_dir, &request.file_name);
+        
+        // Follow the transfer before accepting so no progress is missed
+        let transfer = request.transfer.clone();
+        let file_name = request.file_name.clone();
+        let mut updates = match transfer.watch(&PropMap::new()).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                log::error!("Failed to follow incoming transfer: {}", e);
+                let _ = request.reject();
+                return;
+            }
+        };
+        
+        if let Err(e) = request.accept(&destination) {
+            log::error!("Failed to accept incoming file: {}", e);
+            self.show_error_message(&format!("Failed to receive file: {}", e));
+            return;
+        }
+        
+        let id = transfer.id();
+        if let Some(transfer_view) = imp.transfer_view.borrow().as_ref() {
+            transfer_view.add_transfer(&id, &file_name, &device_name, true);
+        }
+        imp.transfers.borrow_mut().insert(id.clone(), transfer);
+        
+        let mut completed = false;
+        while let Some(progress) = updates.recv().await {
+            completed = progress.status == TransferStatus::Complete;
+            if let Some(transfer_view) = imp.transfer_view.borrow().as_ref() {
+                transfer_view.update_transfer(&id, &progress);
+            }
+        }
+        imp.transfers.borrow_mut().remove(&id);
+        
+        if completed {
+            self.show_info_message(&format!("Received {} from {}", file_name, device_name));
+            self.notify_file_received(&device_name, &destination);
+        } else {
+            self.show_error_message(&format!("Receiving {} from {} failed", file_name, device_name));
+        }
+    }
+    
+    /// Pick the file to export the device settings to with `Save`, or to import from with `Open`.
+    fn choose_backup_file(&self, action: FileChooserAction) {
+        let exporting = action == FileChooserAction::Save;
+        let file_chooser = FileChooserNative::new(
+            Some(if exporting { "Export Device Settings" } else { "Import Device Settings" }),
+            Some(self),
+            action,
+            Some(if exporting { "Export" } else { "Import" }),
+            Some("Cancel"),
+        );
+        if exporting {
+            file_chooser.set_current_name("rustblue-devices.json");
+        }
+        
+        let window_weak = self.downgrade();
+        file_chooser.connect_response(move |file_chooser, response| {
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            if response == ResponseType::Accept {
+                if let Some(path) = file_chooser.file().and_then(|file| file.path()) {
+                    let window = window.clone();
+                    glib::spawn_future_local(async move {
+                        if exporting {
+                            window.export_settings(path).await;
+                        } else {
+                            window.import_settings(path).await;
+                        }
+                    });
+                }
+            }
+            window.imp().backup_chooser.replace(None);
+        });
+        
+        file_chooser.show();
+        // Native dialogs are not kept alive by GTK while they are shown
+        self.imp().backup_chooser.replace(Some(file_chooser));
+    }
+    
+    async fn export_settings(&self, path: PathBuf) {
+        let manager = match self.imp().bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.clone(),
+            None => return,
+        };
+        let settings = self.imp().settings.borrow().clone();
+        
+        let result = match backup::export(&manager, &settings).await {
+            Ok(backup) => backup.save(&path),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => self.show_info_message(&format!("Exported device settings to {}", path.display())),
+            Err(e) => self.show_error_message(&format!("Failed to export device settings: {}", e)),
+        }
+    }
+    
+    /// Show what importing `path` would change and apply it once confirmed.
+    async fn import_settings(&self, path: PathBuf) {
+        let manager = match self.imp().bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.clone(),
+            None => return,
+        };
+        let settings = self.imp().settings.borrow().clone();
+        
+        let backup = match Backup::load(&path) {
+            Ok(backup) => backup,
+            Err(e) => {
+                self.show_error_message(&format!("Failed to read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let preview = match backup::preview(&backup, &manager, &settings).await {
+            Ok(preview) => preview,
+            Err(e) => {
+                self.show_error_message(&format!("Failed to compare device settings: {}", e));
+                return;
+            }
+        };
+        if preview.is_empty() {
+            self.show_info_message("The devices already match the backup");
+            return;
+        }
+        if !self.confirm_import(&preview).await {
+            return;
+        }
+        
+        // RustBlue's own settings are applied either way, so a failure is only partial. They are
+        // kept by address, for devices BlueZ does not know they apply once they are paired again.
+        let restored = backup::restore(&backup, &preview, &manager).await;
+        let addresses = preview.devices.iter().map(|(address, _, _)| address).chain(&preview.unknown);
+        for address in addresses {
+            if let Some(device_backup) = backup.devices.get(address) {
+                self.update_device_settings(address, device_backup.settings.clone());
+            }
+        }
+        
+        let imp = self.imp();
+        if let Some(device_details) = imp.device_details.borrow().as_ref() {
+            if let Some(address) = device_details.address() {
+                device_details.set_settings(&imp.settings.borrow().device(&address));
+            }
+        }
+        self.refresh_device_list().await;
+        let skipped = match preview.unknown.len() {
+            0 => String::new(),
+            count => format!(", {} devices not known to BlueZ were skipped but keep RustBlue's settings", count),
+        };
+        match restored {
+            Ok(()) => self.show_info_message(&format!("Restored settings of {} devices{}", preview.devices.len(), skipped)),
+            Err(e) => self.show_error_message(&format!("Restored device settings only partly: {}{}", e, skipped)),
+        }
+    }
+    
+    /// List the changes of an import, resolving to whether to go ahead.
+    async fn confirm_import(&self, preview: &Preview) -> bool {
+        let mut lines = Vec::new();
+        for (address, name, changes) in &preview.devices {
+            lines.push(format!("{} ({})", name, address));
+            for change in changes {
+                lines.push(format!("    {}: {} → {}", change.field.name(), change.from, change.to));
+            }
+        }
+        if !preview.unknown.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("Not known to BlueZ, only RustBlue's settings are restored: {}", preview.unknown.join(", ")));
+        }
+        
+        let dialog = adw::MessageDialog::new(
+            Some(self),
+            Some("Import Device Settings?"),
+            Some(&format!("{} devices will change. Pair the others first to restore their name and trust too.", preview.devices.len())),
+        );
+        let changes_label = Label::new(Some(&lines.join("\n")));
+        changes_label.set_halign(gtk::Align::Start);
+        changes_label.set_selectable(true);
+        let changes_scroll = ScrolledWindow::new();
+        changes_scroll.set_child(Some(&changes_label));
+        changes_scroll.set_min_content_height(120);
+        changes_scroll.set_max_content_height(360);
+        changes_scroll.set_propagate_natural_height(true);
+        dialog.set_extra_child(Some(&changes_scroll));
+        
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("import", "Import");
+        dialog.set_response_appearance("import", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("import"));
+        dialog.set_close_response("cancel");
+        
+        let (sender, receiver) = oneshot::channel();
+        let sender = std::cell::RefCell::new(Some(sender));
+        dialog.connect_response(None, move |_, response| {
+            if let Some(sender) = sender.take() {
+                let _ = sender.send(response == "import");
+            }
+        });
+        dialog.present();
+        
+        receiver.await.unwrap_or(false)
+    }
+    
+    /// Pick the `SYSTEM` hive of a Windows installation to import pairing keys from.
+    fn choose_windows_hive(&self) {
+        let file_chooser = FileChooserNative::new(
+            Some("Import Pairing Keys From Windows"),
+            Some(self),
+            FileChooserAction::Open,
+            Some("Open"),
+            Some("Cancel"),
+        );
+        
+        let window_weak = self.downgrade();
+        file_chooser.connect_response(move |file_chooser, response| {
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            if response == ResponseType::Accept {
+                if let Some(path) = file_chooser.file().and_then(|file| file.path()) {
+                    let window = window.clone();
+                    glib::spawn_future_local(async move {
+                        window.import_windows_keys(path).await;
+                    });
+                }
+            }
+            window.imp().hive_chooser.replace(None);
+        });
+        
+        file_chooser.show();
+        self.imp().hive_chooser.replace(Some(file_chooser));
+    }
+    
+    /// Read the pairing keys in the hive at `path`, let the user pick the devices and hand
+    /// their keys to the pairing helper.
+    async fn import_windows_keys(&self, path: PathBuf) {
+        let manager = match self.imp().bluetooth_manager.borrow().as_ref() {
+            Some(manager) => manager.clone(),
+            None => return,
+        };
+        
+        // The hive is tens of megabytes
+        let hive_path = path.clone();
+        let keys = tokio::task::spawn_blocking(move || dualboot::keys::read_keys(&hive_path)).await
+            .unwrap_or_else(|e| Err(e.into()));
+        let keys = match keys {
+            Ok(keys) => keys,
+            Err(e) => {
+                self.show_error_message(&format!("Failed to read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        
+        // Keys only work with the adapter they were made with, which both systems share
+        let adapters = manager.adapter_addresses().await;
+        let (keys, foreign): (Vec<DeviceKeys>, Vec<DeviceKeys>) = keys.into_iter()
+            .partition(|device| adapters.contains(&device.adapter));
+        if keys.is_empty() {
+            if foreign.is_empty() {
+                self.show_info_message("Windows has no devices paired");
+            } else {
+                self.show_error_message("The devices paired in Windows belong to another adapter");
+            }
+            return;
+        }
+        
+        let names: HashMap<String, String> = manager.get_devices().await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|device| (device.address, device.name))
+            .collect();
+        let selected = self.choose_windows_devices(&keys, &names, foreign.len()).await;
+        if selected.is_empty() {
+            return;
+        }
+        
+        let updates: Vec<_> = selected.iter().map(|device| device.info_update()).collect();
+        match dualboot::install(&updates).await {
+            Ok(()) => {
+                self.show_info_message(&format!("Imported pairing keys of {} devices", updates.len()));
+                self.refresh_device_list().await;
+            }
+            Err(e) => self.show_error_message(&format!("Failed to import pairing keys: {}", e)),
+        }
+    }
+    
+    /// List the devices paired in Windows, resolving to those the user keeps checked. Devices
+    /// BlueZ knows as well are checked to begin with, they are the ones that need new keys.
+    async fn choose_windows_devices(&self, keys: &[DeviceKeys], names: &HashMap<String, String>, foreign: usize) -> Vec<DeviceKeys> {
+        let devices_box = GtkBox::new(Orientation::Vertical, 6);
+        let check_buttons: Vec<CheckButton> = keys.iter()
+            .map(|device| {
+                let name = names.get(&device.address).map(String::as_str).unwrap_or("Unknown Device");
+                let check_button = CheckButton::with_label(&format!("{} ({}, {})", name, device.address, device.kind()));
+                check_button.set_active(names.contains_key(&device.address));
+                devices_box.append(&check_button);
+                check_button
+            })
+            .collect();
+        if foreign > 0 {
+            let foreign_label = Label::new(Some(&format!("Skipped {} devices paired with other adapters", foreign)));
+            foreign_label.set_halign(gtk::Align::Start);
+            foreign_label.add_css_class("dim-label");
+            devices_box.append(&foreign_label);
+        }
+        let devices_scroll = ScrolledWindow::new();
+        devices_scroll.set_child(Some(&devices_box));
+        devices_scroll.set_min_content_height(120);
+        devices_scroll.set_max_content_height(360);
+        devices_scroll.set_propagate_natural_height(true);
+        
+        let dialog = adw::MessageDialog::new(
+            Some(self),
+            Some("Import Pairing Keys?"),
+            Some("Devices paired in Windows will connect here without pairing again. Bluetooth restarts to load the keys."),
+        );
+        dialog.set_extra_child(Some(&devices_scroll));
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("import", "Import");
+        dialog.set_response_appearance("import", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("import"));
+        dialog.set_close_response("cancel");
+        
+        let (sender, receiver) = oneshot::channel();
+        let sender = std::cell::RefCell::new(Some(sender));
+        dialog.connect_response(None, move |_, response| {
+            if let Some(sender) = sender.take() {
+                let _ = sender.send(response == "import");
+            }
+        });
+        dialog.present();
+        
+        if !receiver.await.unwrap_or(false) {
+            return Vec::new();
+        }
+        keys.iter()
+            .zip(&check_buttons)
+            .filter(|(_, check_button)| check_button.is_active())
+            .map(|(device, _)| device.clone())
+            .collect()
+    }
+    
+    /// Ask whether to accept a file, resolving to `false` if the prompt is dismissed.
+    async fn ask_to_receive(&self, device_name: &str, file_name: &str, size: Option<u64>) -> bool {
+        let size = size.map(|size| format!(" ({})", glib::format_size(size))).unwrap_or_default();
+        let dialog = adw::MessageDialog::new(
+            Some(self),
+            Some("Incoming File"),
+            Some(&format!("{} wants to send you “{}”{}.", device_name, file_name, size)),
+        );
+        dialog.add_response("reject", "Reject");
+        dialog.add_response("accept", "Accept");
+        dialog.set_response_appearance("accept", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("accept"));
+        dialog.set_close_response("reject");
+        
+        let (sender, receiver) = oneshot::channel();
+        let sender = std::cell::RefCell::new(Some(sender));
+        dialog.connect_response(None, move |_, response| {
+            if let Some(sender) = sender.take() {
+                let _ = sender.send(response == "accept");
+            }
+        });
+        
+        dialog.present();
+        self.imp().incoming_dialog.replace(Some(dialog));
+        
+        let accepted = receiver.await.unwrap_or(false);
+        self.imp().incoming_dialog.replace(None);
+        accepted
+    }
+    
+    fn notify_file_received(&self, device_name: &str, destination: &std::path::Path) {
+        let application = match self.application() {
+            Some(application) => application,
+            None => return,
+        };
+        
+        let notification = gio::Notification::new("File Received");
+        notification.set_body(Some(&format!("Saved {} from {}", destination.display(), device_name)));
+        application.send_notification(None, &notification);
+    }
+    
+    async fn cancel_transfer(&self, id: String) {
+        let transfer = self.imp().transfers.borrow().get(&id).cloned();
+        
+        if let Some(transfer) = transfer {
+            if let Err(e) = transfer.cancel().await {
+                log::error!("Failed to cancel transfer {}: {}", id, e);
+                self.show_error_message(&format!("Failed to cancel transfer: {}", e));
+            }
+        }
+    }
+    
+    async fn refresh_device_list(&self) {
+        log::info!("Refreshing device list");
+        let imp = self.imp();
+        
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            match manager.get_devices().await {
+                Ok(devices) => {
+                    log::info!("Refreshed device list with {} devices", devices.len());
+                    self.update_device_list(devices);
+                }
+                Err(e) => {
+                    log::error!("Failed to refresh device list: {}", e);
+                }
+            }
+        }
+    }
+    
+    async fn toggle_bluetooth(&self, enabled: bool) {
+        log::info!("Toggling Bluetooth: {}", if enabled { "ON" } else { "OFF" });
+        let imp = self.imp();
+        
+        if let Some(manager) = imp.bluetooth_manager.borrow().as_ref() {
+            match manager.set_adapter_powered(enabled).await {
+                Ok(()) => {
+                    log::info!("Successfully {} Bluetooth", if enabled { "enabled" } else { "disabled" });
+                    if enabled {
+                        self.show_info_message("Bluetooth enabled");
+                        // Automatically scan for devices when Bluetooth is turned on,
+                        // after a resume that waits until the adapters are restored
+                        if !imp.sleeping.get() {
+                            self.start_device_scan().await;
+                        }
+                    } else {
+                        self.show_info_message("Bluetooth disabled");
+                        // Clear device list when Bluetooth is turned off
+                        self.update_device_list(vec![]);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Failed to {} Bluetooth: {}", if enabled { "enable" } else { "disable" }, e);
+                    self.show_error_message(&format!("Failed to {} Bluetooth: {}", if enabled { "enable" } else { "disable" }, e));
+                    
+                    // Revert the toggle state on error
+                    if let Some(toggle) = imp.bluetooth_toggle.borrow().as_ref() {
+                        toggle.set_active(!enabled);
+                    }
+                }
+            }
+        } else {
+            log::warn!("Bluetooth manager not initialized");
+            self.show_error_message("Bluetooth manager not initialized");
+            
+            // Revert the toggle state on error
+            if let Some(toggle) = imp.bluetooth_toggle.borrow().as_ref() {
+                toggle.set_active(!enabled);
+            }
+        }
+    }
+    
+    /// Whether the header bar switch shows Bluetooth as on.
+    fn is_powered_on(&self) -> bool {
+        self.imp().bluetooth_toggle.borrow().as_ref().is_some_and(|toggle| toggle.is_active())
+    }
+    
+    /// Stop discovering while nobody looks at the list, it drains power and the air.
+    async fn stop_device_scan(&self) {
+        let imp = self.imp();
+        self.stop_auto_scan();
+        if let Some(device_list) = imp.device_list.borrow().as_ref() {
+            device_list.set_discovering(false);
+        }
+        
+        let manager = imp.bluetooth_manager.borrow().clone();
+        if let Some(manager) = manager {
+            if let Err(e) = manager.stop_discovery(DiscoveryOwner::Window).await {
+                log::warn!("Failed to stop device discovery: {}", e);
             }
         }
     }